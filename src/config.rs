@@ -0,0 +1,787 @@
+//! User configuration for sdiff.
+//!
+//! `Settings` holds every option that affects how a diff is computed, filtered
+//! and displayed. Settings can be read from a TOML file, from `git config`
+//! (`sdiff.*` keys) and from `SDIFF_*` environment variables, and are layered
+//! with [`Settings::merge`] so that later sources override earlier ones.
+//!
+//! Configuration files are found by [`ConfigFiles::discover`]: a user-level
+//! file (`$XDG_CONFIG_HOME/sdiff/config.toml`, or
+//! `~/.config/sdiff/config.toml`) and the nearest `.sdiff.toml` in the current
+//! directory or one of its parents. Besides top-level settings, a file can
+//! define named profiles and settings for files matching a glob:
+//!
+//! ```toml
+//! array-strategy = "lcs"
+//!
+//! [profiles.k8s]
+//! ignore = ["metadata.managedFields", "status.**"]
+//!
+//! [[files]]
+//! pattern = "*.toml"
+//! null-as-missing = true
+//!
+//! [[rules]]
+//! path = "spec.ports"
+//! array-key = "port"
+//! ```
+//!
+//! # Keys
+//!
+//! | File (`.sdiff.toml`)  | git config             | Environment              |
+//! |-----------------------|------------------------|--------------------------|
+//! | `format`              | `sdiff.format`         | `SDIFF_FORMAT`           |
+//! | `compact`             | `sdiff.compact`        | `SDIFF_COMPACT`          |
+//! | `show-values`         | `sdiff.showValues`     | `SDIFF_SHOW_VALUES`      |
+//! | `max-value-length`    | `sdiff.maxValueLength` | `SDIFF_MAX_VALUE_LENGTH` |
+//! | `null-as-missing`     | `sdiff.nullAsMissing`  | `SDIFF_NULL_AS_MISSING`  |
+//! | `ignore-whitespace`   | `sdiff.ignoreWhitespace` | `SDIFF_IGNORE_WHITESPACE` |
+//! | `array-strategy`      | `sdiff.arrayStrategy`  | `SDIFF_ARRAY_STRATEGY`   |
+//! | `quiet`               | `sdiff.quiet`          | `SDIFF_QUIET`            |
+//! | `verbose`             | `sdiff.verbose`        | `SDIFF_VERBOSE`          |
+//! | `input-format`        | `sdiff.inputFormat`    | `SDIFF_INPUT_FORMAT`     |
+//! | `ignore` (list)       | `sdiff.ignore` (multi) | `SDIFF_IGNORE` (comma-separated) |
+//! | `only` (list)         | `sdiff.only` (multi)   | `SDIFF_ONLY` (comma-separated)   |
+//! | `case-insensitive-paths` | `sdiff.caseInsensitivePaths` | `SDIFF_CASE_INSENSITIVE_PATHS` |
+//! | `only-type` (list)    | `sdiff.onlyType` (multi) | `SDIFF_ONLY_TYPE` (comma-separated) |
+//! | `old-value` (list)    | `sdiff.oldValue` (multi) | `SDIFF_OLD_VALUE`        |
+//! | `new-value` (list)    | `sdiff.newValue` (multi) | `SDIFF_NEW_VALUE`        |
+//! | `ignore-file` (list)  | `sdiff.ignoreFile` (multi) | `SDIFF_IGNORE_FILE` (comma-separated) |
+//!
+//! The `only` patterns are evaluated before the `ignore` patterns, so an
+//! ignored path stays hidden even if an `only` pattern selects it. Within each
+//! list the last matching pattern wins, and `!pattern` makes an exception to
+//! the ones before it. Patterns given with `--ignore` and `--only` on the
+//! command line are evaluated after these, in the order they were given.
+//!
+//! # Ignore files
+//!
+//! A `.sdiffignore` file lists one ignore pattern per line, in the same
+//! syntax as `ignore`. Blank lines and lines starting with `#` are skipped,
+//! and a leading `!` re-includes paths ignored by an earlier line:
+//!
+//! ```text
+//! # Generated by the cluster
+//! status.**
+//! metadata.**
+//! !metadata.labels.**
+//! ```
+//!
+//! See [`Settings::load_ignore_files`] for where ignore files are found.
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::config::Settings;
+//! use sdiff_rs::ArrayDiffStrategy;
+//!
+//! let mut settings = Settings::from_toml("array-strategy = \"positional\"").unwrap();
+//! settings.merge(Settings::from_toml("array-strategy = \"lcs\"").unwrap());
+//!
+//! assert_eq!(settings.diff_config().array_diff_strategy, ArrayDiffStrategy::Lcs);
+//! ```
+
+use crate::diff::{ArrayDiffStrategy, ChangeType, DiffConfig, RuleSet};
+use crate::error::SdiffError;
+use crate::filter::{glob_match, FilterAction, FilterConfig, PathPattern, ValuePredicate};
+use crate::git;
+use crate::output::{OutputFormat, OutputOptions};
+use crate::parser::FormatHint;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file.
+pub const CONFIG_FILE_NAME: &str = ".sdiff.toml";
+
+/// Name of the user configuration file, inside the `sdiff` config directory.
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Name of the ignore file, found next to the compared files or at the
+/// repository root.
+pub const IGNORE_FILE_NAME: &str = ".sdiffignore";
+
+/// Names of all options, as used in configuration files.
+const OPTION_NAMES: &[&str] = &[
+    "format",
+    "compact",
+    "show-values",
+    "max-value-length",
+    "null-as-missing",
+    "ignore-whitespace",
+    "array-strategy",
+    "quiet",
+    "verbose",
+    "input-format",
+    "ignore",
+    "only",
+    "case-insensitive-paths",
+    "only-type",
+    "old-value",
+    "new-value",
+    "ignore-file",
+];
+
+/// Options that control diffing, filtering and output.
+///
+/// Unset options fall back to the library defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// Output format
+    pub format: Option<OutputFormat>,
+    /// Hide unchanged fields
+    pub compact: Option<bool>,
+    /// Show full values instead of previews
+    pub show_values: Option<bool>,
+    /// Maximum length for displayed values
+    pub max_value_length: Option<usize>,
+    /// Treat null values as missing keys
+    pub null_as_missing: Option<bool>,
+    /// Ignore whitespace differences in strings
+    pub ignore_whitespace: Option<bool>,
+    /// Array comparison strategy
+    pub array_strategy: Option<ArrayDiffStrategy>,
+    /// Suppress the summary line
+    pub quiet: Option<bool>,
+    /// Report progress on stderr
+    pub verbose: Option<bool>,
+    /// Input format for stdin
+    pub input_format: Option<FormatHint>,
+    /// Patterns for paths to ignore
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Patterns for paths to include
+    #[serde(default)]
+    pub only: Vec<String>,
+    /// Ignore and only patterns in the order they were given, evaluated
+    /// after `only` and `ignore`. Only the command line can order them, so
+    /// they are not read from or written to configuration files.
+    #[serde(skip)]
+    pub patterns: Vec<(FilterAction, String)>,
+    /// Match keys in `ignore` and `only` patterns case-insensitively
+    pub case_insensitive_paths: Option<bool>,
+    /// Change types to show (`added`, `removed`, `modified`)
+    #[serde(default)]
+    pub only_type: Vec<String>,
+    /// Predicates the old value of a change must satisfy
+    #[serde(default)]
+    pub old_value: Vec<String>,
+    /// Predicates the new value of a change must satisfy
+    #[serde(default)]
+    pub new_value: Vec<String>,
+    /// Ignore files to read patterns from, relative to the configuration
+    /// file that lists them (or the current directory, for other sources)
+    #[serde(default)]
+    pub ignore_file: Vec<String>,
+    /// Per-path comparison rules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<PathRule>,
+}
+
+/// Comparison rules for the paths matching a pattern (`[[rules]]`).
+///
+/// See [`RuleSet`] for the meaning of each option.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PathRule {
+    /// Path pattern the rules apply to, including nested paths
+    pub path: String,
+    pub ignore: Option<bool>,
+    pub array_strategy: Option<ArrayDiffStrategy>,
+    pub array_key: Option<String>,
+    pub case_insensitive: Option<bool>,
+    pub numeric_tolerance: Option<f64>,
+    pub ignore_whitespace: Option<bool>,
+}
+
+impl PathRule {
+    /// Returns the rule set described by this rule.
+    pub fn rule_set(&self) -> RuleSet {
+        RuleSet {
+            ignore: self.ignore,
+            array_strategy: self.array_strategy,
+            array_key: self.array_key.clone(),
+            case_insensitive: self.case_insensitive,
+            numeric_tolerance: self.numeric_tolerance,
+            ignore_whitespace: self.ignore_whitespace,
+        }
+    }
+}
+
+impl Settings {
+    /// Parses settings from TOML content.
+    pub fn from_toml(content: &str) -> Result<Self, SdiffError> {
+        toml::from_str(content).map_err(|e| SdiffError::ConfigError {
+            message: e.to_string(),
+        })
+    }
+
+    /// Reads settings from a TOML file.
+    pub fn from_file(path: &Path) -> Result<Self, SdiffError> {
+        let content = fs::read_to_string(path).map_err(|e| SdiffError::ConfigError {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+        Self::from_toml(&content).map_err(|e| SdiffError::ConfigError {
+            message: format!("{}: {}", path.display(), e),
+        })
+    }
+
+    /// Reads settings from `SDIFF_*` environment variables.
+    pub fn from_env() -> Result<Self, SdiffError> {
+        let mut settings = Self::default();
+        for key in OPTION_NAMES {
+            let name = format!("SDIFF_{}", key.replace('-', "_").to_uppercase());
+            let Ok(value) = env::var(&name) else {
+                continue;
+            };
+
+            if is_list_key(key) {
+                for item in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    settings.set(&name, item)?;
+                }
+            } else {
+                settings.set(&name, &value)?;
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Reads settings from `sdiff.*` keys in the effective git configuration.
+    pub fn from_git_config() -> Result<Self, SdiffError> {
+        let mut settings = Self::default();
+        for (key, value) in git::get_git_config_section("sdiff")? {
+            settings.set(&key, &value)?;
+        }
+        Ok(settings)
+    }
+
+    /// Loads the settings used when sdiff runs as a git diff or merge driver
+    /// on `path`, relative to the repository root.
+    ///
+    /// Sources are applied in order, each overriding the previous one: the
+    /// configuration files found by [`ConfigFiles::discover`] from the
+    /// repository root (including the profile named by `SDIFF_PROFILE` and
+    /// the `[[files]]` rules matching `path`), `git config sdiff.*`, and
+    /// `SDIFF_*` environment variables. Patterns from the `.sdiffignore`
+    /// files that apply to `path` are added too. Fails if a pattern, change
+    /// type or value predicate does not parse, as on the command line.
+    pub fn for_git_driver(path: &str) -> Result<Self, SdiffError> {
+        let root = match git::repo_root() {
+            Ok(root) => root,
+            Err(_) => env::current_dir().map_err(config_error)?,
+        };
+        let profile = env::var("SDIFF_PROFILE").ok().filter(|p| !p.is_empty());
+
+        let mut settings = ConfigFiles::discover(&root)?.settings(profile.as_deref(), &[path])?;
+        settings.merge(Self::from_git_config()?);
+        settings.merge(Self::from_env()?);
+        // The root is listed too, in case the file's directory was deleted
+        settings
+            .load_ignore_files(&[&root.to_string_lossy(), &root.join(path).to_string_lossy()])?;
+        settings.validate_filters()?;
+        Ok(settings)
+    }
+
+    /// Sets a single option by name.
+    ///
+    /// Names are matched case-insensitively, ignoring `-` and `_`, so
+    /// `array-strategy`, `arrayStrategy` and `ARRAY_STRATEGY` are equivalent.
+    /// List options (`ignore`, `only`, `only-type`, `old-value`,
+    /// `new-value`, `ignore-file`) append the value.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), SdiffError> {
+        let name = key.strip_prefix("SDIFF_").unwrap_or(key);
+        match normalize_key(name).as_str() {
+            "format" => self.format = Some(parse_enum(key, value)?),
+            "compact" => self.compact = Some(parse_bool(key, value)?),
+            "showvalues" => self.show_values = Some(parse_bool(key, value)?),
+            "maxvaluelength" => {
+                self.max_value_length = Some(value.trim().parse().map_err(|_| invalid(key, value))?)
+            }
+            "nullasmissing" => self.null_as_missing = Some(parse_bool(key, value)?),
+            "ignorewhitespace" => self.ignore_whitespace = Some(parse_bool(key, value)?),
+            "arraystrategy" => self.array_strategy = Some(parse_enum(key, value)?),
+            "quiet" => self.quiet = Some(parse_bool(key, value)?),
+            "verbose" => self.verbose = Some(parse_bool(key, value)?),
+            "inputformat" => self.input_format = Some(parse_enum(key, value)?),
+            "ignore" => self.ignore.push(value.to_string()),
+            "only" => self.only.push(value.to_string()),
+            "caseinsensitivepaths" => self.case_insensitive_paths = Some(parse_bool(key, value)?),
+            "onlytype" => self.only_type.push(value.to_string()),
+            "oldvalue" => self.old_value.push(value.to_string()),
+            "newvalue" => self.new_value.push(value.to_string()),
+            "ignorefile" => self.ignore_file.push(value.to_string()),
+            _ => {
+                return Err(SdiffError::ConfigError {
+                    message: format!("Unknown option '{}'", key),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Overrides these settings with every option set in `other`.
+    ///
+    /// Pattern and rule lists are concatenated rather than replaced.
+    pub fn merge(&mut self, other: Settings) {
+        self.format = other.format.or(self.format);
+        self.compact = other.compact.or(self.compact);
+        self.show_values = other.show_values.or(self.show_values);
+        self.max_value_length = other.max_value_length.or(self.max_value_length);
+        self.null_as_missing = other.null_as_missing.or(self.null_as_missing);
+        self.ignore_whitespace = other.ignore_whitespace.or(self.ignore_whitespace);
+        self.array_strategy = other.array_strategy.or(self.array_strategy);
+        self.quiet = other.quiet.or(self.quiet);
+        self.verbose = other.verbose.or(self.verbose);
+        self.input_format = other.input_format.or(self.input_format);
+        self.ignore.extend(other.ignore);
+        self.only.extend(other.only);
+        self.patterns.extend(other.patterns);
+        self.case_insensitive_paths = other.case_insensitive_paths.or(self.case_insensitive_paths);
+        self.only_type.extend(other.only_type);
+        self.old_value.extend(other.old_value);
+        self.new_value.extend(other.new_value);
+        self.ignore_file.extend(other.ignore_file);
+        self.rules.extend(other.rules);
+    }
+
+    /// Builds the diff algorithm configuration.
+    pub fn diff_config(&self) -> DiffConfig {
+        let defaults = DiffConfig::default();
+        DiffConfig {
+            ignore_whitespace: self.ignore_whitespace.unwrap_or(defaults.ignore_whitespace),
+            treat_null_as_missing: self
+                .null_as_missing
+                .unwrap_or(defaults.treat_null_as_missing),
+            array_diff_strategy: self.array_strategy.unwrap_or(defaults.array_diff_strategy),
+            rules: self
+                .rules
+                .iter()
+                .map(|rule| (PathPattern::parse(&rule.path), rule.rule_set()))
+                .collect(),
+        }
+    }
+
+    /// Builds the path filter configuration.
+    ///
+    /// Every `only` pattern is added before every `ignore` pattern; within
+    /// each list the order is kept. The ordered `patterns` come last, so the
+    /// command line can re-include a path the configuration ignores.
+    ///
+    /// Fails on an unknown change type or a malformed value predicate.
+    pub fn filter_config(&self) -> Result<FilterConfig, SdiffError> {
+        let mut filter =
+            FilterConfig::new().case_insensitive(self.case_insensitive_paths.unwrap_or(false));
+        // Only rules come first so that ignore rules, evaluated later, win
+        // over them; each list keeps its own order for `!` exceptions.
+        for pattern in &self.only {
+            filter = filter.only(pattern);
+        }
+        for pattern in &self.ignore {
+            filter = filter.ignore(pattern);
+        }
+        for (action, pattern) in &self.patterns {
+            filter = match action {
+                FilterAction::Ignore => filter.ignore(pattern),
+                FilterAction::Only => filter.only(pattern),
+            };
+        }
+        for change_type in &self.only_type {
+            filter = filter.only_type(change_type.parse()?);
+        }
+        for predicate in &self.old_value {
+            filter = filter.old_value(ValuePredicate::parse(predicate)?);
+        }
+        for predicate in &self.new_value {
+            filter = filter.new_value(ValuePredicate::parse(predicate)?);
+        }
+        Ok(filter)
+    }
+
+    /// Adds the patterns of every ignore file that applies to comparing
+    /// `paths`: the `.sdiffignore` at the repository root, those next to each
+    /// compared file (or inside each compared directory), then the files
+    /// listed in `ignore-file`.
+    ///
+    /// The patterns are placed before the configured `ignore` patterns, so
+    /// the command line and configuration files can override them.
+    pub fn load_ignore_files(&mut self, paths: &[&str]) -> Result<(), SdiffError> {
+        let mut files = find_ignore_files(paths);
+        files.extend(self.ignore_file.iter().map(PathBuf::from));
+
+        let mut patterns = Vec::new();
+        for file in files {
+            patterns.extend(read_ignore_file(&file)?);
+        }
+        patterns.append(&mut self.ignore);
+        self.ignore = patterns;
+        Ok(())
+    }
+
+    /// Checks that every pattern, change type and value predicate parses.
+    ///
+    /// [`Settings::filter_config`] reads malformed patterns as literal keys.
+    pub fn validate_filters(&self) -> Result<(), SdiffError> {
+        let ordered = self.patterns.iter().map(|(_, pattern)| pattern);
+        for pattern in self.ignore.iter().chain(&self.only).chain(ordered) {
+            PathPattern::try_parse(pattern)?;
+        }
+        for change_type in &self.only_type {
+            change_type.parse::<ChangeType>()?;
+        }
+        for predicate in self.old_value.iter().chain(&self.new_value) {
+            ValuePredicate::parse(predicate)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the output options.
+    pub fn output_options(&self) -> OutputOptions {
+        let defaults = OutputOptions::default();
+        OutputOptions {
+            compact: self.compact.unwrap_or(defaults.compact),
+            show_values: self.show_values.unwrap_or(defaults.show_values),
+            max_value_length: self.max_value_length.unwrap_or(defaults.max_value_length),
+            context_lines: defaults.context_lines,
+        }
+    }
+
+    /// Returns the output format, defaulting to terminal output.
+    pub fn output_format(&self) -> OutputFormat {
+        self.format.unwrap_or(OutputFormat::Terminal)
+    }
+
+    /// Returns a copy with every unset option replaced by its default.
+    ///
+    /// The ordered `patterns` are moved to the end of `ignore` and `only`,
+    /// since configuration files cannot record their order.
+    pub fn resolved(&self) -> Settings {
+        let diff = self.diff_config();
+        let output = self.output_options();
+        let (mut ignore, mut only) = (self.ignore.clone(), self.only.clone());
+        for (action, pattern) in &self.patterns {
+            match action {
+                FilterAction::Ignore => ignore.push(pattern.clone()),
+                FilterAction::Only => only.push(pattern.clone()),
+            }
+        }
+        Settings {
+            format: Some(self.output_format()),
+            compact: Some(output.compact),
+            show_values: Some(output.show_values),
+            max_value_length: Some(output.max_value_length),
+            null_as_missing: Some(diff.treat_null_as_missing),
+            ignore_whitespace: Some(diff.ignore_whitespace),
+            array_strategy: Some(diff.array_diff_strategy),
+            quiet: Some(self.quiet.unwrap_or(false)),
+            verbose: Some(self.verbose.unwrap_or(false)),
+            input_format: Some(self.input_format.unwrap_or_default()),
+            ignore,
+            only,
+            patterns: Vec::new(),
+            case_insensitive_paths: Some(self.case_insensitive_paths.unwrap_or(false)),
+            only_type: self.only_type.clone(),
+            old_value: self.old_value.clone(),
+            new_value: self.new_value.clone(),
+            ignore_file: self.ignore_file.clone(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    /// Serializes the settings as TOML, in the configuration file format.
+    pub fn to_toml(&self) -> Result<String, SdiffError> {
+        toml::to_string(self).map_err(|e| SdiffError::ConfigError {
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Settings that apply to files matching a glob.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRule {
+    /// Glob matched against the compared file paths (see `filter::glob_match`)
+    pub pattern: String,
+    /// Settings for matching files
+    pub settings: Settings,
+}
+
+/// The contents of a configuration file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+    /// Top-level settings
+    pub settings: Settings,
+    /// Named profiles, selected with `--profile`
+    pub profiles: BTreeMap<String, Settings>,
+    /// Settings for specific files, applied in order
+    pub files: Vec<FileRule>,
+}
+
+impl ConfigFile {
+    /// Parses a configuration file from TOML content.
+    pub fn from_toml(content: &str) -> Result<Self, SdiffError> {
+        let mut table: toml::Table = toml::from_str(content).map_err(config_error)?;
+
+        let mut profiles = BTreeMap::new();
+        if let Some(value) = table.remove("profiles") {
+            let toml::Value::Table(entries) = value else {
+                return Err(SdiffError::ConfigError {
+                    message: "'profiles' must be a table".to_string(),
+                });
+            };
+            for (name, value) in entries {
+                let settings =
+                    Settings::deserialize(value).map_err(|e| SdiffError::ConfigError {
+                        message: format!("profile '{}': {}", name, e),
+                    })?;
+                profiles.insert(name, settings);
+            }
+        }
+
+        let mut files = Vec::new();
+        if let Some(value) = table.remove("files") {
+            let toml::Value::Array(entries) = value else {
+                return Err(SdiffError::ConfigError {
+                    message: "'files' must be an array of tables".to_string(),
+                });
+            };
+            for entry in entries {
+                let toml::Value::Table(mut entry) = entry else {
+                    return Err(SdiffError::ConfigError {
+                        message: "'files' must be an array of tables".to_string(),
+                    });
+                };
+                let Some(toml::Value::String(pattern)) = entry.remove("pattern") else {
+                    return Err(SdiffError::ConfigError {
+                        message: "every [[files]] entry needs a 'pattern' string".to_string(),
+                    });
+                };
+                let settings = Settings::deserialize(toml::Value::Table(entry)).map_err(|e| {
+                    SdiffError::ConfigError {
+                        message: format!("files '{}': {}", pattern, e),
+                    }
+                })?;
+                files.push(FileRule { pattern, settings });
+            }
+        }
+
+        let settings = Settings::deserialize(toml::Value::Table(table)).map_err(config_error)?;
+        Ok(Self {
+            settings,
+            profiles,
+            files,
+        })
+    }
+
+    /// Reads a configuration file.
+    ///
+    /// Relative `ignore-file` entries are resolved against the directory of
+    /// the file.
+    pub fn from_file(path: &Path) -> Result<Self, SdiffError> {
+        let content = fs::read_to_string(path).map_err(|e| SdiffError::ConfigError {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+        let mut file = Self::from_toml(&content).map_err(|e| SdiffError::ConfigError {
+            message: format!("{}: {}", path.display(), e),
+        })?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let layers = std::iter::once(&mut file.settings)
+            .chain(file.profiles.values_mut())
+            .chain(file.files.iter_mut().map(|rule| &mut rule.settings));
+        for settings in layers {
+            for ignore_file in &mut settings.ignore_file {
+                *ignore_file = dir.join(&*ignore_file).to_string_lossy().into_owned();
+            }
+        }
+        Ok(file)
+    }
+}
+
+/// The configuration files that apply to an invocation, lowest precedence
+/// first.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFiles {
+    pub files: Vec<(PathBuf, ConfigFile)>,
+}
+
+impl ConfigFiles {
+    /// Loads the user configuration file and the nearest `.sdiff.toml` in
+    /// `dir` or one of its parents, if they exist.
+    pub fn discover(dir: &Path) -> Result<Self, SdiffError> {
+        let mut paths = Vec::new();
+        if let Some(path) = user_config_path().filter(|p| p.is_file()) {
+            paths.push(path);
+        }
+        if let Some(path) = find_project_config(dir) {
+            paths.push(path);
+        }
+
+        let mut files = Vec::new();
+        for path in paths {
+            let file = ConfigFile::from_file(&path)?;
+            files.push((path, file));
+        }
+        Ok(Self { files })
+    }
+
+    /// Combines the loaded files into settings for comparing `paths`.
+    ///
+    /// Top-level settings come first, then the selected profile, then every
+    /// `[[files]]` rule whose pattern matches one of `paths`. Within each
+    /// layer, the project file overrides the user file.
+    pub fn settings(&self, profile: Option<&str>, paths: &[&str]) -> Result<Settings, SdiffError> {
+        let mut settings = Settings::default();
+        for (_, file) in &self.files {
+            settings.merge(file.settings.clone());
+        }
+
+        if let Some(name) = profile {
+            let mut found = false;
+            for (_, file) in &self.files {
+                if let Some(profile) = file.profiles.get(name) {
+                    settings.merge(profile.clone());
+                    found = true;
+                }
+            }
+            if !found {
+                return Err(SdiffError::ConfigError {
+                    message: format!("Unknown profile '{}'", name),
+                });
+            }
+        }
+
+        for (_, file) in &self.files {
+            for rule in &file.files {
+                let matches = paths.iter().any(|path| {
+                    let path = path.trim_start_matches("./").replace('\\', "/");
+                    glob_match(&rule.pattern, &path)
+                });
+                if matches {
+                    settings.merge(rule.settings.clone());
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Returns the nearest `.sdiff.toml` in `dir` or one of its parents.
+pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Returns the `.sdiffignore` files that apply to comparing `paths`, in the
+/// order their patterns are applied: the root of each repository holding a
+/// compared path first (or the current directory's, without paths), then
+/// the directory of each file (or each directory itself).
+pub fn find_ignore_files(paths: &[&str]) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            }
+        };
+        dirs.push(dir.to_path_buf());
+    }
+
+    let lookups = if dirs.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        dirs.clone()
+    };
+    let roots = lookups.iter().filter_map(|dir| git::repo_root_of(dir).ok());
+    let dirs: Vec<PathBuf> = roots.chain(dirs).collect();
+
+    let mut seen = Vec::new();
+    let mut files = Vec::new();
+    for file in dirs.iter().map(|dir| dir.join(IGNORE_FILE_NAME)) {
+        let Ok(canonical) = file.canonicalize() else {
+            continue;
+        };
+        if file.is_file() && !seen.contains(&canonical) {
+            seen.push(canonical);
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// Reads the patterns in an ignore file.
+pub fn read_ignore_file(path: &Path) -> Result<Vec<String>, SdiffError> {
+    let content = fs::read_to_string(path).map_err(|e| SdiffError::ConfigError {
+        message: format!("Failed to read {}: {}", path.display(), e),
+    })?;
+    Ok(parse_ignore_file(&content))
+}
+
+/// Returns the patterns in the content of an ignore file, skipping blank
+/// lines and `#` comments.
+pub fn parse_ignore_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Returns the location of the user configuration file.
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("sdiff").join(USER_CONFIG_FILE_NAME))
+}
+
+fn config_error(e: impl std::fmt::Display) -> SdiffError {
+    SdiffError::ConfigError {
+        message: e.to_string(),
+    }
+}
+
+fn is_list_key(key: &str) -> bool {
+    matches!(
+        normalize_key(key).as_str(),
+        "ignore" | "only" | "onlytype" | "ignorefile"
+    )
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn invalid(key: &str, value: &str) -> SdiffError {
+    SdiffError::ConfigError {
+        message: format!("Invalid value '{}' for '{}'", value, key),
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, SdiffError> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(invalid(key, value)),
+    }
+}
+
+fn parse_enum<T: DeserializeOwned>(key: &str, value: &str) -> Result<T, SdiffError> {
+    let normalized = value.trim().to_lowercase();
+    T::deserialize(
+        IntoDeserializer::<serde::de::value::Error>::into_deserializer(normalized.as_str()),
+    )
+    .map_err(|_| invalid(key, value))
+}
@@ -23,12 +23,28 @@
 //!
 //! assert_eq!(diff.stats.modified, 1);
 //! ```
+//!
+//! # Serialization
+//!
+//! [`Diff`], [`DiffConfig`] and their parts implement serde's `Serialize`
+//! and `Deserialize`, using the field names of `--format json` output (see
+//! [`crate::output`]), so a diff can be stored or sent elsewhere and read
+//! back.
 
+use crate::error::{SdiffError, SerializeError};
+use crate::filter::{FilterConfig, HiddenBy, HiddenChanges, PathPattern};
+use crate::path::{Path, PathSegment};
+use crate::serializer::to_node;
 use crate::tree::Node;
-use std::collections::HashSet;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 /// The type of change that occurred.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ChangeType {
     /// Field exists in new but not old
     Added,
@@ -40,26 +56,67 @@ pub enum ChangeType {
     Unchanged,
 }
 
+impl FromStr for ChangeType {
+    type Err = SdiffError;
+
+    /// Parses a change type name (`added`, `removed`, `modified` or
+    /// `unchanged`), ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "added" => Ok(ChangeType::Added),
+            "removed" => Ok(ChangeType::Removed),
+            "modified" => Ok(ChangeType::Modified),
+            "unchanged" => Ok(ChangeType::Unchanged),
+            _ => Err(SdiffError::ConfigError {
+                message: format!(
+                    "Unknown change type '{}' (expected added, removed or modified)",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
 /// A single change in the diff.
 ///
 /// Each change represents a difference at a specific path in the tree structure.
-/// The path is represented as a vector of strings, where each string is either:
-/// - An object key (e.g., "user", "profile", "age")
-/// - An array index (e.g., "\[0\]", "\[1\]")
-#[derive(Debug, Clone)]
+///
+/// Serialized as `{"path", "type", "old_value", "new_value"}`, leaving out
+/// a value that is None.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Change {
-    /// Path to the changed value (e.g., ["user", "profile", "age"])
-    pub path: Vec<String>,
+    /// Path to the changed value (e.g., `user.profile.age`)
+    pub path: Path,
     /// Type of change
+    #[serde(rename = "type")]
     pub change_type: ChangeType,
     /// Old value (None for Added changes)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_value"
+    )]
     pub old_value: Option<Node>,
     /// New value (None for Removed changes)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_value"
+    )]
     pub new_value: Option<Node>,
 }
 
+/// Reads a value that is present, keeping a `null` as `Node::Null` rather
+/// than None.
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Node>, D::Error> {
+    Node::deserialize(deserializer).map(Some)
+}
+
 /// Statistics about the diff.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Serialized with an extra `total` field holding [`DiffStats::total_changes`],
+/// which is ignored when reading stats back.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct DiffStats {
     /// Number of added fields
     pub added: usize,
@@ -82,6 +139,20 @@ impl DiffStats {
         }
     }
 
+    /// Counts the changes of each type.
+    pub fn from_changes(changes: &[Change]) -> Self {
+        let mut stats = Self::new();
+        for change in changes {
+            match change.change_type {
+                ChangeType::Added => stats.added += 1,
+                ChangeType::Removed => stats.removed += 1,
+                ChangeType::Modified => stats.modified += 1,
+                ChangeType::Unchanged => stats.unchanged += 1,
+            }
+        }
+        stats
+    }
+
     /// Returns the total number of changes (excluding unchanged).
     pub fn total_changes(&self) -> usize {
         self.added + self.removed + self.modified
@@ -99,13 +170,28 @@ impl Default for DiffStats {
     }
 }
 
+impl Serialize for DiffStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut stats = serializer.serialize_struct("DiffStats", 5)?;
+        stats.serialize_field("added", &self.added)?;
+        stats.serialize_field("removed", &self.removed)?;
+        stats.serialize_field("modified", &self.modified)?;
+        stats.serialize_field("unchanged", &self.unchanged)?;
+        stats.serialize_field("total", &self.total_changes())?;
+        stats.end()
+    }
+}
+
 /// The complete diff result.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Diff {
     /// List of all changes
     pub changes: Vec<Change>,
     /// Summary statistics
     pub stats: DiffStats,
+    /// Changes left out by a filter, if one was applied
+    #[serde(default)]
+    pub hidden: HiddenChanges,
 }
 
 impl Diff {
@@ -114,6 +200,7 @@ impl Diff {
         Self {
             changes: Vec::new(),
             stats: DiffStats::new(),
+            hidden: HiddenChanges::default(),
         }
     }
 
@@ -130,7 +217,8 @@ impl Default for Diff {
 }
 
 /// Strategy for comparing arrays.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ArrayDiffStrategy {
     /// Compare arrays by index position (simple, fast)
     #[default]
@@ -139,17 +227,159 @@ pub enum ArrayDiffStrategy {
     Lcs,
 }
 
+/// How values at particular paths are compared.
+///
+/// A rule set applies to every path matched by its pattern and to everything
+/// nested below those paths. Unset options fall back to the global settings
+/// in `DiffConfig`.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::diff::RuleSet;
+/// use sdiff_rs::{compute_diff, parse_json, DiffConfig};
+///
+/// let config = DiffConfig::default()
+///     .rule("labels", RuleSet::new().case_insensitive(true))
+///     .rule("status.**", RuleSet::new().ignore(true));
+///
+/// let old = parse_json(r#"{"labels": {"tier": "Web"}, "status": {"ready": 1}}"#).unwrap();
+/// let new = parse_json(r#"{"labels": {"tier": "web"}, "status": {"ready": 3}}"#).unwrap();
+///
+/// assert!(compute_diff(&old, &new, &config).is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    /// Skip matching paths entirely
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<bool>,
+    /// Array comparison strategy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_strategy: Option<ArrayDiffStrategy>,
+    /// Pair array elements by the value of this field instead of by position.
+    /// The field must hold a string, number or boolean in every element;
+    /// strings are compared under this rule set's case and whitespace rules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_key: Option<String>,
+    /// Compare strings case-insensitively
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_insensitive: Option<bool>,
+    /// Treat numbers that differ by at most this much as equal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric_tolerance: Option<f64>,
+    /// Normalize whitespace in strings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_whitespace: Option<bool>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore(mut self, ignore: bool) -> Self {
+        self.ignore = Some(ignore);
+        self
+    }
+
+    pub fn array_strategy(mut self, strategy: ArrayDiffStrategy) -> Self {
+        self.array_strategy = Some(strategy);
+        self
+    }
+
+    pub fn array_key(mut self, field: &str) -> Self {
+        self.array_key = Some(field.to_string());
+        self
+    }
+
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = Some(case_insensitive);
+        self
+    }
+
+    pub fn numeric_tolerance(mut self, tolerance: f64) -> Self {
+        self.numeric_tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = Some(ignore_whitespace);
+        self
+    }
+
+    /// Overrides these rules with every option set in `other`.
+    pub fn merge(&mut self, other: &RuleSet) {
+        self.ignore = other.ignore.or(self.ignore);
+        self.array_strategy = other.array_strategy.or(self.array_strategy);
+        if other.array_key.is_some() {
+            self.array_key = other.array_key.clone();
+        }
+        self.case_insensitive = other.case_insensitive.or(self.case_insensitive);
+        self.numeric_tolerance = other.numeric_tolerance.or(self.numeric_tolerance);
+        self.ignore_whitespace = other.ignore_whitespace.or(self.ignore_whitespace);
+    }
+}
+
 /// Configuration for the diff algorithm.
 ///
-/// This allows customization of how diffs are computed.
-#[derive(Debug, Clone)]
+/// This allows customization of how diffs are computed. Serialized as in
+/// the `config.diff` section of JSON output, with each rule written as its
+/// pattern next to the options it sets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DiffConfig {
     /// Normalize whitespace in strings (trim and collapse multiple spaces)
     pub ignore_whitespace: bool,
     /// Treat null as equivalent to a missing key
+    #[serde(rename = "null_as_missing")]
     pub treat_null_as_missing: bool,
     /// Array comparison strategy
+    #[serde(rename = "array_strategy")]
     pub array_diff_strategy: ArrayDiffStrategy,
+    /// Per-path rules, applied in order so that later rules override
+    /// earlier ones
+    #[serde(
+        serialize_with = "serialize_rules",
+        deserialize_with = "deserialize_rules"
+    )]
+    pub rules: Vec<(PathPattern, RuleSet)>,
+}
+
+/// A per-path rule as serialized in a [`DiffConfig`].
+#[derive(Serialize)]
+struct RuleRef<'a> {
+    pattern: &'a PathPattern,
+    #[serde(flatten)]
+    rules: &'a RuleSet,
+}
+
+#[derive(Deserialize)]
+struct RuleEntry {
+    pattern: PathPattern,
+    #[serde(flatten)]
+    rules: RuleSet,
+}
+
+fn serialize_rules<S: Serializer>(
+    rules: &[(PathPattern, RuleSet)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        rules
+            .iter()
+            .map(|(pattern, rules)| RuleRef { pattern, rules }),
+    )
+}
+
+fn deserialize_rules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(PathPattern, RuleSet)>, D::Error> {
+    let entries = Vec::<RuleEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.pattern, entry.rules))
+        .collect())
 }
 
 impl Default for DiffConfig {
@@ -158,10 +388,33 @@ impl Default for DiffConfig {
             ignore_whitespace: false,
             treat_null_as_missing: false,
             array_diff_strategy: ArrayDiffStrategy::Positional,
+            rules: Vec::new(),
         }
     }
 }
 
+impl DiffConfig {
+    /// Adds a rule set for paths matching `pattern`.
+    pub fn rule(mut self, pattern: &str, rules: RuleSet) -> Self {
+        self.rules.push((PathPattern::parse(pattern), rules));
+        self
+    }
+
+    /// Returns the combined rules that apply at `path`.
+    ///
+    /// A rule applies when its pattern matches `path` or one of its
+    /// ancestors.
+    pub fn rules_for(&self, path: &[PathSegment]) -> RuleSet {
+        let mut result = RuleSet::default();
+        for (pattern, rules) in &self.rules {
+            if (0..=path.len()).any(|n| pattern.matches(&path[..n])) {
+                result.merge(rules);
+            }
+        }
+        result
+    }
+}
+
 /// Computes the semantic diff between two nodes.
 ///
 /// This is the main entry point for the diff algorithm. It recursively compares
@@ -190,158 +443,598 @@ impl Default for DiffConfig {
 /// assert_eq!(diff.stats.modified, 1);
 /// ```
 pub fn compute_diff(old: &Node, new: &Node, config: &DiffConfig) -> Diff {
-    let mut changes = Vec::new();
-    diff_nodes(old, new, Vec::new(), &mut changes, config);
+    compute_diff_filtered(old, new, config, &FilterConfig::new())
+}
 
-    let mut stats = DiffStats::new();
-    for change in &changes {
-        match change.change_type {
-            ChangeType::Added => stats.added += 1,
-            ChangeType::Removed => stats.removed += 1,
-            ChangeType::Modified => stats.modified += 1,
-            ChangeType::Unchanged => stats.unchanged += 1,
-        }
-    }
+/// Computes the semantic diff between two serializable values.
+///
+/// Both values are converted to nodes with [`to_node`], so any type
+/// implementing `Serialize` can be compared without going through JSON
+/// text. Fails if a value cannot be represented as a node, such as a NaN.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::{diff_values, DiffConfig};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Deployment {
+///     image: String,
+///     replicas: u32,
+/// }
+///
+/// let old = Deployment { image: "web:1".to_string(), replicas: 2 };
+/// let new = Deployment { image: "web:1".to_string(), replicas: 3 };
+/// let diff = diff_values(&old, &new, &DiffConfig::default()).unwrap();
+///
+/// assert_eq!(diff.stats.modified, 1);
+/// assert_eq!(diff.changes[0].path.to_string(), "replicas");
+/// ```
+pub fn diff_values<T: Serialize + ?Sized>(
+    old: &T,
+    new: &T,
+    config: &DiffConfig,
+) -> Result<Diff, SerializeError> {
+    Ok(compute_diff(&to_node(old)?, &to_node(new)?, config))
+}
 
-    Diff { changes, stats }
+/// Computes the semantic diff between two nodes, keeping only the changes
+/// that pass `filter`.
+///
+/// Subtrees that the filter excludes entirely are pruned during traversal:
+/// their values are still compared, to count the changes in them in
+/// [`Diff::hidden`], but no changes are built and the filter is not
+/// evaluated inside them. A subtree is pruned when an ignore pattern matches
+/// it and everything below it (for example `status.**`), or when only
+/// patterns are given and none of them can match inside it.
+///
+/// Containers that were added or removed as a whole are broken up into
+/// their leaves when only patterns select something inside them, so
+/// `--only spec.replicas` still reports the replica count of a newly added
+/// `spec`.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::diff::compute_diff_filtered;
+/// use sdiff_rs::filter::FilterConfig;
+/// use sdiff_rs::{parse_json, DiffConfig};
+///
+/// let old = parse_json(r#"{"name": "app", "status": {"ready": 1}}"#).unwrap();
+/// let new = parse_json(r#"{"name": "web", "status": {"ready": 3}}"#).unwrap();
+///
+/// let filter = FilterConfig::new().ignore("status.**");
+/// let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+///
+/// assert_eq!(diff.stats.modified, 1);
+/// assert_eq!(diff.changes[0].path.to_string(), "name");
+/// ```
+pub fn compute_diff_filtered(
+    old: &Node,
+    new: &Node,
+    config: &DiffConfig,
+    filter: &FilterConfig,
+) -> Diff {
+    compute_diff_traced(old, new, config, filter).0
+}
+
+/// Work done by a single diff traversal, for checking that pruning works.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraversalStats {
+    /// Number of value pairs compared, not counting pairs inside subtrees a
+    /// filter left out, which are only compared to count hidden changes
+    pub compared: usize,
 }
 
-fn diff_nodes(
+/// Like [`compute_diff_filtered`], also returning how much of the documents
+/// was traversed.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::diff::compute_diff_traced;
+/// use sdiff_rs::filter::FilterConfig;
+/// use sdiff_rs::{parse_json, DiffConfig};
+///
+/// let old = parse_json(r#"{"name": "app", "status": {"ready": 1}}"#).unwrap();
+/// let new = parse_json(r#"{"name": "web", "status": {"ready": 3}}"#).unwrap();
+///
+/// let filter = FilterConfig::new().ignore("status.**");
+/// let (_, traversal) = compute_diff_traced(&old, &new, &DiffConfig::default(), &filter);
+///
+/// // The root and `name`; nothing inside `status`
+/// assert_eq!(traversal.compared, 2);
+/// ```
+pub fn compute_diff_traced(
     old: &Node,
     new: &Node,
-    path: Vec<String>,
-    changes: &mut Vec<Change>,
     config: &DiffConfig,
-) {
-    if nodes_equal(old, new, config) {
-        if let (Node::Object(old_map), Node::Object(new_map)) = (old, new) {
-            diff_objects(old_map, new_map, path, changes, config);
-        } else if let (Node::Array(old_arr), Node::Array(new_arr)) = (old, new) {
-            diff_arrays(old_arr, new_arr, path, changes, config);
-        }
-        return;
+    filter: &FilterConfig,
+) -> (Diff, TraversalStats) {
+    let mut walker = Walker {
+        config,
+        filter,
+        old_root: old,
+        new_root: new,
+        changes: Vec::new(),
+        compared: 0,
+        hidden: HiddenChanges::new(filter),
+        unmatched: filter.rules.len(),
+        pruned: None,
+    };
+    walker.diff_nodes(old, new, Path::root());
+
+    let diff = Diff {
+        stats: DiffStats::from_changes(&walker.changes),
+        changes: walker.changes,
+        hidden: walker.hidden,
+    };
+    let traversal = TraversalStats {
+        compared: walker.compared,
+    };
+    (diff, traversal)
+}
+
+/// Computes the diff for a document that did not exist before.
+///
+/// Every leaf value of `new` (scalars and empty objects or arrays) is
+/// reported as a separate `Added` change, so a new file is shown in the same
+/// form as any other diff.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::diff::compute_added_diff;
+/// use sdiff_rs::parse_json;
+///
+/// let new = parse_json(r#"{"name": "app", "ports": [80, 443]}"#).unwrap();
+/// let diff = compute_added_diff(&new);
+///
+/// assert_eq!(diff.stats.added, 3);
+/// ```
+pub fn compute_added_diff(new: &Node) -> Diff {
+    let mut changes = Vec::new();
+    collect_leaves(new, Path::root(), ChangeType::Added, &mut changes);
+
+    let stats = DiffStats::from_changes(&changes);
+    Diff {
+        changes,
+        stats,
+        hidden: HiddenChanges::default(),
     }
+}
 
-    match (old, new) {
-        (Node::Object(old_map), Node::Object(new_map)) => {
-            diff_objects(old_map, new_map, path, changes, config);
+/// Computes the diff for a document that no longer exists.
+///
+/// Every leaf value of `old` is reported as a separate `Removed` change.
+pub fn compute_removed_diff(old: &Node) -> Diff {
+    let mut changes = Vec::new();
+    collect_leaves(old, Path::root(), ChangeType::Removed, &mut changes);
+
+    let stats = DiffStats::from_changes(&changes);
+    Diff {
+        changes,
+        stats,
+        hidden: HiddenChanges::default(),
+    }
+}
+
+fn collect_leaves(node: &Node, path: Path, change_type: ChangeType, changes: &mut Vec<Change>) {
+    match node {
+        Node::Object(map) if !map.is_empty() => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                let new_path = path.child(PathSegment::Key(key.clone()));
+                collect_leaves(&map[key], new_path, change_type.clone(), changes);
+            }
         }
-        (Node::Array(old_arr), Node::Array(new_arr)) => {
-            diff_arrays(old_arr, new_arr, path, changes, config);
+        Node::Array(arr) if !arr.is_empty() => {
+            for (i, item) in arr.iter().enumerate() {
+                let new_path = path.child(PathSegment::Index(i));
+                collect_leaves(item, new_path, change_type.clone(), changes);
+            }
         }
         _ => {
+            let (old_value, new_value) = match change_type {
+                ChangeType::Added => (None, Some(node.clone())),
+                _ => (Some(node.clone()), None),
+            };
             changes.push(Change {
                 path,
-                change_type: ChangeType::Modified,
-                old_value: Some(old.clone()),
-                new_value: Some(new.clone()),
+                change_type,
+                old_value,
+                new_value,
             });
         }
     }
 }
 
-fn diff_objects(
-    old_map: &std::collections::HashMap<String, Node>,
-    new_map: &std::collections::HashMap<String, Node>,
-    path: Vec<String>,
-    changes: &mut Vec<Change>,
-    config: &DiffConfig,
-) {
-    let old_keys: HashSet<&String> = old_map.keys().collect();
-    let new_keys: HashSet<&String> = new_map.keys().collect();
+/// State of a single diff traversal.
+struct Walker<'a> {
+    config: &'a DiffConfig,
+    filter: &'a FilterConfig,
+    /// Documents the filter's predicates are evaluated against
+    old_root: &'a Node,
+    new_root: &'a Node,
+    changes: Vec<Change>,
+    /// Number of node pairs compared so far
+    compared: usize,
+    /// Changes the filter left out so far
+    hidden: HiddenChanges,
+    /// Number of rules whose pattern has not matched a path yet
+    unmatched: usize,
+    /// Why the subtree being walked is pruned, if it is: its changes are
+    /// only counted as hidden, without building them
+    pruned: Option<HiddenBy>,
+}
+
+impl Walker<'_> {
+    fn excludes(&self, path: &[PathSegment]) -> bool {
+        self.filter
+            .excludes_subtree_in(path, self.old_root, self.new_root)
+    }
+
+    /// Marks the rules whose patterns match `path`, which the walk visits.
+    fn visit(&mut self, path: &[PathSegment]) {
+        self.mark_matched(|pattern, old_root, new_root| {
+            pattern.matches_in(path, old_root) || pattern.matches_in(path, new_root)
+        });
+    }
 
-    for key in new_keys.difference(&old_keys) {
-        let mut new_path = path.clone();
-        new_path.push((*key).clone());
-        let value = new_map.get(*key).unwrap();
+    /// Marks the rules whose patterns match `path` or a path inside `old`
+    /// or `new`, the values at `path` that the walk does not descend into.
+    fn visit_subtree(&mut self, path: &[PathSegment], old: Option<&Node>, new: Option<&Node>) {
+        self.mark_matched(|pattern, old_root, new_root| {
+            old.is_some_and(|old| pattern.matches_any_at_in(path, old, old_root))
+                || new.is_some_and(|new| pattern.matches_any_at_in(path, new, new_root))
+        });
+    }
+
+    fn mark_matched(&mut self, matches: impl Fn(&PathPattern, &Node, &Node) -> bool) {
+        if self.unmatched == 0 {
+            return;
+        }
+        for (count, rule) in self.hidden.rules.iter_mut().zip(&self.filter.rules) {
+            if !count.matched && matches(&rule.pattern, self.old_root, self.new_root) {
+                count.matched = true;
+                self.unmatched -= 1;
+            }
+        }
+    }
+
+    fn includes(&self, path: &[PathSegment]) -> bool {
+        self.filter
+            .should_include_in(path, self.old_root, self.new_root)
+    }
+
+    /// Counts a change the filter leaves out.
+    fn hide(&mut self, change: &Change) {
+        if let Some(reason) = self
+            .filter
+            .hidden_by_in(change, self.old_root, self.new_root)
+        {
+            self.hidden.record(reason);
+        }
+    }
+
+    /// Counts the changes in a subtree the filter excludes, without building
+    /// them or evaluating the filter inside it.
+    fn hide_subtree(&mut self, old: &Node, new: &Node, path: Path, reason: HiddenBy) {
+        let outer = self.pruned.replace(reason);
+        self.diff_nodes(old, new, path);
+        self.pruned = outer;
+    }
+
+    /// Records a change if the filter lets it through.
+    fn push(&mut self, change: Change) {
+        let included = self.includes(&change.path);
+        if included && self.filter.accepts_change(&change) {
+            self.changes.push(change);
+            return;
+        }
+        if !included
+            && !self
+                .filter
+                .may_include_inside_in(&change.path, self.old_root, self.new_root)
+        {
+            self.hide(&change);
+            return;
+        }
 
-        changes.push(Change {
-            path: new_path,
+        // A whole container was added or removed, but later rules or value
+        // filters may select values inside it: report the matching leaves
+        // instead.
+        let value = match change.change_type {
+            ChangeType::Added => &change.new_value,
+            ChangeType::Removed => &change.old_value,
+            _ => &None,
+        };
+        let Some(value @ (Node::Object(_) | Node::Array(_))) = value else {
+            self.hide(&change);
+            return;
+        };
+
+        let mut leaves = Vec::new();
+        collect_leaves(value, change.path, change.change_type, &mut leaves);
+        for leaf in leaves {
+            if self.includes(&leaf.path) && self.filter.accepts_change(&leaf) {
+                self.changes.push(leaf);
+            } else {
+                self.hide(&leaf);
+            }
+        }
+    }
+
+    fn added(&mut self, path: Path, value: &Node) {
+        if let Some(reason) = self.pruned {
+            self.hide_pruned(&path, reason);
+            return;
+        }
+        self.visit_subtree(&path, None, Some(value));
+        self.push_unless_pruned(Change {
+            path,
             change_type: ChangeType::Added,
             old_value: None,
             new_value: Some(value.clone()),
         });
     }
 
-    for key in old_keys.difference(&new_keys) {
-        let mut new_path = path.clone();
-        new_path.push((*key).clone());
-        let value = old_map.get(*key).unwrap();
-
-        changes.push(Change {
-            path: new_path,
+    fn removed(&mut self, path: Path, value: &Node) {
+        if let Some(reason) = self.pruned {
+            self.hide_pruned(&path, reason);
+            return;
+        }
+        self.visit_subtree(&path, Some(value), None);
+        self.push_unless_pruned(Change {
+            path,
             change_type: ChangeType::Removed,
             old_value: Some(value.clone()),
             new_value: None,
         });
     }
 
-    for key in old_keys.intersection(&new_keys) {
-        let mut new_path = path.clone();
-        new_path.push((*key).clone());
+    /// Counts a change inside a pruned subtree.
+    fn hide_pruned(&mut self, path: &[PathSegment], reason: HiddenBy) {
+        if !is_ignored(self.config, path) {
+            self.hidden.record(reason);
+        }
+    }
+
+    fn push_unless_pruned(&mut self, change: Change) {
+        if is_ignored(self.config, &change.path) {
+            return;
+        }
+        if self.excludes(&change.path) {
+            self.hide(&change);
+        } else {
+            self.push(change);
+        }
+    }
 
-        let old_value = old_map.get(*key).unwrap();
-        let new_value = new_map.get(*key).unwrap();
+    fn diff_nodes(&mut self, old: &Node, new: &Node, path: Path) {
+        if self.pruned.is_none() {
+            if let Some(reason) =
+                self.filter
+                    .subtree_hidden_by_in(&path, self.old_root, self.new_root)
+            {
+                self.visit_subtree(&path, Some(old), Some(new));
+                self.hide_subtree(old, new, path, reason);
+                return;
+            }
+        }
+        let rules = self.config.rules_for(&path);
+        if rules.ignore == Some(true) {
+            if self.pruned.is_none() {
+                self.visit_subtree(&path, Some(old), Some(new));
+            }
+            return;
+        }
+        if self.pruned.is_none() {
+            self.visit(&path);
+            self.compared += 1;
+        }
 
-        diff_nodes(old_value, new_value, new_path, changes, config);
+        match (old, new) {
+            (Node::Object(old_map), Node::Object(new_map)) => {
+                self.diff_objects(old_map, new_map, path);
+            }
+            (Node::Array(old_arr), Node::Array(new_arr)) => {
+                self.diff_arrays(old_arr, new_arr, path, &rules);
+            }
+            _ if nodes_equal(old, new, self.config, &rules) => {}
+            _ => {
+                if let Some(reason) = self.pruned {
+                    self.hide_pruned(&path, reason);
+                    return;
+                }
+                // A container replaced by a scalar, or the other way round
+                self.visit_subtree(&path, Some(old), Some(new));
+                self.push(Change {
+                    path,
+                    change_type: ChangeType::Modified,
+                    old_value: Some(old.clone()),
+                    new_value: Some(new.clone()),
+                });
+            }
+        }
     }
-}
 
-fn diff_arrays(
-    old_arr: &[Node],
-    new_arr: &[Node],
-    path: Vec<String>,
-    changes: &mut Vec<Change>,
-    config: &DiffConfig,
-) {
-    match config.array_diff_strategy {
-        ArrayDiffStrategy::Positional => {
-            diff_arrays_positional(old_arr, new_arr, path, changes, config);
+    fn diff_objects(
+        &mut self,
+        old_map: &HashMap<String, Node>,
+        new_map: &HashMap<String, Node>,
+        path: Path,
+    ) {
+        let old_keys: HashSet<&String> = old_map.keys().collect();
+        let new_keys: HashSet<&String> = new_map.keys().collect();
+
+        for key in new_keys.difference(&old_keys) {
+            let new_path = path.child(PathSegment::Key((*key).clone()));
+            self.added(new_path, &new_map[*key]);
+        }
+
+        for key in old_keys.difference(&new_keys) {
+            let new_path = path.child(PathSegment::Key((*key).clone()));
+            self.removed(new_path, &old_map[*key]);
         }
-        ArrayDiffStrategy::Lcs => {
-            diff_arrays_lcs(old_arr, new_arr, path, changes, config);
+
+        for key in old_keys.intersection(&new_keys) {
+            let new_path = path.child(PathSegment::Key((*key).clone()));
+            self.diff_nodes(&old_map[*key], &new_map[*key], new_path);
         }
     }
-}
 
-fn diff_arrays_positional(
-    old_arr: &[Node],
-    new_arr: &[Node],
-    path: Vec<String>,
-    changes: &mut Vec<Change>,
-    config: &DiffConfig,
-) {
-    let min_len = old_arr.len().min(new_arr.len());
+    fn diff_arrays(&mut self, old_arr: &[Node], new_arr: &[Node], path: Path, rules: &RuleSet) {
+        if let Some(field) = &rules.array_key {
+            if let (Some(old_keys), Some(new_keys)) = (
+                element_keys(old_arr, field, self.config, rules),
+                element_keys(new_arr, field, self.config, rules),
+            ) {
+                self.diff_arrays_keyed(old_arr, new_arr, &old_keys, &new_keys, path);
+                return;
+            }
+        }
 
-    for i in 0..min_len {
-        let mut new_path = path.clone();
-        new_path.push(format!("[{}]", i));
-        diff_nodes(&old_arr[i], &new_arr[i], new_path, changes, config);
+        match rules
+            .array_strategy
+            .unwrap_or(self.config.array_diff_strategy)
+        {
+            ArrayDiffStrategy::Positional => self.diff_arrays_positional(old_arr, new_arr, path),
+            ArrayDiffStrategy::Lcs => self.diff_arrays_lcs(old_arr, new_arr, path, rules),
+        }
     }
 
-    for (i, item) in old_arr.iter().enumerate().skip(min_len) {
-        let mut new_path = path.clone();
-        new_path.push(format!("[{}]", i));
-        changes.push(Change {
-            path: new_path,
-            change_type: ChangeType::Removed,
-            old_value: Some(item.clone()),
-            new_value: None,
-        });
+    /// Pairs array elements by key. Matched elements are compared and reported
+    /// at their index in the new array; removed elements are reported at their
+    /// index in the old array.
+    fn diff_arrays_keyed(
+        &mut self,
+        old_arr: &[Node],
+        new_arr: &[Node],
+        old_keys: &[ElementKey],
+        new_keys: &[ElementKey],
+        path: Path,
+    ) {
+        let old_index: HashMap<&ElementKey, usize> =
+            old_keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+        let new_set: HashSet<&ElementKey> = new_keys.iter().collect();
+
+        for (i, (key, item)) in old_keys.iter().zip(old_arr).enumerate() {
+            if !new_set.contains(key) {
+                self.removed(index_path(&path, i), item);
+            }
+        }
+
+        for (i, (key, item)) in new_keys.iter().zip(new_arr).enumerate() {
+            match old_index.get(key) {
+                Some(&old_i) => self.diff_nodes(&old_arr[old_i], item, index_path(&path, i)),
+                None => self.added(index_path(&path, i), item),
+            }
+        }
     }
 
-    for (i, item) in new_arr.iter().enumerate().skip(min_len) {
-        let mut new_path = path.clone();
-        new_path.push(format!("[{}]", i));
-        changes.push(Change {
-            path: new_path,
-            change_type: ChangeType::Added,
-            old_value: None,
-            new_value: Some(item.clone()),
-        });
+    fn diff_arrays_positional(&mut self, old_arr: &[Node], new_arr: &[Node], path: Path) {
+        let min_len = old_arr.len().min(new_arr.len());
+
+        for i in 0..min_len {
+            self.diff_nodes(&old_arr[i], &new_arr[i], index_path(&path, i));
+        }
+
+        for (i, item) in old_arr.iter().enumerate().skip(min_len) {
+            self.removed(index_path(&path, i), item);
+        }
+
+        for (i, item) in new_arr.iter().enumerate().skip(min_len) {
+            self.added(index_path(&path, i), item);
+        }
+    }
+
+    fn diff_arrays_lcs(&mut self, old_arr: &[Node], new_arr: &[Node], path: Path, rules: &RuleSet) {
+        let edits = compute_lcs_edits(old_arr, new_arr, self.config, rules);
+
+        let mut new_idx = 0;
+
+        for edit in edits {
+            match edit {
+                EditOp::Keep(old_idx, new_i) => {
+                    self.diff_nodes(&old_arr[old_idx], &new_arr[new_i], index_path(&path, new_i));
+                    new_idx = new_i + 1;
+                }
+                EditOp::Delete(old_idx) => {
+                    self.removed(index_path(&path, new_idx), &old_arr[old_idx]);
+                }
+                EditOp::Insert(new_i) => {
+                    self.added(index_path(&path, new_i), &new_arr[new_i]);
+                    new_idx = new_i + 1;
+                }
+            }
+        }
     }
 }
 
+fn index_path(path: &Path, index: usize) -> Path {
+    path.child(PathSegment::Index(index))
+}
+
+/// The value an array element is paired by, normalized so that values equal
+/// under the array's rules are equal keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ElementKey {
+    Bool(bool),
+    /// The bits of the number, with `-0.0` stored as `0.0`
+    Number(u64),
+    String(String),
+}
+
+impl ElementKey {
+    /// Returns the key for a scalar value, or None for null, objects and
+    /// arrays. Strings are normalized by the whitespace and case rules;
+    /// numeric tolerance is not applied, since it cannot be hashed.
+    fn new(node: &Node, config: &DiffConfig, rules: &RuleSet) -> Option<Self> {
+        match node {
+            Node::Bool(b) => Some(Self::Bool(*b)),
+            Node::Number(n) => Some(Self::Number((n + 0.0).to_bits())),
+            Node::String(s) => {
+                let mut s = if rules.ignore_whitespace.unwrap_or(config.ignore_whitespace) {
+                    normalize_whitespace(s)
+                } else {
+                    s.clone()
+                };
+                if rules.case_insensitive == Some(true) {
+                    s = s.to_lowercase();
+                }
+                Some(Self::String(s))
+            }
+            Node::Null | Node::Object(_) | Node::Array(_) => None,
+        }
+    }
+}
+
+/// Returns the key of every element, or None if some element has no scalar
+/// `field` or two elements share a key.
+fn element_keys(
+    arr: &[Node],
+    field: &str,
+    config: &DiffConfig,
+    rules: &RuleSet,
+) -> Option<Vec<ElementKey>> {
+    let mut keys = Vec::with_capacity(arr.len());
+    let mut seen = HashSet::new();
+    for item in arr {
+        let Node::Object(map) = item else {
+            return None;
+        };
+        let key = ElementKey::new(map.get(field)?, config, rules)?;
+        if !seen.insert(key.clone()) {
+            return None;
+        }
+        keys.push(key);
+    }
+    Some(keys)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum EditOp {
     Keep(usize, usize),
@@ -349,7 +1042,12 @@ enum EditOp {
     Insert(usize),
 }
 
-fn compute_lcs_edits(old: &[Node], new: &[Node], config: &DiffConfig) -> Vec<EditOp> {
+fn compute_lcs_edits(
+    old: &[Node],
+    new: &[Node],
+    config: &DiffConfig,
+    rules: &RuleSet,
+) -> Vec<EditOp> {
     let n = old.len();
     let m = new.len();
 
@@ -357,7 +1055,7 @@ fn compute_lcs_edits(old: &[Node], new: &[Node], config: &DiffConfig) -> Vec<Edi
 
     for i in 1..=n {
         for j in 1..=m {
-            if nodes_equal(&old[i - 1], &new[j - 1], config) {
+            if nodes_equal(&old[i - 1], &new[j - 1], config, rules) {
                 dp[i][j] = dp[i - 1][j - 1] + 1;
             } else {
                 dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
@@ -370,7 +1068,7 @@ fn compute_lcs_edits(old: &[Node], new: &[Node], config: &DiffConfig) -> Vec<Edi
     let mut j = m;
 
     while i > 0 || j > 0 {
-        if i > 0 && j > 0 && nodes_equal(&old[i - 1], &new[j - 1], config) {
+        if i > 0 && j > 0 && nodes_equal(&old[i - 1], &new[j - 1], config, rules) {
             edits.push(EditOp::Keep(i - 1, j - 1));
             i -= 1;
             j -= 1;
@@ -387,64 +1085,33 @@ fn compute_lcs_edits(old: &[Node], new: &[Node], config: &DiffConfig) -> Vec<Edi
     edits
 }
 
-fn diff_arrays_lcs(
-    old_arr: &[Node],
-    new_arr: &[Node],
-    path: Vec<String>,
-    changes: &mut Vec<Change>,
-    config: &DiffConfig,
-) {
-    let edits = compute_lcs_edits(old_arr, new_arr, config);
-
-    let mut new_idx = 0;
-
-    for edit in edits {
-        match edit {
-            EditOp::Keep(old_idx, new_i) => {
-                let mut new_path = path.clone();
-                new_path.push(format!("[{}]", new_i));
-                diff_nodes(
-                    &old_arr[old_idx],
-                    &new_arr[new_i],
-                    new_path,
-                    changes,
-                    config,
-                );
-                new_idx = new_i + 1;
-            }
-            EditOp::Delete(old_idx) => {
-                let mut new_path = path.clone();
-                new_path.push(format!("[{}]", new_idx));
-                changes.push(Change {
-                    path: new_path,
-                    change_type: ChangeType::Removed,
-                    old_value: Some(old_arr[old_idx].clone()),
-                    new_value: None,
-                });
-            }
-            EditOp::Insert(new_i) => {
-                let mut new_path = path.clone();
-                new_path.push(format!("[{}]", new_i));
-                changes.push(Change {
-                    path: new_path,
-                    change_type: ChangeType::Added,
-                    old_value: None,
-                    new_value: Some(new_arr[new_i].clone()),
-                });
-                new_idx = new_i + 1;
-            }
-        }
-    }
+fn is_ignored(config: &DiffConfig, path: &[PathSegment]) -> bool {
+    !config.rules.is_empty() && config.rules_for(path).ignore == Some(true)
 }
 
-fn nodes_equal(old: &Node, new: &Node, config: &DiffConfig) -> bool {
-    if config.ignore_whitespace {
-        if let (Node::String(s1), Node::String(s2)) = (old, new) {
-            return normalize_whitespace(s1) == normalize_whitespace(s2);
+fn nodes_equal(old: &Node, new: &Node, config: &DiffConfig, rules: &RuleSet) -> bool {
+    match (old, new) {
+        (Node::String(s1), Node::String(s2)) => {
+            let (s1, s2) = if rules.ignore_whitespace.unwrap_or(config.ignore_whitespace) {
+                (
+                    Cow::Owned(normalize_whitespace(s1)),
+                    Cow::Owned(normalize_whitespace(s2)),
+                )
+            } else {
+                (Cow::Borrowed(s1.as_str()), Cow::Borrowed(s2.as_str()))
+            };
+
+            if rules.case_insensitive == Some(true) {
+                s1.to_lowercase() == s2.to_lowercase()
+            } else {
+                s1 == s2
+            }
+        }
+        (Node::Number(n1), Node::Number(n2)) if rules.numeric_tolerance.is_some() => {
+            (n1 - n2).abs() <= rules.numeric_tolerance.unwrap_or_default()
         }
+        _ => old.semantic_equals(new),
     }
-
-    old.semantic_equals(new)
 }
 
 fn normalize_whitespace(s: &str) -> String {
@@ -0,0 +1,421 @@
+//! Structural diffs of whole directory trees and git revisions.
+//!
+//! This module compares every structured file under two roots, which can be
+//! directories on disk or git revisions. Files are paired by their relative
+//! path. Files that only exist on one side are reported as added or removed,
+//! unless they are similar enough to a file on the other side to count as a
+//! rename. Files whose format cannot be determined from the extension are
+//! skipped, as are files that are not valid UTF-8 or fail to parse.
+//! Symlinks to files are read, but symlinked directories are not followed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sdiff_rs::dir::{diff_sources, Source};
+//! use sdiff_rs::DiffConfig;
+//!
+//! let old = Source::Directory("deploy-v1".into());
+//! let new = Source::Directory("deploy-v2".into());
+//! let result = diff_sources(&old, &new, &DiffConfig::default()).unwrap();
+//!
+//! for file in &result.files {
+//!     println!("{}: {} changes", file.path, file.diff.stats.total_changes());
+//! }
+//! ```
+
+use crate::diff::{
+    compute_added_diff, compute_diff, compute_diff_filtered, compute_removed_diff, Diff,
+    DiffConfig, DiffStats,
+};
+use crate::error::{ParseError, SdiffError};
+use crate::filter::{filter_diff, filter_diff_in, FilterConfig, HiddenChanges};
+use crate::git;
+use crate::parser::{detect_format, parse_content, FormatHint};
+use crate::tree::Node;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Minimum similarity for an added and a removed file to be paired as a rename.
+pub const RENAME_THRESHOLD: f64 = 0.5;
+
+/// A tree of files to compare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A directory on disk
+    Directory(PathBuf),
+    /// A git revision in the current repository
+    Revision(String),
+}
+
+impl Source {
+    /// Lists all files in the tree, as sorted `/`-separated relative paths.
+    pub fn files(&self) -> Result<Vec<String>, SdiffError> {
+        match self {
+            Source::Directory(root) => {
+                let mut files = Vec::new();
+                walk_directory(root, "", &mut files)?;
+                files.sort();
+                Ok(files)
+            }
+            Source::Revision(rev) => {
+                let mut files = git::list_files(rev)?;
+                files.sort();
+                Ok(files)
+            }
+        }
+    }
+
+    /// Reads a file from the tree by its relative path.
+    pub fn read(&self, path: &str) -> Result<String, SdiffError> {
+        match self {
+            Source::Directory(root) => {
+                let full_path = root.join(path);
+                fs::read_to_string(&full_path)
+                    .map_err(|e| ParseError::read_error(full_path.to_string_lossy(), e).into())
+            }
+            Source::Revision(rev) => Ok(git::read_repo_file(rev, path)?),
+        }
+    }
+}
+
+/// How a file differs between the two trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// File exists only in the new tree
+    Added,
+    /// File exists only in the old tree
+    Removed,
+    /// File exists in both trees with different content
+    Modified,
+    /// File was moved from another path, possibly with changes
+    Renamed { from: String },
+}
+
+/// The structural diff of a single file.
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    /// Path of the file, relative to the root (the new path for renames)
+    pub path: String,
+    /// How the file changed
+    pub status: FileStatus,
+    /// Changes within the file
+    pub diff: Diff,
+}
+
+/// A file that could not be compared.
+#[derive(Debug, Clone)]
+pub struct SkippedFile {
+    /// Path of the file, relative to the root
+    pub path: String,
+    /// Why the file was skipped
+    pub reason: String,
+}
+
+/// The structural diff of two file trees.
+#[derive(Debug, Clone, Default)]
+pub struct DirDiff {
+    /// Files that were added, removed, renamed or modified, sorted by path
+    pub files: Vec<FileDiff>,
+    /// Structured files that failed to parse
+    pub skipped: Vec<SkippedFile>,
+    /// Files that were ignored because their format is not supported
+    pub unsupported: Vec<String>,
+    /// Number of structured files that are semantically identical
+    pub unchanged_files: usize,
+    /// Aggregate statistics over all files
+    pub stats: DiffStats,
+    /// Changes left out by a filter, over all files including those left
+    /// without any change
+    pub hidden: HiddenChanges,
+}
+
+impl DirDiff {
+    /// Returns true if no file changed.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Applies a path filter to every file's diff.
+    ///
+    /// Modified files left without any change are counted as unchanged.
+    /// Added, removed and renamed files are always kept.
+    pub fn filter(&self, config: &FilterConfig) -> DirDiff {
+        let mut result = DirDiff {
+            skipped: self.skipped.clone(),
+            unsupported: self.unsupported.clone(),
+            unchanged_files: self.unchanged_files,
+            ..DirDiff::default()
+        };
+
+        result.hidden = HiddenChanges::new(config);
+        for file in &self.files {
+            let diff = filter_diff(&file.diff, config);
+            result.hidden.merge(&diff.hidden);
+            if file.status == FileStatus::Modified && diff.is_empty() {
+                result.unchanged_files += 1;
+                continue;
+            }
+            result.files.push(FileDiff {
+                path: file.path.clone(),
+                status: file.status.clone(),
+                diff,
+            });
+        }
+
+        result.stats = total_stats(&result.files);
+        result
+    }
+}
+
+/// Compares every structured file in two trees.
+///
+/// Errors reading either tree are returned; files that are not valid UTF-8
+/// or fail to parse are recorded in `DirDiff::skipped` instead.
+pub fn diff_sources(
+    old: &Source,
+    new: &Source,
+    config: &DiffConfig,
+) -> Result<DirDiff, SdiffError> {
+    diff_sources_filtered(old, new, config, &FilterConfig::new())
+}
+
+/// Compares every structured file in two trees, keeping only the changes
+/// that pass `filter`.
+///
+/// Unlike [`DirDiff::filter`], filter segments in patterns are evaluated
+/// against each file's documents. Modified files left without any change
+/// are counted as unchanged.
+pub fn diff_sources_filtered(
+    old: &Source,
+    new: &Source,
+    config: &DiffConfig,
+    filter: &FilterConfig,
+) -> Result<DirDiff, SdiffError> {
+    let mut result = DirDiff {
+        hidden: HiddenChanges::new(filter),
+        ..DirDiff::default()
+    };
+
+    let old_files = old.files()?;
+    let new_files = new.files()?;
+    let all_files: BTreeSet<&String> = old_files.iter().chain(&new_files).collect();
+    result.unsupported = all_files
+        .iter()
+        .filter(|path| !is_supported(path))
+        .map(|path| path.to_string())
+        .collect();
+
+    let old_docs = load_documents(old, &old_files, &mut result.skipped)?;
+    let new_docs = load_documents(new, &new_files, &mut result.skipped)?;
+
+    let mut removed = Vec::new();
+    for (path, (old_content, old_node)) in &old_docs {
+        match new_docs.get(path) {
+            Some((new_content, new_node)) => {
+                if old_content == new_content {
+                    result.unchanged_files += 1;
+                    continue;
+                }
+                let diff = compute_diff_filtered(old_node, new_node, config, filter);
+                result.hidden.merge(&diff.hidden);
+                if diff.is_empty() {
+                    result.unchanged_files += 1;
+                } else {
+                    result.files.push(FileDiff {
+                        path: path.clone(),
+                        status: FileStatus::Modified,
+                        diff,
+                    });
+                }
+            }
+            None => removed.push(path.as_str()),
+        }
+    }
+
+    let mut added: Vec<&str> = new_docs
+        .keys()
+        .filter(|path| !old_docs.contains_key(*path))
+        .map(String::as_str)
+        .collect();
+
+    for (from, to) in detect_renames(&removed, &added, &old_docs, &new_docs, config) {
+        removed.retain(|path| *path != from);
+        added.retain(|path| *path != to);
+        result.files.push(FileDiff {
+            path: to.to_string(),
+            status: FileStatus::Renamed {
+                from: from.to_string(),
+            },
+            diff: compute_diff_filtered(&old_docs[from].1, &new_docs[to].1, config, filter),
+        });
+    }
+
+    for path in removed {
+        let old_node = &old_docs[path].1;
+        result.files.push(FileDiff {
+            path: path.to_string(),
+            status: FileStatus::Removed,
+            diff: filter_diff_in(
+                &compute_removed_diff(old_node),
+                filter,
+                old_node,
+                &Node::Null,
+            ),
+        });
+    }
+    for path in added {
+        let new_node = &new_docs[path].1;
+        result.files.push(FileDiff {
+            path: path.to_string(),
+            status: FileStatus::Added,
+            diff: filter_diff_in(&compute_added_diff(new_node), filter, &Node::Null, new_node),
+        });
+    }
+
+    for file in &result.files {
+        if file.status != FileStatus::Modified {
+            result.hidden.merge(&file.diff.hidden);
+        }
+    }
+    // Identical files are never diffed, but patterns matching only in them
+    // still matched something
+    for (_, node) in old_docs.values().chain(new_docs.values()) {
+        result.hidden.mark_matched(filter, node, &Node::Null);
+    }
+
+    result.files.sort_by(|a, b| a.path.cmp(&b.path));
+    result.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    result.stats = total_stats(&result.files);
+    Ok(result)
+}
+
+/// Returns how similar two documents are, from 0.0 (nothing in common) to
+/// 1.0 (semantically identical).
+///
+/// Similarity is one minus the number of changed leaves relative to the
+/// number of leaves in the larger document.
+pub fn similarity(old: &Node, new: &Node, config: &DiffConfig) -> f64 {
+    let leaves = leaf_count(old).max(leaf_count(new));
+    if leaves == 0 {
+        return 1.0;
+    }
+
+    let changes = compute_diff(old, new, config).stats.total_changes();
+    (1.0 - changes as f64 / leaves as f64).max(0.0)
+}
+
+fn is_supported(path: &str) -> bool {
+    detect_format(Path::new(path)) != FormatHint::Auto
+}
+
+fn walk_directory(root: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), SdiffError> {
+    let dir = root.join(prefix);
+    let entries =
+        fs::read_dir(&dir).map_err(|e| ParseError::read_error(dir.to_string_lossy(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| ParseError::read_error(dir.to_string_lossy(), e))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        // Symlinked directories are not followed, so a link back into the
+        // tree cannot loop and a link out of it cannot pull in other files
+        let file_type = entry
+            .file_type()
+            .map_err(|e| ParseError::read_error(entry.path().to_string_lossy(), e))?;
+        if file_type.is_dir() {
+            if name != ".git" {
+                walk_directory(root, &relative, files)?;
+            }
+        } else if file_type.is_file() || (file_type.is_symlink() && entry.path().is_file()) {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Reads and parses every supported file, keyed by relative path.
+fn load_documents(
+    source: &Source,
+    files: &[String],
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<BTreeMap<String, (String, Node)>, SdiffError> {
+    let mut documents = BTreeMap::new();
+    for path in files.iter().filter(|path| is_supported(path)) {
+        let content = match source.read(path) {
+            Ok(content) => content,
+            Err(SdiffError::Parse(e @ ParseError::ReadError { .. })) if is_not_text(&e) => {
+                skipped.push(SkippedFile {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        match parse_content(&content, detect_format(Path::new(path)), path) {
+            Ok(node) => {
+                documents.insert(path.clone(), (content, node));
+            }
+            Err(e) => skipped.push(SkippedFile {
+                path: path.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+    Ok(documents)
+}
+
+/// Returns true if a read failed because the file is not valid UTF-8.
+fn is_not_text(error: &ParseError) -> bool {
+    matches!(error, ParseError::ReadError { source, .. } if source.kind() == std::io::ErrorKind::InvalidData)
+}
+
+/// Pairs removed and added files whose content is similar enough, most
+/// similar pairs first.
+fn detect_renames<'a>(
+    removed: &[&'a str],
+    added: &[&'a str],
+    old_docs: &BTreeMap<String, (String, Node)>,
+    new_docs: &BTreeMap<String, (String, Node)>,
+    config: &DiffConfig,
+) -> Vec<(&'a str, &'a str)> {
+    let mut candidates = Vec::new();
+    for from in removed {
+        for to in added {
+            let score = similarity(&old_docs[*from].1, &new_docs[*to].1, config);
+            if score >= RENAME_THRESHOLD {
+                candidates.push((score, *from, *to));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut renames: Vec<(&str, &str)> = Vec::new();
+    for (_, from, to) in candidates {
+        if renames.iter().all(|(f, t)| *f != from && *t != to) {
+            renames.push((from, to));
+        }
+    }
+    renames
+}
+
+fn leaf_count(node: &Node) -> usize {
+    compute_added_diff(node).changes.len()
+}
+
+fn total_stats(files: &[FileDiff]) -> DiffStats {
+    let mut stats = DiffStats::new();
+    for file in files {
+        stats.added += file.diff.stats.added;
+        stats.removed += file.diff.stats.removed;
+        stats.modified += file.diff.stats.modified;
+        stats.unchanged += file.diff.stats.unchanged;
+    }
+    stats
+}
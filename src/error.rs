@@ -47,6 +47,37 @@ pub enum OutputError {
         #[source]
         source: serde_json::Error,
     },
+
+    #[error("Failed to serialize to YAML: {source}")]
+    YamlSerializationError {
+        #[source]
+        source: serde_yaml::Error,
+    },
+
+    #[error("Failed to serialize to TOML: {source}")]
+    TomlSerializationError {
+        #[source]
+        source: toml::ser::Error,
+    },
+
+    #[error("Cannot represent {value} in {format}")]
+    UnsupportedValue { format: String, value: String },
+}
+
+/// An error turning a Rust value into a [`Node`](crate::Node).
+#[derive(Debug, thiserror::Error)]
+pub enum SerializeError {
+    #[error("Cannot represent {value} as a node")]
+    UnsupportedValue { value: String },
+
+    #[error("Object keys must be strings, numbers or booleans, found {key}")]
+    UnsupportedKey { key: String },
+
+    #[error("Duplicate object key '{key}'")]
+    DuplicateKey { key: String },
+
+    #[error("{message}")]
+    Custom { message: String },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -57,8 +88,17 @@ pub enum SdiffError {
     #[error(transparent)]
     Output(#[from] OutputError),
 
+    #[error(transparent)]
+    Serialize(#[from] SerializeError),
+
     #[error("Invalid configuration: {message}")]
     ConfigError { message: String },
+
+    #[error("Invalid pattern '{pattern}': {message}")]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error(transparent)]
+    Git(#[from] crate::git::GitError),
 }
 
 impl ParseError {
@@ -98,3 +138,11 @@ impl ParseError {
         Self::UnknownFormat { path: path.into() }
     }
 }
+
+impl serde::ser::Error for SerializeError {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        Self::Custom {
+            message: message.to_string(),
+        }
+    }
+}
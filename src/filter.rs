@@ -10,88 +10,1420 @@
 //! - `**` - matches any number of path segments (including zero)
 //! - `foo.bar` - matches nested path "foo.bar"
 //! - `**.version` - matches "version" at any depth
+//! - `items[0]` - matches the first element of the array "items"
+//! - `items[*]` - matches any element of the array "items"
+//! - `labels."app.kubernetes.io/name"` - quotes a key containing dots;
+//!   `\"` and `\\` escape a quote or backslash inside the quotes
+//! - `items[1:3]`, `items[-1]`, `items[::2]` - array slices; negative
+//!   bounds count from the end of the array
+//! - `containers[?(@.name == 'web')]` - elements whose value satisfies a
+//!   filter. Filters support `@` paths (`@.a.b`, `@['k']`, `@[0]`), string,
+//!   number, boolean and null literals, `==`, `!=`, `<`, `<=`, `>`, `>=`,
+//!   `&&`, `||`, `!` and parentheses
+//! - JSONPath spelling is accepted too: a leading `$`, `['key']` and `..`
+//!   for any depth
+//! - `feature_*_enabled`, `v?`, `[abc]x`, `[!_]*` - globs within a key: `*`
+//!   matches any run of characters, `?` one character and `[...]` one
+//!   character from a class (`[a-z]`, negated with `!` or `^`). A bracket
+//!   is only a class if it is not an index, slice, `*`, quoted key or filter
+//! - `/^x-.*$/` - keys matching a regular expression (not anchored unless
+//!   written so); `/.../i` ignores case and `\/` is a slash in the
+//!   expression
+//!
+//! Keys can be matched case-insensitively with
+//! [`PathPattern::case_insensitive`] or [`FilterConfig::case_insensitive`].
+//! Keys containing `*`, `?` or starting with `/` must be quoted to be matched
+//! literally.
+//!
+//! Filters and negative slices are evaluated against the compared documents,
+//! and a pattern applies if it matches in either the old or the new one.
+//!
+//! A leading `!` negates a pattern. In [`FilterConfig`], ignore and only
+//! patterns are evaluated in the order they were added and the last one
+//! matching a path decides, so `!` makes an exception to earlier patterns:
+//! ignoring `metadata.**` then `!metadata.labels.**` keeps the labels, and
+//! `!spec.secret` after only `spec.**` hides the secret again.
+//!
+//! Paths printed by the output formatters use the same syntax, so any
+//! printed path can be pasted back as a pattern.
 //!
 //! # Examples
 //!
 //! ```
 //! use sdiff_rs::filter::{PathPattern, FilterConfig};
+//! use sdiff_rs::Path;
 //!
 //! let pattern = PathPattern::parse("metadata.timestamp");
-//! assert!(pattern.matches(&["metadata".to_string(), "timestamp".to_string()]));
+//! assert!(pattern.matches(&Path::root().key("metadata").key("timestamp")));
 //!
 //! let pattern = PathPattern::parse("**.version");
-//! assert!(pattern.matches(&["package".to_string(), "version".to_string()]));
-//! assert!(pattern.matches(&["dependencies".to_string(), "foo".to_string(), "version".to_string()]));
+//! assert!(pattern.matches(&Path::root().key("package").key("version")));
+//! assert!(pattern.matches(&Path::root().key("dependencies").key("foo").key("version")));
+//!
+//! let pattern = PathPattern::parse("items[*].name");
+//! assert!(pattern.matches(&Path::root().key("items").index(2).key("name")));
+//!
+//! let doc = sdiff_rs::parse_json(r#"{"items": [{"name": "web"}, {"name": "db"}]}"#).unwrap();
+//! let pattern = PathPattern::parse("$.items[?(@.name == 'web')]..*");
+//! assert!(pattern.matches_in(&Path::root().key("items").index(0).key("name"), &doc));
+//! assert!(!pattern.matches_in(&Path::root().key("items").index(1).key("name"), &doc));
 //! ```
 
-use crate::diff::{Change, Diff, DiffStats};
+use crate::diff::{Change, ChangeType, Diff, DiffStats};
+use crate::error::SdiffError;
+use crate::path::{quote_key, PathSegment};
+use crate::tree::Node;
+use regex::{Regex, RegexBuilder};
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
 
 /// A single segment in a path pattern.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PatternSegment {
-    /// Matches an exact string
+    /// Matches an object key exactly
     Literal(String),
+    /// Matches an array index exactly ([3])
+    Index(usize),
     /// Matches any single path segment (*)
     SingleWildcard,
     /// Matches any number of path segments (**)
     DoubleWildcard,
+    /// Matches any array index ([*])
+    AnyIndex,
+    /// Matches the array indices selected by a slice ([start:end:step]).
+    /// Negative bounds count from the end of the array.
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    /// Matches array elements and object members whose value satisfies a
+    /// predicate ([?(@.name == 'web')])
+    Filter(Predicate),
+    /// Matches object keys against a glob with `*`, `?` and `[abc]`
+    /// (feature_*_enabled)
+    Glob(String),
+    /// Matches object keys against a regular expression (/^x-.*$/)
+    Regex(KeyRegex),
+}
+
+/// A regular expression in a path pattern, matched against object keys.
+///
+/// The expression is not anchored: `/^x-/` matches keys starting with `x-`.
+#[derive(Debug, Clone)]
+pub struct KeyRegex {
+    source: String,
+    /// Written with the `i` flag (/.../i)
+    flagged: bool,
+    regex: Regex,
+}
+
+impl KeyRegex {
+    pub fn new(source: &str, case_insensitive: bool) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(source)
+            .case_insensitive(case_insensitive)
+            .build()?;
+        Ok(Self {
+            source: source.to_string(),
+            flagged: case_insensitive,
+            regex,
+        })
+    }
+
+    /// Returns the expression as written in the pattern.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_match(&self, key: &str) -> bool {
+        self.regex.is_match(key)
+    }
+}
+
+impl PartialEq for KeyRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.flagged == other.flagged
+    }
+}
+
+/// A condition on a value, used by filter segments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// The operand exists (`@.name`)
+    Exists(Operand),
+    /// Compares two operands (`@.replicas > 2`)
+    Compare(Operand, CompareOp, Operand),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+/// A value in a predicate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// A value relative to the element being tested (`@`, `@.metadata.name`)
+    Current(Vec<PathSegment>),
+    /// A constant (`'web'`, `3`, `true`, `null`)
+    Literal(Node),
+}
+
+/// A comparison operator in a predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Predicate {
+    /// Returns true if `node` satisfies the predicate.
+    ///
+    /// Operands that refer to missing values are only equal to each other;
+    /// ordering comparisons need two numbers or two strings.
+    pub fn evaluate(&self, node: &Node) -> bool {
+        match self {
+            Predicate::Exists(operand) => operand.resolve(node).is_some(),
+            Predicate::Compare(left, op, right) => {
+                let (left, right) = (left.resolve(node), right.resolve(node));
+                let equal = match (left, right) {
+                    (Some(a), Some(b)) => a.semantic_equals(b),
+                    (None, None) => true,
+                    _ => false,
+                };
+                let ordering = match (left, right) {
+                    (Some(Node::Number(a)), Some(Node::Number(b))) => a.partial_cmp(b),
+                    (Some(Node::String(a)), Some(Node::String(b))) => Some(a.cmp(b)),
+                    _ => None,
+                };
+                match op {
+                    CompareOp::Eq => equal,
+                    CompareOp::Ne => !equal,
+                    CompareOp::Lt => ordering == Some(Ordering::Less),
+                    CompareOp::Le => equal || ordering == Some(Ordering::Less),
+                    CompareOp::Gt => ordering == Some(Ordering::Greater),
+                    CompareOp::Ge => equal || ordering == Some(Ordering::Greater),
+                }
+            }
+            Predicate::Not(inner) => !inner.evaluate(node),
+            Predicate::And(a, b) => a.evaluate(node) && b.evaluate(node),
+            Predicate::Or(a, b) => a.evaluate(node) || b.evaluate(node),
+        }
+    }
+}
+
+impl Operand {
+    fn resolve<'a>(&'a self, node: &'a Node) -> Option<&'a Node> {
+        match self {
+            Operand::Current(path) => node.get(path),
+            Operand::Literal(value) => Some(value),
+        }
+    }
+}
+
+impl Predicate {
+    /// Writes the predicate, parenthesized if it binds more loosely than
+    /// `min` (1 for `||`, 2 for `&&`, 3 for everything else).
+    fn write(&self, f: &mut fmt::Formatter<'_>, min: u8) -> fmt::Result {
+        let precedence = match self {
+            Predicate::Or(..) => 1,
+            Predicate::And(..) => 2,
+            _ => 3,
+        };
+        if precedence < min {
+            f.write_str("(")?;
+        }
+        match self {
+            Predicate::Exists(operand) => write!(f, "{}", operand)?,
+            Predicate::Compare(left, op, right) => write!(f, "{} {} {}", left, op, right)?,
+            Predicate::Not(inner) => {
+                f.write_str("!")?;
+                inner.write(f, 3)?;
+            }
+            Predicate::And(left, right) => {
+                left.write(f, 2)?;
+                f.write_str(" && ")?;
+                right.write(f, 3)?;
+            }
+            Predicate::Or(left, right) => {
+                left.write(f, 1)?;
+                f.write_str(" || ")?;
+                right.write(f, 2)?;
+            }
+        }
+        if precedence < min {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the predicate in filter expression syntax.
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 1)
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Current(path) => {
+                f.write_str("@")?;
+                for segment in path {
+                    match segment {
+                        PathSegment::Key(key)
+                            if !key.is_empty()
+                                && key
+                                    .chars()
+                                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+                        {
+                            write!(f, ".{}", key)?
+                        }
+                        PathSegment::Key(key) => write!(f, "[{}]", single_quote(key))?,
+                        PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                    }
+                }
+                Ok(())
+            }
+            Operand::Literal(Node::String(s)) => f.write_str(&single_quote(s)),
+            Operand::Literal(Node::Number(n)) => write!(f, "{}", n),
+            Operand::Literal(Node::Bool(b)) => write!(f, "{}", b),
+            Operand::Literal(_) => f.write_str("null"),
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        })
+    }
+}
+
+/// Quotes a string for a filter expression, escaping `'` and `\`.
+fn single_quote(s: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in s.chars() {
+        if c == '\'' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// What is known about the value at the current position while matching.
+#[derive(Debug, Clone, Copy)]
+enum Doc<'a> {
+    /// No document was given, so filters and relative slices cannot be
+    /// evaluated
+    Unknown,
+    /// The document has no value at this position
+    Missing,
+    Value(&'a Node),
+}
+
+impl<'a> Doc<'a> {
+    fn child(self, segment: &PathSegment) -> Doc<'a> {
+        match self {
+            Doc::Value(node) => node
+                .get(std::slice::from_ref(segment))
+                .map_or(Doc::Missing, Doc::Value),
+            other => other,
+        }
+    }
+}
+
+/// How a pattern is matched against a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// The pattern matches the path itself
+    Exact,
+    /// The pattern could match the path or a path below it
+    Prefix,
+    /// The pattern matches the path and every path below it
+    Subtree,
+}
+
+impl PatternSegment {
+    /// Returns whether this single-segment pattern matches `path_seg`, where
+    /// `parent` is the container holding it, or None if that depends on a
+    /// document that was not given. `**` is handled by the callers since it
+    /// can span several segments.
+    fn matches(&self, path_seg: &PathSegment, parent: Doc, ignore_case: bool) -> Option<bool> {
+        match (self, path_seg) {
+            (PatternSegment::Literal(lit), PathSegment::Key(key)) if ignore_case => {
+                Some(lit.to_lowercase() == key.to_lowercase())
+            }
+            (PatternSegment::Literal(lit), PathSegment::Key(key)) => Some(lit == key),
+            (PatternSegment::Glob(glob), PathSegment::Key(key)) => {
+                Some(wildcard_match_with(glob, key, ignore_case))
+            }
+            (PatternSegment::Regex(regex), PathSegment::Key(key)) => Some(regex.is_match(key)),
+            (PatternSegment::Index(i), PathSegment::Index(j)) => Some(i == j),
+            (PatternSegment::SingleWildcard | PatternSegment::DoubleWildcard, _) => Some(true),
+            (PatternSegment::AnyIndex, PathSegment::Index(_)) => Some(true),
+            (PatternSegment::Slice { start, end, step }, PathSegment::Index(index)) => {
+                let len = match parent {
+                    Doc::Value(Node::Array(arr)) => Some(arr.len()),
+                    Doc::Unknown => None,
+                    _ => return Some(false),
+                };
+                slice_contains(*start, *end, *step, len, *index)
+            }
+            (PatternSegment::Filter(predicate), _) => match parent.child(path_seg) {
+                Doc::Value(node) => Some(predicate.evaluate(node)),
+                Doc::Missing => Some(false),
+                Doc::Unknown => None,
+            },
+            _ => Some(false),
+        }
+    }
+}
+
+/// Returns whether a slice selects `index` from an array of length `len`,
+/// following JSONPath slice semantics. Without a length, only slices with
+/// non-negative bounds and a positive step can be decided.
+fn slice_contains(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: Option<usize>,
+    index: usize,
+) -> Option<bool> {
+    let step = step.unwrap_or(1);
+    let index = index as i64;
+    if step == 0 {
+        return Some(false);
+    }
+
+    let Some(len) = len.map(|len| len as i64) else {
+        if step < 0 || start.is_some_and(|s| s < 0) || end.is_some_and(|e| e < 0) {
+            return None;
+        }
+        let start = start.unwrap_or(0);
+        let in_range = index >= start && end.is_none_or(|end| index < end);
+        return Some(in_range && (index - start) % step == 0);
+    };
+
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        Some(index >= lower && index < upper && (index - lower) % step == 0)
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        // `-step` would overflow for i64::MIN
+        Some(
+            index > lower
+                && index <= upper
+                && (upper - index)
+                    .unsigned_abs()
+                    .is_multiple_of(step.unsigned_abs()),
+        )
+    }
 }
 
 /// A compiled path pattern for matching against diff paths.
 #[derive(Debug, Clone)]
 pub struct PathPattern {
     pub segments: Vec<PatternSegment>,
+    /// Compare keys case-insensitively in literal, glob and regex segments
+    pub case_insensitive: bool,
+    /// Written with a leading `!`: the pattern undoes earlier patterns in
+    /// the same list instead of adding to them. Matching itself ignores it.
+    pub negated: bool,
 }
 
 impl PathPattern {
+    /// Parses a pattern. Bracket expressions and regular expressions that
+    /// fail to parse are read as literal keys; use [`PathPattern::try_parse`]
+    /// to report them instead.
     pub fn parse(pattern: &str) -> Self {
-        let segments = pattern
-            .split('.')
-            .map(|s| match s {
-                "**" => PatternSegment::DoubleWildcard,
-                "*" => PatternSegment::SingleWildcard,
-                _ => PatternSegment::Literal(s.to_string()),
-            })
-            .collect();
-        Self { segments }
+        Self::parse_segments(pattern, false).unwrap_or_else(|_| Self {
+            segments: vec![PatternSegment::Literal(pattern.to_string())],
+            case_insensitive: false,
+            negated: false,
+        })
+    }
+
+    /// Parses a pattern, failing on malformed filter expressions, slices and
+    /// regular expressions.
+    pub fn try_parse(pattern: &str) -> Result<Self, SdiffError> {
+        Self::parse_segments(pattern, true).map_err(|message| SdiffError::InvalidPattern {
+            pattern: pattern.to_string(),
+            message,
+        })
+    }
+
+    fn parse_segments(pattern: &str, strict: bool) -> Result<Self, String> {
+        let negated = pattern.starts_with('!');
+        let chars: Vec<char> = pattern.chars().skip(usize::from(negated)).collect();
+        let mut segments = Vec::new();
+        // A JSONPath root; `$schema` is still an ordinary key
+        let mut i = usize::from(
+            chars.first() == Some(&'$') && matches!(chars.get(1), None | Some('.' | '[')),
+        );
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'.') => {
+                    segments.push(PatternSegment::DoubleWildcard);
+                    i += 2;
+                }
+                '.' => i += 1,
+                '"' => {
+                    let (key, next) = parse_quoted(&chars, i + 1);
+                    segments.push(PatternSegment::Literal(key));
+                    i = next;
+                }
+                '[' if is_selector_at(&chars, i) => {
+                    let end = bracket_end(&chars, i + 1);
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    segments.push(match parse_bracket(inner.trim()) {
+                        Ok(segment) => segment,
+                        Err(message) if strict => return Err(message),
+                        Err(_) => PatternSegment::Literal(inner),
+                    });
+                    i = end + 1;
+                }
+                '/' => {
+                    let end = regex_end(&chars, i + 1);
+                    segments.push(match parse_regex(&chars[i..end]) {
+                        Ok(regex) => PatternSegment::Regex(regex),
+                        Err(message) if strict => return Err(message),
+                        Err(_) => PatternSegment::Literal(chars[i..end].iter().collect()),
+                    });
+                    i = end;
+                }
+                _ => {
+                    // A word runs to the next `.` or selector bracket; other
+                    // brackets are character classes within the word
+                    let mut end = i;
+                    while end < chars.len() && chars[end] != '.' {
+                        if chars[end] != '[' {
+                            end += 1;
+                        } else if is_selector_at(&chars, end) {
+                            break;
+                        } else {
+                            end = bracket_end(&chars, end + 1) + 1;
+                        }
+                    }
+                    let word: String = chars[i..end].iter().collect();
+                    segments.push(match word.as_str() {
+                        "**" => PatternSegment::DoubleWildcard,
+                        "*" => PatternSegment::SingleWildcard,
+                        _ if word.contains(['*', '?', '[']) => PatternSegment::Glob(word),
+                        _ => PatternSegment::Literal(word),
+                    });
+                    i = end;
+                }
+            }
+        }
+
+        Ok(Self {
+            segments,
+            case_insensitive: false,
+            negated,
+        })
+    }
+
+    /// Returns this pattern with case-insensitive key matching turned on or
+    /// off. Regular expressions written with the `i` flag stay
+    /// case-insensitive.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        for segment in &mut self.segments {
+            if let PatternSegment::Regex(regex) = segment {
+                if let Ok(rebuilt) = KeyRegex::new(&regex.source, regex.flagged || case_insensitive)
+                {
+                    *regex = KeyRegex {
+                        flagged: regex.flagged,
+                        ..rebuilt
+                    };
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns true if the pattern matches `path`.
+    ///
+    /// Filter segments need the document to be evaluated and never match
+    /// here; use [`PathPattern::matches_in`] instead.
+    pub fn matches(&self, path: &[PathSegment]) -> bool {
+        self.matches_with(&self.segments, path, Doc::Unknown, Mode::Exact)
+    }
+
+    /// Returns true if the pattern matches `path` in the document `root`.
+    pub fn matches_in(&self, path: &[PathSegment], root: &Node) -> bool {
+        self.matches_with(&self.segments, path, Doc::Value(root), Mode::Exact)
+    }
+
+    /// Returns true if `path` or some path nested inside it could match.
+    ///
+    /// This is used to tell whether a change to a container affects any of
+    /// the paths the pattern refers to.
+    pub fn matches_prefix(&self, path: &[PathSegment]) -> bool {
+        self.matches_with(&self.segments, path, Doc::Unknown, Mode::Prefix)
+    }
+
+    /// Like [`PathPattern::matches_prefix`], evaluating filters in `root`.
+    pub fn matches_prefix_in(&self, path: &[PathSegment], root: &Node) -> bool {
+        self.matches_with(&self.segments, path, Doc::Value(root), Mode::Prefix)
     }
 
-    pub fn matches(&self, path: &[String]) -> bool {
-        self.matches_recursive(&self.segments, path)
+    /// Returns true if the pattern matches `path` and every path nested
+    /// inside it, as `metadata.**` does for `metadata`.
+    pub fn matches_subtree(&self, path: &[PathSegment]) -> bool {
+        self.matches_with(&self.segments, path, Doc::Unknown, Mode::Subtree)
+    }
+
+    /// Like [`PathPattern::matches_subtree`], evaluating filters in `root`.
+    pub fn matches_subtree_in(&self, path: &[PathSegment], root: &Node) -> bool {
+        self.matches_with(&self.segments, path, Doc::Value(root), Mode::Subtree)
+    }
+
+    /// Returns true if the pattern matches the path of any value in `root`,
+    /// including `root` itself.
+    pub fn matches_any_in(&self, root: &Node) -> bool {
+        self.matches_below(root, &mut Vec::new(), root)
+    }
+
+    /// Returns true if the pattern matches `path` or the path of any value
+    /// inside `node`, the value at `path` in `root`.
+    pub fn matches_any_at_in(&self, path: &[PathSegment], node: &Node, root: &Node) -> bool {
+        self.matches_prefix_in(path, root) && self.matches_below(node, &mut path.to_vec(), root)
+    }
+
+    fn matches_below(&self, node: &Node, path: &mut Vec<PathSegment>, root: &Node) -> bool {
+        if self.matches_in(path, root) {
+            return true;
+        }
+        let children: Vec<(PathSegment, &Node)> = match node {
+            Node::Object(map) => map
+                .iter()
+                .map(|(key, value)| (PathSegment::Key(key.clone()), value))
+                .collect(),
+            Node::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (PathSegment::Index(i), value))
+                .collect(),
+            _ => return false,
+        };
+        for (segment, child) in children {
+            path.push(segment);
+            let found = self.matches_prefix_in(path, root) && self.matches_below(child, path, root);
+            path.pop();
+            if found {
+                return true;
+            }
+        }
+        false
     }
 
-    fn matches_recursive(&self, pattern: &[PatternSegment], path: &[String]) -> bool {
+    /// Returns true if the pattern has segments that can only be evaluated
+    /// against a document.
+    pub fn needs_document(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|s| matches!(s, PatternSegment::Filter(_) | PatternSegment::Slice { .. }))
+    }
+
+    /// Matches `pattern` against `path`, where `doc` is the value at the
+    /// position reached so far. Segments that cannot be decided without the
+    /// document count as matching only when looking for possible matches.
+    fn matches_with(
+        &self,
+        pattern: &[PatternSegment],
+        path: &[PathSegment],
+        doc: Doc,
+        mode: Mode,
+    ) -> bool {
         match (pattern.first(), path.first()) {
-            (None, None) => true,
+            (None, None) => mode != Mode::Subtree,
             (None, Some(_)) => false,
-            (Some(_seg), None) => pattern
+            (Some(_), None) => {
+                mode == Mode::Prefix
+                    || pattern
+                        .iter()
+                        .all(|s| matches!(s, PatternSegment::DoubleWildcard))
+            }
+            (Some(PatternSegment::DoubleWildcard), Some(path_seg)) => {
+                mode == Mode::Prefix
+                    || self.matches_with(&pattern[1..], path, doc, mode)
+                    || self.matches_with(pattern, &path[1..], doc.child(path_seg), mode)
+            }
+            (Some(seg), Some(path_seg)) => {
+                seg.matches(path_seg, doc, self.case_insensitive)
+                    .unwrap_or(mode == Mode::Prefix)
+                    && self.matches_with(&pattern[1..], &path[1..], doc.child(path_seg), mode)
+            }
+        }
+    }
+}
+
+/// Returns true if the bracket at `start` is a selector (an index, `*`, a
+/// slice, a quoted key or a filter) rather than a character class. Malformed
+/// and unterminated brackets that look like selectors count as selectors so
+/// that they are reported.
+fn is_selector_at(chars: &[char], start: usize) -> bool {
+    let end = bracket_end(chars, start + 1);
+    if end == chars.len() {
+        return true;
+    }
+    let inner: String = chars[start + 1..end].iter().collect();
+    let inner = inner.trim();
+    let is_index = inner.strip_prefix('-').unwrap_or(inner);
+    inner.is_empty()
+        || inner == "*"
+        || inner.starts_with(['?', '\'', '"'])
+        || (!is_index.is_empty() && is_index.chars().all(|c| c.is_ascii_digit()))
+        || (inner.contains(':')
+            && inner
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == ':' || c.is_whitespace()))
+}
+
+/// Returns the end of a regex segment whose body starts at `start`: the
+/// position after its closing `/` and flags.
+fn regex_end(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while i < chars.len() && chars[i] != '/' {
+        i += if chars[i] == '\\' { 2 } else { 1 };
+    }
+    i += 1;
+    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+        i += 1;
+    }
+    i.min(chars.len())
+}
+
+/// Parses a regex segment (`/body/flags`). `\/` stands for a `/` in the body.
+fn parse_regex(chars: &[char]) -> Result<KeyRegex, String> {
+    let text: String = chars.iter().collect();
+    let close = (1..chars.len())
+        .filter(|i| chars[*i] == '/')
+        .find(|i| {
+            chars[1..*i]
                 .iter()
-                .all(|s| matches!(s, PatternSegment::DoubleWildcard)),
-            (Some(seg), Some(path_seg)) => match seg {
-                PatternSegment::Literal(lit) => {
-                    if lit == path_seg {
-                        self.matches_recursive(&pattern[1..], &path[1..])
-                    } else {
-                        false
+                .rev()
+                .take_while(|c| **c == '\\')
+                .count()
+                % 2
+                == 0
+        })
+        .ok_or_else(|| format!("unterminated regex '{}'", text))?;
+
+    let source: String = chars[1..close]
+        .iter()
+        .collect::<String>()
+        .replace("\\/", "/");
+    let flags: String = chars[close + 1..].iter().collect();
+    let case_insensitive = match flags.as_str() {
+        "" => false,
+        "i" => true,
+        _ => return Err(format!("unknown regex flags '{}' in '{}'", flags, text)),
+    };
+    KeyRegex::new(&source, case_insensitive).map_err(|e| format!("invalid regex '{}': {}", text, e))
+}
+
+/// Returns the position of the `]` closing a bracket that starts just
+/// before `start`, skipping over quoted strings and parentheses.
+fn bracket_end(chars: &[char], start: usize) -> usize {
+    let mut quote = None;
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match (quote, chars[i]) {
+            (Some(_), '\\') => i += 1,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(chars[i]),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ']') if depth <= 0 => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Parses the contents of a bracket: an index, `*`, a quoted key, a slice or
+/// a filter expression.
+fn parse_bracket(inner: &str) -> Result<PatternSegment, String> {
+    if inner == "*" {
+        return Ok(PatternSegment::AnyIndex);
+    }
+    if let Some(expression) = inner.strip_prefix('?') {
+        let mut parser = ExprParser::new(expression);
+        let predicate = parser.parse()?;
+        return Ok(PatternSegment::Filter(predicate));
+    }
+    if let Ok(index) = inner.parse::<i64>() {
+        return Ok(match usize::try_from(index) {
+            Ok(index) => PatternSegment::Index(index),
+            Err(_) => PatternSegment::Slice {
+                start: Some(index),
+                end: (index != -1).then_some(index + 1),
+                step: None,
+            },
+        });
+    }
+    if inner.contains(':') {
+        let bounds: Vec<&str> = inner.split(':').map(str::trim).collect();
+        if bounds.len() > 3 {
+            return Err(format!("invalid slice '[{}]'", inner));
+        }
+        let bound = |n: usize| -> Result<Option<i64>, String> {
+            match bounds.get(n).copied().unwrap_or("") {
+                "" => Ok(None),
+                b => b
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("invalid slice bound '{}'", b)),
+            }
+        };
+        return Ok(PatternSegment::Slice {
+            start: bound(0)?,
+            end: bound(1)?,
+            step: bound(2)?,
+        });
+    }
+
+    let chars: Vec<char> = inner.chars().collect();
+    match chars.first() {
+        Some(&q @ ('\'' | '"')) if chars.len() >= 2 && chars[chars.len() - 1] == q => {
+            let (key, next) = parse_string(&chars, 1, q);
+            if next != chars.len() {
+                return Err(format!("unexpected characters after key in '[{}]'", inner));
+            }
+            Ok(PatternSegment::Literal(key))
+        }
+        _ => Err(format!("invalid bracket expression '[{}]'", inner)),
+    }
+}
+
+/// Reads a quoted key starting just after the opening quote. Returns the key
+/// and the position after the closing quote.
+fn parse_quoted(chars: &[char], start: usize) -> (String, usize) {
+    parse_string(chars, start, '"')
+}
+
+/// Reads a string delimited by `quote` starting just after the opening
+/// quote, with backslash escapes. Returns the string and the position after
+/// the closing quote.
+fn parse_string(chars: &[char], start: usize, quote: char) -> (String, usize) {
+    let mut value = String::new();
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                value.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return (value, i + 1),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    (value, i)
+}
+
+/// Recursive-descent parser for filter expressions:
+///
+/// ```text
+/// or         := and ("||" and)*
+/// and        := unary ("&&" unary)*
+/// unary      := "!" unary | "(" or ")" | comparison
+/// comparison := operand (("==" | "!=" | "<" | "<=" | ">" | ">=") operand)?
+/// operand    := "@" ("." name | "[" index-or-key "]")* | string | number
+///             | "true" | "false" | "null"
+/// ```
+struct ExprParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn new(expression: &str) -> Self {
+        Self {
+            chars: expression.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Predicate, String> {
+        let predicate = self.parse_or()?;
+        self.skip_whitespace();
+        if self.pos < self.chars.len() {
+            return Err(format!("unexpected '{}' in filter", self.rest()));
+        }
+        Ok(predicate)
+    }
+
+    fn rest(&self) -> String {
+        self.chars[self.pos..].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes `token` if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let matches = token
+            .chars()
+            .enumerate()
+            .all(|(n, c)| self.chars.get(self.pos + n) == Some(&c));
+        if matches {
+            self.pos += token.chars().count();
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Result<Predicate, String> {
+        let mut left = self.parse_and()?;
+        while self.eat("||") {
+            left = Predicate::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Predicate, String> {
+        let mut left = self.parse_unary()?;
+        while self.eat("&&") {
+            left = Predicate::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Predicate, String> {
+        if !self.eat("!=") && self.eat("!") {
+            return Ok(Predicate::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat("(") {
+            let inner = self.parse_or()?;
+            if !self.eat(")") {
+                return Err("missing ')' in filter".to_string());
+            }
+            return Ok(inner);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Predicate, String> {
+        let left = self.parse_operand()?;
+        let op = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ]
+        .into_iter()
+        .find(|(token, _)| self.eat(token))
+        .map(|(_, op)| op);
+
+        match (op, left) {
+            (Some(op), left) => Ok(Predicate::Compare(left, op, self.parse_operand()?)),
+            (None, left @ Operand::Current(_)) => Ok(Predicate::Exists(left)),
+            (None, Operand::Literal(_)) => Err("expected a comparison in filter".to_string()),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos) {
+            Some('@') => {
+                self.pos += 1;
+                self.parse_relative_path().map(Operand::Current)
+            }
+            Some(&q @ ('\'' | '"')) => {
+                let (value, next) = parse_string(&self.chars, self.pos + 1, q);
+                if next > self.chars.len() || self.chars.get(next - 1) != Some(&q) {
+                    return Err("unterminated string in filter".to_string());
+                }
+                self.pos = next;
+                Ok(Operand::Literal(Node::String(value)))
+            }
+            Some(c) if c.is_ascii_digit() || *c == '-' => {
+                let word = self.take_while(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+                word.parse()
+                    .map(|n| Operand::Literal(Node::Number(n)))
+                    .map_err(|_| format!("invalid number '{}' in filter", word))
+            }
+            Some(c) if c.is_alphabetic() => match self.take_while(char::is_alphanumeric).as_str() {
+                "true" => Ok(Operand::Literal(Node::Bool(true))),
+                "false" => Ok(Operand::Literal(Node::Bool(false))),
+                "null" => Ok(Operand::Literal(Node::Null)),
+                word => Err(format!("unknown value '{}' in filter", word)),
+            },
+            _ => Err(format!("expected a value in filter at '{}'", self.rest())),
+        }
+    }
+
+    fn parse_relative_path(&mut self) -> Result<Vec<PathSegment>, String> {
+        let mut path = Vec::new();
+        loop {
+            match self.chars.get(self.pos) {
+                Some('.') => {
+                    self.pos += 1;
+                    let name = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+                    if name.is_empty() {
+                        return Err("expected a key after '.' in filter".to_string());
+                    }
+                    path.push(PathSegment::Key(name));
+                }
+                Some('[') => {
+                    let end = bracket_end(&self.chars, self.pos + 1);
+                    let inner: String = self.chars[self.pos + 1..end].iter().collect();
+                    path.push(match parse_bracket(inner.trim())? {
+                        PatternSegment::Literal(key) => PathSegment::Key(key),
+                        PatternSegment::Index(index) => PathSegment::Index(index),
+                        _ => return Err(format!("unsupported selector '[{}]' in filter", inner)),
+                    });
+                    self.pos = end + 1;
+                }
+                _ => return Ok(path),
+            }
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| predicate(*c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+}
+
+/// Formats the pattern in the syntax [`PathPattern::parse`] reads, so that
+/// parsing the result gives back an equivalent pattern. Case-insensitivity
+/// set by [`PathPattern::case_insensitive`] is not part of the syntax and
+/// is not shown.
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("!")?;
+        }
+        for (i, segment) in self.segments.iter().enumerate() {
+            let dot = if i > 0 { "." } else { "" };
+            match segment {
+                PatternSegment::Literal(key) => write!(f, "{}{}", dot, quote_key(key))?,
+                PatternSegment::SingleWildcard => write!(f, "{}*", dot)?,
+                PatternSegment::DoubleWildcard => write!(f, "{}**", dot)?,
+                PatternSegment::Glob(glob) => write!(f, "{}{}", dot, glob)?,
+                PatternSegment::Regex(regex) => {
+                    write!(f, "{}/{}/", dot, regex.as_str().replace('/', "\\/"))?;
+                    if regex.flagged {
+                        f.write_str("i")?;
                     }
                 }
-                PatternSegment::SingleWildcard => self.matches_recursive(&pattern[1..], &path[1..]),
-                PatternSegment::DoubleWildcard => {
-                    self.matches_recursive(&pattern[1..], path)
-                        || self.matches_recursive(pattern, &path[1..])
+                PatternSegment::Index(index) => write!(f, "[{}]", index)?,
+                PatternSegment::AnyIndex => f.write_str("[*]")?,
+                PatternSegment::Slice { start, end, step } => {
+                    let bound = |b: &Option<i64>| b.map(|b| b.to_string()).unwrap_or_default();
+                    write!(f, "[{}:{}", bound(start), bound(end))?;
+                    if step.is_some() {
+                        write!(f, ":{}", bound(step))?;
+                    }
+                    f.write_str("]")?;
                 }
+                PatternSegment::Filter(predicate) => write!(f, "[?({})]", predicate)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as its text, as [`PathPattern::try_parse`] reads it.
+impl Serialize for PathPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        PathPattern::try_parse(&pattern).map_err(de::Error::custom)
+    }
+}
+
+/// A condition on the old or new value of a change.
+#[derive(Debug, Clone)]
+pub enum ValuePredicate {
+    /// A string value matches a regular expression (not anchored)
+    Matches(Regex),
+    /// A number compares to a constant
+    Compare(CompareOp, f64),
+}
+
+impl ValuePredicate {
+    /// Parses a value predicate: a comparison operator followed by a number
+    /// (`> 3`, `<= 0.5`, `!= 0`), or otherwise a regular expression matched
+    /// against string values (`:latest$`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sdiff_rs::filter::ValuePredicate;
+    /// use sdiff_rs::Node;
+    ///
+    /// let latest = ValuePredicate::parse(":latest$").unwrap();
+    /// assert!(latest.evaluate(&Node::String("nginx:latest".to_string())));
+    ///
+    /// let large = ValuePredicate::parse(">= 10").unwrap();
+    /// assert!(large.evaluate(&Node::Number(12.0)));
+    /// assert!(!large.evaluate(&Node::String("12".to_string())));
+    /// ```
+    pub fn parse(expression: &str) -> Result<Self, SdiffError> {
+        let error = |message: String| SdiffError::InvalidPattern {
+            pattern: expression.to_string(),
+            message,
+        };
+
+        let trimmed = expression.trim_start();
+        let op = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| Some((op, trimmed.strip_prefix(token)?)));
+
+        if let Some((op, number)) = op {
+            if let Ok(number) = number.trim().parse() {
+                return Ok(ValuePredicate::Compare(op, number));
+            }
+        }
+        Regex::new(expression)
+            .map(ValuePredicate::Matches)
+            .map_err(|e| error(e.to_string()))
+    }
+
+    /// Returns true if `value` satisfies the predicate. Regular expressions
+    /// only match strings and comparisons only hold for numbers.
+    pub fn evaluate(&self, value: &Node) -> bool {
+        match (self, value) {
+            (ValuePredicate::Matches(regex), Node::String(s)) => regex.is_match(s),
+            (ValuePredicate::Compare(op, expected), Node::Number(n)) => match op {
+                CompareOp::Eq => n == expected,
+                CompareOp::Ne => n != expected,
+                CompareOp::Lt => n < expected,
+                CompareOp::Le => n <= expected,
+                CompareOp::Gt => n > expected,
+                CompareOp::Ge => n >= expected,
             },
+            _ => false,
+        }
+    }
+}
+
+/// Formats the predicate as [`ValuePredicate::parse`] reads it.
+impl fmt::Display for ValuePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValuePredicate::Matches(regex) => f.write_str(regex.as_str()),
+            ValuePredicate::Compare(op, number) => write!(f, "{} {}", op, number),
         }
     }
 }
 
+/// Serialized as its text, as [`ValuePredicate::parse`] reads it.
+impl Serialize for ValuePredicate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValuePredicate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expression = String::deserialize(deserializer)?;
+        ValuePredicate::parse(&expression).map_err(de::Error::custom)
+    }
+}
+
+/// What a [`FilterRule`] does with the paths its pattern matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Hide matching paths (`--ignore`); `!pattern` shows them again
+    Ignore,
+    /// Show matching paths, hiding every path no only rule matches
+    /// (`--only`); `!pattern` hides them again
+    Only,
+}
+
+/// A path pattern in a [`FilterConfig`], with what to do when it matches.
+///
+/// Serialized as `{"action", "pattern"}` with the pattern as it was written.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterRule {
+    pub action: FilterAction,
+    #[serde(skip)]
+    pub pattern: PathPattern,
+    /// The pattern as it was written, including a leading `!`
+    #[serde(rename = "pattern")]
+    pub source: String,
+}
+
+impl FilterRule {
+    /// Returns true if a path matched by this rule is shown.
+    pub fn includes(&self) -> bool {
+        (self.action == FilterAction::Only) != self.pattern.negated
+    }
+}
+
+/// Why a [`FilterConfig`] hid a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenBy {
+    /// The rule at this index in [`FilterConfig::rules`] matched last and
+    /// hides its matches
+    Rule(usize),
+    /// There are only rules and none of them matched the path
+    Unselected,
+    /// The change type or value filters rejected the change
+    Change,
+}
+
+/// How many changes one filter rule hid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCount {
+    /// The pattern as it was written
+    pub pattern: String,
+    pub action: FilterAction,
+    /// Number of changes the rule hid, including those inside subtrees it
+    /// pruned
+    pub hidden: usize,
+    /// Whether the pattern matched any path of the compared documents
+    pub matched: bool,
+}
+
+/// Counts of the changes a filter kept out of a diff.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::diff::compute_diff_filtered;
+/// use sdiff_rs::filter::FilterConfig;
+/// use sdiff_rs::{parse_json, DiffConfig};
+///
+/// let old = parse_json(r#"{"name": "app", "status": {"ready": 1, "pods": 2}}"#).unwrap();
+/// let new = parse_json(r#"{"name": "web", "status": {"ready": 3, "pods": 4}}"#).unwrap();
+///
+/// let filter = FilterConfig::new().ignore("status.**").ignore("stauts");
+/// let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+///
+/// assert_eq!(diff.hidden.total(), 2);
+/// assert_eq!(diff.hidden.rules[0].hidden, 2);
+/// assert!(!diff.hidden.rules[1].matched);
+/// ```
+///
+/// Serialized with the rule counts as `patterns` and an extra `total` field
+/// holding [`HiddenChanges::total`], which is ignored when reading counts
+/// back.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HiddenChanges {
+    /// Changes hidden by each rule, in the order of [`FilterConfig::rules`]
+    #[serde(rename = "patterns")]
+    pub rules: Vec<RuleCount>,
+    /// Changes hidden because no only pattern selected their path
+    pub unselected: usize,
+    /// Changes hidden by the change type and value filters
+    pub by_change: usize,
+}
+
+impl HiddenChanges {
+    /// Creates zero counts for the rules of `config`.
+    pub fn new(config: &FilterConfig) -> Self {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| RuleCount {
+                action: rule.action,
+                pattern: rule.source.clone(),
+                hidden: 0,
+                matched: false,
+            })
+            .collect();
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+
+    /// Counts one hidden change.
+    pub fn record(&mut self, reason: HiddenBy) {
+        match reason {
+            HiddenBy::Rule(i) => {
+                if let Some(rule) = self.rules.get_mut(i) {
+                    rule.hidden += 1;
+                }
+            }
+            HiddenBy::Unselected => self.unselected += 1,
+            HiddenBy::Change => self.by_change += 1,
+        }
+    }
+
+    /// Marks the rules whose patterns match some path in `old` or `new`.
+    pub fn mark_matched(&mut self, config: &FilterConfig, old: &Node, new: &Node) {
+        for (count, rule) in self.rules.iter_mut().zip(&config.rules) {
+            count.matched |= rule.pattern.matches_any_in(old) || rule.pattern.matches_any_in(new);
+        }
+    }
+
+    /// Adds the counts of another diff made with the same filter, as when
+    /// totalling the files of a directory diff.
+    pub fn merge(&mut self, other: &HiddenChanges) {
+        if self.rules.is_empty() {
+            self.rules = other.rules.clone();
+        } else {
+            for (count, theirs) in self.rules.iter_mut().zip(&other.rules) {
+                count.hidden += theirs.hidden;
+                count.matched |= theirs.matched;
+            }
+        }
+        self.unselected += other.unselected;
+        self.by_change += other.by_change;
+    }
+
+    /// Returns the total number of hidden changes.
+    pub fn total(&self) -> usize {
+        self.rules.iter().map(|rule| rule.hidden).sum::<usize>() + self.unselected + self.by_change
+    }
+
+    /// Returns the rules whose patterns matched nothing, which are likely
+    /// typos.
+    pub fn unmatched(&self) -> impl Iterator<Item = &RuleCount> {
+        self.rules.iter().filter(|rule| !rule.matched)
+    }
+}
+
+impl Serialize for HiddenChanges {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut hidden = serializer.serialize_struct("HiddenChanges", 4)?;
+        hidden.serialize_field("total", &self.total())?;
+        hidden.serialize_field("patterns", &self.rules)?;
+        hidden.serialize_field("unselected", &self.unselected)?;
+        hidden.serialize_field("by_change", &self.by_change)?;
+        hidden.end()
+    }
+}
+
 /// Configuration for filtering diff results.
-#[derive(Debug, Clone, Default)]
+///
+/// A change is kept if its path passes the rules, its type is one of
+/// `change_types` (if any are given), and its old and new values satisfy
+/// every value predicate.
+///
+/// Rules are evaluated in the order they were added and the last rule whose
+/// pattern matches a path decides whether it is shown. A path no rule
+/// matches is shown unless there are only rules. Negated patterns (`!`)
+/// reverse a rule, which makes exceptions possible:
+///
+/// ```
+/// use sdiff_rs::filter::FilterConfig;
+/// use sdiff_rs::Path;
+///
+/// let filter = FilterConfig::new()
+///     .ignore("metadata.**")
+///     .ignore("!metadata.labels.**");
+///
+/// assert!(!filter.should_include(&Path::root().key("metadata").key("uid")));
+/// assert!(filter.should_include(&Path::root().key("metadata").key("labels").key("app")));
+/// ```
+///
+/// Serialized as in the `config.filter` section of JSON output. Reading a
+/// config back fails if one of its patterns or predicates is malformed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "FilterConfigSpec")]
 pub struct FilterConfig {
-    /// Patterns for paths to ignore (exclude from output)
-    pub ignore_patterns: Vec<PathPattern>,
-    /// Patterns for paths to include (if non-empty, only these are shown)
-    pub only_patterns: Vec<PathPattern>,
+    /// Ignore and only rules, in evaluation order
+    pub rules: Vec<FilterRule>,
+    /// Match keys case-insensitively in every pattern
+    pub case_insensitive: bool,
+    /// Change types to keep (if non-empty, only these are shown)
+    pub change_types: Vec<ChangeType>,
+    /// Predicates the old value must satisfy; changes without an old value
+    /// never do
+    #[serde(rename = "old_value")]
+    pub old_value_predicates: Vec<ValuePredicate>,
+    /// Predicates the new value must satisfy; changes without a new value
+    /// never do
+    #[serde(rename = "new_value")]
+    pub new_value_predicates: Vec<ValuePredicate>,
+}
+
+/// The serialized form of a [`FilterConfig`].
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FilterConfigSpec {
+    rules: Vec<FilterRuleSpec>,
+    case_insensitive: bool,
+    change_types: Vec<ChangeType>,
+    old_value: Vec<ValuePredicate>,
+    new_value: Vec<ValuePredicate>,
+}
+
+#[derive(Deserialize)]
+struct FilterRuleSpec {
+    action: FilterAction,
+    pattern: String,
+}
+
+impl TryFrom<FilterConfigSpec> for FilterConfig {
+    type Error = SdiffError;
+
+    fn try_from(spec: FilterConfigSpec) -> Result<Self, Self::Error> {
+        let rules = spec
+            .rules
+            .into_iter()
+            .map(|rule| {
+                let pattern =
+                    PathPattern::try_parse(&rule.pattern)?.case_insensitive(spec.case_insensitive);
+                Ok(FilterRule {
+                    action: rule.action,
+                    pattern,
+                    source: rule.pattern,
+                })
+            })
+            .collect::<Result<_, SdiffError>>()?;
+        Ok(Self {
+            rules,
+            case_insensitive: spec.case_insensitive,
+            change_types: spec.change_types,
+            old_value_predicates: spec.old_value,
+            new_value_predicates: spec.new_value,
+        })
+    }
 }
 
 impl FilterConfig {
@@ -99,67 +1431,446 @@ impl FilterConfig {
         Self::default()
     }
 
-    pub fn ignore(mut self, pattern: &str) -> Self {
-        self.ignore_patterns.push(PathPattern::parse(pattern));
+    /// Adds a rule hiding the paths matching `pattern`, or showing them
+    /// again if it starts with `!`.
+    pub fn ignore(self, pattern: &str) -> Self {
+        self.rule(FilterAction::Ignore, pattern)
+    }
+
+    /// Adds a rule showing the paths matching `pattern`, or hiding them
+    /// again if it starts with `!`.
+    pub fn only(self, pattern: &str) -> Self {
+        self.rule(FilterAction::Only, pattern)
+    }
+
+    fn rule(mut self, action: FilterAction, source: &str) -> Self {
+        let pattern = PathPattern::parse(source).case_insensitive(self.case_insensitive);
+        self.rules.push(FilterRule {
+            action,
+            pattern,
+            source: source.to_string(),
+        });
+        self
+    }
+
+    /// Turns case-insensitive key matching on or off for the patterns added
+    /// so far and those added later.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        for rule in &mut self.rules {
+            rule.pattern = rule.pattern.clone().case_insensitive(case_insensitive);
+        }
+        self
+    }
+
+    /// Returns the patterns of the rules with `action`, in order.
+    pub fn patterns(&self, action: FilterAction) -> impl Iterator<Item = &PathPattern> {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.action == action)
+            .map(|rule| &rule.pattern)
+    }
+
+    fn has_only_rules(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.action == FilterAction::Only && !rule.pattern.negated)
+    }
+
+    /// Keeps only changes of `change_type`. Can be given several times.
+    pub fn only_type(mut self, change_type: ChangeType) -> Self {
+        self.change_types.push(change_type);
         self
     }
 
-    pub fn only(mut self, pattern: &str) -> Self {
-        self.only_patterns.push(PathPattern::parse(pattern));
+    /// Keeps only changes whose old value satisfies `predicate`.
+    pub fn old_value(mut self, predicate: ValuePredicate) -> Self {
+        self.old_value_predicates.push(predicate);
+        self
+    }
+
+    /// Keeps only changes whose new value satisfies `predicate`.
+    pub fn new_value(mut self, predicate: ValuePredicate) -> Self {
+        self.new_value_predicates.push(predicate);
         self
     }
 
     pub fn has_filters(&self) -> bool {
-        !self.ignore_patterns.is_empty() || !self.only_patterns.is_empty()
+        !self.rules.is_empty()
+            || !self.change_types.is_empty()
+            || !self.old_value_predicates.is_empty()
+            || !self.new_value_predicates.is_empty()
     }
 
-    pub fn should_include(&self, path: &[String]) -> bool {
-        for pattern in &self.ignore_patterns {
-            if pattern.matches(path) {
-                return false;
-            }
+    /// Returns true if the type and values of `change` pass the filter. The
+    /// path is checked separately by [`FilterConfig::should_include`].
+    pub fn accepts_change(&self, change: &Change) -> bool {
+        let satisfies = |predicates: &[ValuePredicate], value: &Option<Node>| {
+            predicates
+                .iter()
+                .all(|predicate| value.as_ref().is_some_and(|v| predicate.evaluate(v)))
+        };
+
+        (self.change_types.is_empty() || self.change_types.contains(&change.change_type))
+            && satisfies(&self.old_value_predicates, &change.old_value)
+            && satisfies(&self.new_value_predicates, &change.new_value)
+    }
+
+    pub fn should_include(&self, path: &[PathSegment]) -> bool {
+        self.includes(|pattern| pattern.matches(path))
+    }
+
+    /// Like [`FilterConfig::should_include`], evaluating filter segments
+    /// against the compared documents. A pattern applies if it matches in
+    /// either the old or the new document.
+    pub fn should_include_in(&self, path: &[PathSegment], old: &Node, new: &Node) -> bool {
+        self.includes(|pattern| pattern.matches_in(path, old) || pattern.matches_in(path, new))
+    }
+
+    /// Returns true if some path strictly inside `path` could be shown
+    /// even though `path` itself is not, because a rule after the one that
+    /// decided `path` reaches inside it. Filter segments are evaluated in
+    /// either document.
+    pub fn may_include_inside_in(&self, path: &[PathSegment], old: &Node, new: &Node) -> bool {
+        let start = self
+            .rules
+            .iter()
+            .rposition(|rule| {
+                rule.pattern.matches_in(path, old) || rule.pattern.matches_in(path, new)
+            })
+            .map_or(0, |i| i + 1);
+
+        self.rules[start..].iter().any(|rule| {
+            rule.includes()
+                && (rule.pattern.matches_prefix_in(path, old)
+                    || rule.pattern.matches_prefix_in(path, new))
+        })
+    }
+
+    /// Returns why the filter hides `change`, or `None` if it is kept.
+    ///
+    /// Filter segments never match here; use [`FilterConfig::hidden_by_in`]
+    /// for those.
+    pub fn hidden_by(&self, change: &Change) -> Option<HiddenBy> {
+        self.hide_reason(change, |pattern| pattern.matches(&change.path))
+    }
+
+    /// Like [`FilterConfig::hidden_by`], evaluating filter segments against
+    /// the compared documents.
+    pub fn hidden_by_in(&self, change: &Change, old: &Node, new: &Node) -> Option<HiddenBy> {
+        self.hide_reason(change, |pattern| {
+            pattern.matches_in(&change.path, old) || pattern.matches_in(&change.path, new)
+        })
+    }
+
+    fn hide_reason(
+        &self,
+        change: &Change,
+        matches: impl Fn(&PathPattern) -> bool,
+    ) -> Option<HiddenBy> {
+        match self.decide(matches) {
+            Err(reason) => Some(reason),
+            Ok(()) if !self.accepts_change(change) => Some(HiddenBy::Change),
+            Ok(()) => None,
         }
+    }
 
-        // If only patterns are specified, at least one must match
-        if !self.only_patterns.is_empty() {
-            for pattern in &self.only_patterns {
-                if pattern.matches(path) {
-                    return true;
-                }
-            }
-            return false;
+    fn includes(&self, matches: impl Fn(&PathPattern) -> bool) -> bool {
+        self.decide(matches).is_ok()
+    }
+
+    /// Applies the rules to a path, returning why it is hidden if it is.
+    fn decide(&self, matches: impl Fn(&PathPattern) -> bool) -> Result<(), HiddenBy> {
+        match self.rules.iter().rposition(|rule| matches(&rule.pattern)) {
+            Some(i) if self.rules[i].includes() => Ok(()),
+            Some(i) => Err(HiddenBy::Rule(i)),
+            None if self.has_only_rules() => Err(HiddenBy::Unselected),
+            None => Ok(()),
         }
+    }
+
+    /// Returns true if no path at or below `path` can pass the filter, so
+    /// the whole subtree can be skipped.
+    pub fn excludes_subtree(&self, path: &[PathSegment]) -> bool {
+        self.subtree_hidden_by(
+            |pattern| pattern.matches_subtree(path),
+            |pattern| pattern.matches_prefix(path),
+        )
+        .is_some()
+    }
+
+    /// Like [`FilterConfig::excludes_subtree`], evaluating filter segments
+    /// against the compared documents.
+    pub fn excludes_subtree_in(&self, path: &[PathSegment], old: &Node, new: &Node) -> bool {
+        self.subtree_hidden_by_in(path, old, new).is_some()
+    }
 
-        // No only patterns, and no ignore matched
-        true
+    /// Returns why no path at or below `path` can pass the filter, or `None`
+    /// if some path there might. Filter segments are evaluated in either
+    /// document.
+    pub fn subtree_hidden_by_in(
+        &self,
+        path: &[PathSegment],
+        old: &Node,
+        new: &Node,
+    ) -> Option<HiddenBy> {
+        self.subtree_hidden_by(
+            |pattern| {
+                pattern.matches_subtree_in(path, old) || pattern.matches_subtree_in(path, new)
+            },
+            |pattern| pattern.matches_prefix_in(path, old) || pattern.matches_prefix_in(path, new),
+        )
+    }
+
+    fn subtree_hidden_by(
+        &self,
+        subtree: impl Fn(&PathPattern) -> bool,
+        prefix: impl Fn(&PathPattern) -> bool,
+    ) -> Option<HiddenBy> {
+        // The last rule covering the whole subtree decides it, unless a
+        // later rule reaches inside and shows part of it again. Without such
+        // a rule, unmatched paths are hidden only if there are only rules.
+        let (reason, later) = match self.rules.iter().rposition(|rule| subtree(&rule.pattern)) {
+            Some(i) if self.rules[i].includes() => return None,
+            Some(i) => (HiddenBy::Rule(i), &self.rules[i + 1..]),
+            None if self.has_only_rules() => (HiddenBy::Unselected, &self.rules[..]),
+            None => return None,
+        };
+
+        let reopened = later
+            .iter()
+            .any(|rule| rule.includes() && prefix(&rule.pattern));
+        (!reopened).then_some(reason)
     }
 }
 
 /// Filters a diff based on the filter configuration.
+///
+/// Filter segments (`[?(...)]`) and negative slices need the compared
+/// documents and never match here; use [`filter_diff_in`] for those.
+/// Without the documents, a rule counts as matched if it matches a changed
+/// path or a path inside a changed container.
 pub fn filter_diff(diff: &Diff, config: &FilterConfig) -> Diff {
+    let mut hidden = HiddenChanges::new(config);
+    for (count, rule) in hidden.rules.iter_mut().zip(&config.rules) {
+        count.matched = diff
+            .changes
+            .iter()
+            .any(|change| rule.pattern.matches_prefix(&change.path));
+    }
+    retain_changes(diff, config, hidden, |change| config.hidden_by(change))
+}
+
+/// Filters a diff of `old` and `new` based on the filter configuration.
+pub fn filter_diff_in(diff: &Diff, config: &FilterConfig, old: &Node, new: &Node) -> Diff {
+    let mut hidden = HiddenChanges::new(config);
+    hidden.mark_matched(config, old, new);
+    retain_changes(diff, config, hidden, |change| {
+        config.hidden_by_in(change, old, new)
+    })
+}
+
+fn retain_changes(
+    diff: &Diff,
+    config: &FilterConfig,
+    mut hidden: HiddenChanges,
+    hidden_by: impl Fn(&Change) -> Option<HiddenBy>,
+) -> Diff {
     if !config.has_filters() {
         return diff.clone();
     }
 
-    let filtered_changes: Vec<Change> = diff
-        .changes
-        .iter()
-        .filter(|change| config.should_include(&change.path))
-        .cloned()
-        .collect();
-
-    let mut stats = DiffStats::new();
-    for change in &filtered_changes {
-        match change.change_type {
-            crate::diff::ChangeType::Added => stats.added += 1,
-            crate::diff::ChangeType::Removed => stats.removed += 1,
-            crate::diff::ChangeType::Modified => stats.modified += 1,
-            crate::diff::ChangeType::Unchanged => stats.unchanged += 1,
+    let mut filtered_changes = Vec::new();
+    for change in &diff.changes {
+        match hidden_by(change) {
+            Some(reason) => hidden.record(reason),
+            None => filtered_changes.push(change.clone()),
         }
     }
 
+    let stats = DiffStats::from_changes(&filtered_changes);
+
     Diff {
         changes: filtered_changes,
         stats,
+        hidden,
+    }
+}
+
+/// Matches a `/`-separated file path against a glob.
+///
+/// `*` matches any characters within a path component, `?` matches a single
+/// character and `**` matches any number of components. A glob without a `/`
+/// is matched against the file name only, like in `.gitignore`.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::filter::glob_match;
+///
+/// assert!(glob_match("config/prod/*.yaml", "config/prod/db.yaml"));
+/// assert!(glob_match("**/prod/*.yaml", "deploy/prod/db.yaml"));
+/// assert!(glob_match("*.json", "nested/dir/package.json"));
+/// assert!(!glob_match("config/*.yaml", "config/prod/db.yaml"));
+/// ```
+pub fn glob_match(glob: &str, path: &str) -> bool {
+    if !glob.contains('/') {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        return wildcard_match(glob, name);
+    }
+
+    let glob: Vec<&str> = glob.trim_start_matches('/').split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    glob_match_components(&glob, &path)
+}
+
+fn glob_match_components(glob: &[&str], path: &[&str]) -> bool {
+    match (glob.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            glob_match_components(&glob[1..], path)
+                || (!path.is_empty() && glob_match_components(glob, &path[1..]))
+        }
+        (Some(g), Some(p)) => wildcard_match(g, p) && glob_match_components(&glob[1..], &path[1..]),
+        _ => false,
+    }
+}
+
+/// Matches a single name against a pattern where `*` matches any run of
+/// characters, `?` matches exactly one, `[abc]` and `[a-z]` match one
+/// character from a class (`[!abc]` or `[^abc]` negate it) and a backslash
+/// escapes the next character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    wildcard_match_with(pattern, text, false)
+}
+
+fn wildcard_match_with(pattern: &str, text: &str, case_insensitive: bool) -> bool {
+    let pattern = wildcard_tokens(pattern);
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(WildcardToken::Star) => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some(token) if token.matches(text[t], case_insensitive) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let Some((star, matched)) = backtrack else {
+            return false;
+        };
+        p = star + 1;
+        t = matched + 1;
+        backtrack = Some((star, matched + 1));
+    }
+
+    pattern[p..]
+        .iter()
+        .all(|token| *token == WildcardToken::Star)
+}
+
+/// A single element of a wildcard pattern.
+#[derive(Debug, PartialEq)]
+enum WildcardToken {
+    Char(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `[...]`, as inclusive character ranges
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl WildcardToken {
+    fn matches(&self, c: char, case_insensitive: bool) -> bool {
+        let variants = |c: char| -> Vec<char> {
+            if case_insensitive {
+                c.to_lowercase()
+                    .chain(c.to_uppercase())
+                    .chain([c])
+                    .collect()
+            } else {
+                vec![c]
+            }
+        };
+        match self {
+            WildcardToken::Char(expected) => variants(c).contains(expected),
+            WildcardToken::Any => true,
+            WildcardToken::Star => false,
+            WildcardToken::Class { negated, ranges } => {
+                let in_class = variants(c)
+                    .into_iter()
+                    .any(|c| ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c)));
+                in_class != *negated
+            }
+        }
+    }
+}
+
+/// Splits a wildcard pattern into tokens. An unterminated `[` is an ordinary
+/// character.
+fn wildcard_tokens(pattern: &str) -> Vec<WildcardToken> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' => tokens.push(WildcardToken::Star),
+            '?' => tokens.push(WildcardToken::Any),
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                tokens.push(WildcardToken::Char(chars[i]));
+            }
+            '[' => match parse_class(&chars, i + 1) {
+                Some((token, end)) => {
+                    tokens.push(token);
+                    i = end;
+                }
+                None => tokens.push(WildcardToken::Char('[')),
+            },
+            c => tokens.push(WildcardToken::Char(c)),
+        }
+        i += 1;
+    }
+    tokens
+}
+
+/// Parses a character class starting just after its `[`. Returns the token
+/// and the position of the closing `]`.
+fn parse_class(chars: &[char], start: usize) -> Option<(WildcardToken, usize)> {
+    let mut i = start;
+    let negated = matches!(chars.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let first = i;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == ']' && i > first {
+            return Some((WildcardToken::Class { negated, ranges }, i));
+        }
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|c| *c != ']') {
+            ranges.push((c, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
     }
+    None
 }
@@ -1,6 +1,13 @@
 //! Git integration for sdiff.
 
+use crate::diff::DiffConfig;
+use crate::error::{OutputError, ParseError};
+use crate::merge::{merge, MergeResult};
+use crate::parser::{detect_format, parse_content_with_format};
+use crate::writer::write_content;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub type GitResult<T> = Result<T, GitError>;
@@ -18,40 +25,177 @@ pub enum GitError {
 
     #[error("Git command returned error: {0}")]
     GitError(String),
+
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    #[error(transparent)]
+    Output(#[from] OutputError),
+
+    #[error("A pre-commit hook already exists at {path}; add `sdiff-rs --git-pre-commit` to it manually")]
+    HookExists { path: String },
+
+    #[error("Failed to write {path}: {source}")]
+    WriteError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Git configuration keys managed by `install` and `uninstall`.
+const CONFIG_KEYS: &[&str] = &[
+    "difftool.sdiff.cmd",
+    "difftool.sdiff.prompt",
+    "diff.sdiff.command",
+    "merge.sdiff.name",
+    "merge.sdiff.driver",
+    "diff.sdiff.textconv",
+];
+
+/// File extensions that `install` associates with sdiff in `.gitattributes`.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["json", "yaml", "yml", "toml"];
+
+/// The git configuration file that sdiff is installed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigScope {
+    /// The current repository (`git config --local`)
+    Local,
+    /// The current user (`git config --global`)
+    #[default]
+    Global,
+    /// All users on the machine (`git config --system`)
+    System,
+}
+
+impl ConfigScope {
+    /// All scopes, in the order git reads them.
+    pub const ALL: [ConfigScope; 3] =
+        [ConfigScope::System, ConfigScope::Global, ConfigScope::Local];
+
+    /// Returns the scope name as used by `git config`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigScope::Local => "local",
+            ConfigScope::Global => "global",
+            ConfigScope::System => "system",
+        }
+    }
+
+    fn flag(&self) -> &'static str {
+        match self {
+            ConfigScope::Local => "--local",
+            ConfigScope::Global => "--global",
+            ConfigScope::System => "--system",
+        }
+    }
+}
+
+/// Options for `install` and `uninstall`.
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    /// Configuration file to write to
+    pub scope: ConfigScope,
+    /// Also register sdiff as a textconv filter (`diff.sdiff.textconv`), so
+    /// that commands which do not run external diff tools, such as
+    /// `git log -p`, show a canonical rendering of structured files.
+    pub textconv: bool,
+    /// Add (or, when uninstalling, remove) `diff=sdiff merge=sdiff` entries
+    /// in the repository's `.gitattributes`
+    pub attributes: bool,
+    /// File extensions to configure in `.gitattributes`
+    pub extensions: Vec<String>,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            scope: ConfigScope::default(),
+            textconv: false,
+            attributes: false,
+            extensions: DEFAULT_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
 }
 
 /// Installs sdiff as a git difftool and diff driver.
-pub fn install() -> GitResult<()> {
+pub fn install(options: &InstallOptions) -> GitResult<()> {
     let sdiff_path = get_executable_path()?;
+    let scope = options.scope;
 
     run_git_config(
+        scope,
         "difftool.sdiff.cmd",
         &format!("{} \"$LOCAL\" \"$REMOTE\"", sdiff_path),
     )?;
-    run_git_config("diff.sdiff.command", &sdiff_path)?;
-    run_git_config("difftool.sdiff.prompt", "false")?;
+    run_git_config(scope, "diff.sdiff.command", &sdiff_path)?;
+    run_git_config(scope, "difftool.sdiff.prompt", "false")?;
+    run_git_config(scope, "merge.sdiff.name", "sdiff structured merge")?;
+    run_git_config(
+        scope,
+        "merge.sdiff.driver",
+        &format!("{} --git-merge-driver %O %A %B %P", sdiff_path),
+    )?;
+    if options.textconv {
+        run_git_config(
+            scope,
+            "diff.sdiff.textconv",
+            &format!("{} --textconv", sdiff_path),
+        )?;
+    }
 
-    println!("Successfully installed sdiff as git difftool.");
+    println!(
+        "Successfully installed sdiff as git difftool and merge driver ({} config).",
+        scope.name()
+    );
     println!();
     println!("Usage:");
     println!("  git difftool -t sdiff HEAD~1 -- file.json");
     println!("  git difftool -t sdiff branch1 branch2 -- config.yaml");
     println!();
-    println!("To use automatically for specific files, add to .gitattributes:");
-    println!("  *.json diff=sdiff");
-    println!("  *.yaml diff=sdiff");
-    println!("  *.toml diff=sdiff");
+
+    if options.attributes {
+        let path = gitattributes_path()?;
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let updated = add_gitattributes(&content, &options.extensions);
+        if updated != content {
+            write_file(&path.to_string_lossy(), &updated)?;
+            println!("Updated {}.", path.display());
+        } else {
+            println!("{} is already up to date.", path.display());
+        }
+    } else {
+        println!("To use automatically for specific files, add to .gitattributes");
+        println!("(or run again with --git-attributes):");
+        for ext in &options.extensions {
+            println!("  *.{} diff=sdiff merge=sdiff", ext);
+        }
+    }
 
     Ok(())
 }
 
 /// Uninstalls sdiff from git configuration.
-pub fn uninstall() -> GitResult<()> {
-    run_git_config_unset("difftool.sdiff.cmd")?;
-    run_git_config_unset("difftool.sdiff.prompt")?;
-    run_git_config_unset("diff.sdiff.command")?;
+pub fn uninstall(options: &InstallOptions) -> GitResult<()> {
+    for key in CONFIG_KEYS {
+        run_git_config_unset(options.scope, key)?;
+    }
 
-    println!("Successfully uninstalled sdiff from git configuration.");
+    println!(
+        "Successfully uninstalled sdiff from {} git configuration.",
+        options.scope.name()
+    );
+
+    if options.attributes {
+        let path = gitattributes_path()?;
+        if let Ok(content) = fs::read_to_string(&path) {
+            let updated = remove_gitattributes(&content);
+            if updated != content {
+                write_file(&path.to_string_lossy(), &updated)?;
+                println!("Removed sdiff entries from {}.", path.display());
+            }
+        }
+    }
 
     Ok(())
 }
@@ -59,37 +203,35 @@ pub fn uninstall() -> GitResult<()> {
 /// Shows the current git configuration status for sdiff.
 pub fn status() -> GitResult<()> {
     println!("Git sdiff configuration status:");
-    println!();
 
-    match get_git_config("difftool.sdiff.cmd") {
-        Ok(value) => {
-            println!("  difftool.sdiff.cmd: {}", value);
-        }
-        Err(_) => {
-            println!("  difftool.sdiff.cmd: (not configured)");
+    for scope in ConfigScope::ALL {
+        println!();
+        println!("  [{}]", scope.name());
+        for key in CONFIG_KEYS {
+            match get_git_config_in(scope, key) {
+                Ok(value) => {
+                    println!("    {}: {}", key, value);
+                }
+                Err(_) => {
+                    println!("    {}: (not configured)", key);
+                }
+            }
         }
     }
 
-    match get_git_config("difftool.sdiff.prompt") {
-        Ok(value) => {
-            println!("  difftool.sdiff.prompt: {}", value);
-        }
-        Err(_) => {
-            println!("  difftool.sdiff.prompt: (not configured)");
-        }
-    }
+    println!();
 
-    match get_git_config("diff.sdiff.command") {
-        Ok(value) => {
-            println!("  diff.sdiff.command: {}", value);
-        }
-        Err(_) => {
-            println!("  diff.sdiff.command: (not configured)");
+    if repo_root().is_ok() {
+        println!("Attributes in this repository:");
+        for ext in DEFAULT_EXTENSIONS {
+            let sample = format!("file.{}", ext);
+            let diff = check_attr(&sample, "diff")?;
+            let merge = check_attr(&sample, "merge")?;
+            println!("  *.{}: diff={} merge={}", ext, diff, merge);
         }
+        println!();
     }
 
-    println!();
-
     let has_config = get_git_config("difftool.sdiff.cmd").is_ok()
         || get_git_config("diff.sdiff.command").is_ok();
 
@@ -102,27 +244,401 @@ pub fn status() -> GitResult<()> {
         println!("sdiff is not configured. Run 'sdiff --git-install' to set up.");
     }
 
+    if get_git_config("merge.sdiff.driver").is_ok() {
+        println!();
+        println!("sdiff is configured as a git merge driver.");
+        println!("Enable it per file type in .gitattributes, e.g. '*.yaml merge=sdiff'.");
+    }
+
     Ok(())
 }
 
+/// Adds `diff=sdiff merge=sdiff` for each extension to `.gitattributes`
+/// content.
+///
+/// Existing `*.ext` lines are updated in place, replacing any other `diff` or
+/// `merge` attribute; missing extensions are appended. Applying this more than
+/// once leaves the content unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::git::add_gitattributes;
+///
+/// let content = "*.json text\n";
+/// let updated = add_gitattributes(content, &["json".to_string(), "yaml".to_string()]);
+/// assert_eq!(updated, "*.json text diff=sdiff merge=sdiff\n*.yaml diff=sdiff merge=sdiff\n");
+/// assert_eq!(add_gitattributes(&updated, &["json".to_string()]), updated);
+/// ```
+pub fn add_gitattributes(content: &str, extensions: &[String]) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    for ext in extensions {
+        let pattern = format!("*.{}", ext);
+        let existing = lines
+            .iter_mut()
+            .find(|line| line.split_whitespace().next() == Some(pattern.as_str()));
+
+        match existing {
+            Some(line) => {
+                let mut attrs: Vec<&str> = line
+                    .split_whitespace()
+                    .skip(1)
+                    .filter(|attr| !is_driver_attribute(attr))
+                    .collect();
+                attrs.extend(["diff=sdiff", "merge=sdiff"]);
+                *line = format!("{} {}", pattern, attrs.join(" "));
+            }
+            None => lines.push(format!("{} diff=sdiff merge=sdiff", pattern)),
+        }
+    }
+
+    join_lines(&lines)
+}
+
+/// Removes `diff=sdiff` and `merge=sdiff` from `.gitattributes` content,
+/// dropping lines that are left without any attributes.
+pub fn remove_gitattributes(content: &str) -> String {
+    let lines: Vec<String> = content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                return Some(line.to_string());
+            };
+            if pattern.starts_with('#') {
+                return Some(line.to_string());
+            }
+
+            let attrs: Vec<&str> = parts.collect();
+            if !attrs
+                .iter()
+                .any(|a| *a == "diff=sdiff" || *a == "merge=sdiff")
+            {
+                return Some(line.to_string());
+            }
+
+            let remaining: Vec<&str> = attrs
+                .into_iter()
+                .filter(|a| *a != "diff=sdiff" && *a != "merge=sdiff")
+                .collect();
+            if remaining.is_empty() {
+                None
+            } else {
+                Some(format!("{} {}", pattern, remaining.join(" ")))
+            }
+        })
+        .collect();
+
+    join_lines(&lines)
+}
+
+fn is_driver_attribute(attr: &str) -> bool {
+    let name = attr.trim_start_matches(['-', '!']);
+    let name = name.split('=').next().unwrap_or(name);
+    name == "diff" || name == "merge"
+}
+
+fn join_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+fn gitattributes_path() -> GitResult<PathBuf> {
+    Ok(repo_root()?.join(".gitattributes"))
+}
+
+/// Returns the value of a git attribute for a path in the current repository
+/// (`unspecified` if unset).
+fn check_attr(path: &str, attr: &str) -> GitResult<String> {
+    let output = Command::new("git")
+        .args(["check-attr", attr, "--", path])
+        .output()
+        .map_err(|_| GitError::GitNotFound)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::GitError(stderr.into_owned()));
+    }
+
+    // Output format: "<path>: <attr>: <value>"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .trim()
+        .rsplit(": ")
+        .next()
+        .unwrap_or("unspecified")
+        .to_string())
+}
+
 /// Detects if invoked with git's 7-argument diff driver protocol.
 /// Returns `Some((old_file, new_file))` if 7-arg mode is detected.
+///
+/// For added or deleted files git passes `/dev/null` as the missing file and
+/// `.` in place of its hash.
 pub fn detect_git_diff_driver_args(args: &[String]) -> Option<(String, String)> {
     if args.len() != 7 {
         return None;
     }
 
-    let old_hex = &args[2];
-    let new_hex = &args[5];
+    let old_file = &args[1];
+    let new_file = &args[4];
 
-    if !is_git_hash(old_hex) || !is_git_hash(new_hex) {
+    let valid_hash = |file: &str, hex: &str| is_git_hash(hex) || (is_null_file(file) && hex == ".");
+    if !valid_hash(old_file, &args[2]) || !valid_hash(new_file, &args[5]) {
         return None;
     }
 
-    let old_file = args[1].clone();
-    let new_file = args[4].clone();
+    if is_null_file(old_file) && is_null_file(new_file) {
+        return None;
+    }
+
+    Some((old_file.clone(), new_file.clone()))
+}
+
+/// Runs sdiff as a git merge driver (`%O %A %B %P`).
+///
+/// Merges `ours` and `theirs` against `base` and overwrites `ours` with the
+/// result, written in the format implied by `pathname` (git's temporary files
+/// have no extension). If `pathname` has no recognized extension, the result
+/// is written in the format `ours` was parsed as. When paths collide, the merged file keeps our value
+/// and the conflicts are returned for the caller to report; nothing else is
+/// written to the working tree, so no report can be committed by accident.
+pub fn run_merge_driver(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    pathname: &str,
+    config: &DiffConfig,
+) -> GitResult<MergeResult> {
+    let format = detect_format(Path::new(pathname));
+
+    let read = |path: &str| -> GitResult<_> {
+        let content =
+            fs::read_to_string(path).map_err(|e| ParseError::read_error(path.to_string(), e))?;
+        Ok(parse_content_with_format(&content, format, pathname)?)
+    };
+
+    let (base_node, _) = read(base)?;
+    let (ours_node, ours_format) = read(ours)?;
+    let (theirs_node, _) = read(theirs)?;
+
+    let result = merge(&base_node, &ours_node, &theirs_node, config);
+
+    let document = write_content(&result.merged, ours_format)?;
+    write_file(ours, &document)?;
+
+    Ok(result)
+}
+
+fn write_file(path: &str, content: &str) -> GitResult<()> {
+    fs::write(path, content).map_err(|e| GitError::WriteError {
+        path: path.to_string(),
+        source: e,
+    })
+}
+
+/// Reads the contents of `path` as of revision `rev` (e.g. `HEAD~3`).
+///
+/// `path` is resolved relative to the current directory, like the paths
+/// given to `git diff`.
+pub fn read_blob(rev: &str, path: &str) -> GitResult<String> {
+    run_git(&["show", &blob_spec(rev, path)?])
+}
+
+/// Like [`read_blob`], returning None if `path` does not exist at `rev`.
+/// An unknown revision is still an error.
+pub fn read_blob_if_exists(rev: &str, path: &str) -> GitResult<Option<String>> {
+    let spec = blob_spec(rev, path)?;
+    let exists = Command::new("git")
+        .args(["cat-file", "-e", &spec])
+        .output()
+        .map_err(|_| GitError::GitNotFound)?
+        .status
+        .success();
+    if !exists && is_revision(rev) {
+        return Ok(None);
+    }
+    run_git(&["show", &spec]).map(Some)
+}
+
+/// Returns the `rev:path` object name of `path` as of revision `rev`.
+fn blob_spec(rev: &str, path: &str) -> GitResult<String> {
+    Ok(if Path::new(path).is_absolute() {
+        let root = repo_root()?;
+        let relative = Path::new(path).strip_prefix(&root).map_err(|_| {
+            GitError::GitError(format!("{} is outside repository {}", path, root.display()))
+        })?;
+        format!("{}:{}", rev, relative.to_string_lossy().replace('\\', "/"))
+    } else {
+        format!("{}:./{}", rev, path.trim_start_matches("./"))
+    })
+}
+
+/// Reads the contents of a file at revision `rev`, where `path` is relative
+/// to the repository root (as returned by `list_files`).
+pub fn read_repo_file(rev: &str, path: &str) -> GitResult<String> {
+    run_git(&["show", &format!("{}:{}", rev, path)])
+}
+
+/// Lists all files in revision `rev`, relative to the repository root.
+pub fn list_files(rev: &str) -> GitResult<Vec<String>> {
+    let output = run_git(&["ls-tree", "-r", "-z", "--name-only", "--full-tree", rev])?;
+    Ok(output
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns true if `spec` names a commit or tree in the current repository.
+pub fn is_revision(spec: &str) -> bool {
+    Command::new("git")
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{tree}}", spec),
+        ])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// A commit, as listed by `file_log`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// Full commit hash
+    pub hash: String,
+    /// Author name
+    pub author: String,
+    /// Author date in strict ISO 8601 format
+    pub date: String,
+    /// First line of the commit message
+    pub subject: String,
+}
+
+/// Lists the commits that touched `path`, newest first.
+///
+/// `path` is resolved relative to the current directory.
+pub fn file_log(path: &str) -> GitResult<Vec<Commit>> {
+    let output = run_git(&["log", "--format=%H%x1f%an%x1f%aI%x1f%s%x1e", "--", path])?;
+
+    Ok(output
+        .split('\x1e')
+        .map(str::trim)
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let mut fields = record.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Lists the files staged for commit, relative to the repository root.
+///
+/// Renames are reported as a deletion and an addition.
+pub fn staged_files() -> GitResult<Vec<String>> {
+    let output = run_git(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--no-renames",
+        "--diff-filter=ACMD",
+    ])?;
+    Ok(output
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Reads the staged contents of a file, where `path` is relative to the
+/// repository root.
+pub fn read_staged_file(path: &str) -> GitResult<String> {
+    run_git(&["show", &format!(":{}", path)])
+}
+
+/// Marker line identifying hooks written by `install_hook`.
+const HOOK_MARKER: &str = "# Installed by sdiff";
+
+/// Installs a pre-commit hook that runs `sdiff --git-pre-commit`.
+///
+/// An existing hook that was not written by sdiff is left untouched and
+/// reported as `GitError::HookExists`. Returns the path of the hook.
+pub fn install_hook() -> GitResult<PathBuf> {
+    let hooks_dir = PathBuf::from(run_git(&["rev-parse", "--git-path", "hooks"])?.trim());
+    let hook = hooks_dir.join("pre-commit");
+    let hook_path = hook.to_string_lossy().into_owned();
+
+    if let Ok(existing) = fs::read_to_string(&hook) {
+        if !existing.contains(HOOK_MARKER) {
+            return Err(GitError::HookExists { path: hook_path });
+        }
+    }
+
+    fs::create_dir_all(&hooks_dir).map_err(|e| GitError::WriteError {
+        path: hooks_dir.to_string_lossy().into_owned(),
+        source: e,
+    })?;
+
+    let exe_path = get_executable_path()?;
+    let script = format!(
+        "#!/bin/sh\n{}\nexec \"{}\" --git-pre-commit\n",
+        HOOK_MARKER, exe_path
+    );
+    write_file(&hook_path, &script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).map_err(|e| {
+            GitError::WriteError {
+                path: hook_path.clone(),
+                source: e,
+            }
+        })?;
+    }
+
+    Ok(hook)
+}
+
+/// Runs a git command and returns its standard output.
+fn run_git(args: &[&str]) -> GitResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|_| GitError::GitNotFound)?;
 
-    Some((old_file, new_file))
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::GitError(stderr.trim().to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the top-level directory of the current git repository.
+pub fn repo_root() -> GitResult<PathBuf> {
+    let root = run_git(&["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(root.trim()))
+}
+
+/// Returns the top-level directory of the git repository containing `dir`.
+pub fn repo_root_of(dir: &Path) -> GitResult<PathBuf> {
+    let dir = dir.to_string_lossy();
+    let root = run_git(&["-C", &dir, "rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(root.trim()))
 }
 
 /// Checks if a file path represents a deleted or new file (/dev/null).
@@ -150,9 +666,9 @@ fn get_executable_path() -> GitResult<String> {
     // }
 }
 
-fn run_git_config(key: &str, value: &str) -> GitResult<()> {
+fn run_git_config(scope: ConfigScope, key: &str, value: &str) -> GitResult<()> {
     let output = Command::new("git")
-        .args(["config", "--global", key, value])
+        .args(["config", scope.flag(), key, value])
         .output()
         .map_err(|_| GitError::GitNotFound)?;
 
@@ -164,9 +680,9 @@ fn run_git_config(key: &str, value: &str) -> GitResult<()> {
     Ok(())
 }
 
-fn run_git_config_unset(key: &str) -> GitResult<()> {
+fn run_git_config_unset(scope: ConfigScope, key: &str) -> GitResult<()> {
     let output = Command::new("git")
-        .args(["config", "--global", "--unset", key])
+        .args(["config", scope.flag(), "--unset", key])
         .output()
         .map_err(|_| GitError::GitNotFound)?;
 
@@ -178,9 +694,49 @@ fn run_git_config_unset(key: &str) -> GitResult<()> {
     Ok(())
 }
 
+/// Returns all `<section>.<key>` entries from the effective git configuration
+/// (all scopes), with the section prefix stripped from each key.
+pub fn get_git_config_section(section: &str) -> GitResult<Vec<(String, String)>> {
+    let output = Command::new("git")
+        .args(["config", "--get-regexp", &format!("^{}\\.", section)])
+        .output()
+        .map_err(|_| GitError::GitNotFound)?;
+
+    // Exit code 1 means no matching keys were found
+    if !output.status.success() {
+        if output.status.code() == Some(1) {
+            return Ok(Vec::new());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::GitError(stderr.into_owned()));
+    }
+
+    let prefix = format!("{}.", section);
+    let entries = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            key.strip_prefix(&prefix)
+                .map(|k| (k.to_string(), value.to_string()))
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Reads a key from the effective git configuration (all scopes).
 fn get_git_config(key: &str) -> GitResult<String> {
+    read_git_config(&["config", "--get", key], key)
+}
+
+/// Reads a key from a single git configuration scope.
+fn get_git_config_in(scope: ConfigScope, key: &str) -> GitResult<String> {
+    read_git_config(&["config", scope.flag(), "--get", key], key)
+}
+
+fn read_git_config(args: &[&str], key: &str) -> GitResult<String> {
     let output = Command::new("git")
-        .args(["config", "--global", "--get", key])
+        .args(args)
         .output()
         .map_err(|_| GitError::GitNotFound)?;
 
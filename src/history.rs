@@ -0,0 +1,162 @@
+//! Semantic history of a file across git commits.
+//!
+//! This module walks the commits that touched a file, parses the file as of
+//! each commit and diffs every version against the previous one. Commits that
+//! only reformatted the file produce no changes and are left out, so the
+//! history shows when values actually changed.
+//!
+//! [`blame`] builds on the history to find, for every value in the current
+//! version of a file, the last commit that changed it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sdiff_rs::filter::FilterConfig;
+//! use sdiff_rs::history::file_history;
+//! use sdiff_rs::DiffConfig;
+//!
+//! let filter = FilterConfig::new().only("spec.replicas");
+//! let history = file_history("deploy.yaml", &DiffConfig::default(), &filter).unwrap();
+//!
+//! for entry in &history {
+//!     println!("{} {}", entry.commit.hash, entry.commit.subject);
+//! }
+//! ```
+
+use crate::diff::{
+    compute_added_diff, compute_diff, compute_diff_filtered, compute_removed_diff, Diff, DiffConfig,
+};
+use crate::error::SdiffError;
+use crate::filter::{filter_diff_in, FilterConfig};
+use crate::git::{self, Commit};
+use crate::parser::{detect_format, parse_content, parse_file};
+use crate::path::PathSegment;
+use crate::tree::Node;
+use std::path::Path;
+
+/// A commit that changed the file, with the changes it made.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// The commit
+    pub commit: Commit,
+    /// Changes relative to the previous version of the file
+    pub diff: Diff,
+}
+
+/// The last commit that changed a value.
+#[derive(Debug, Clone)]
+pub struct BlameEntry {
+    /// Path to the value
+    pub path: crate::path::Path,
+    /// Current value
+    pub value: Node,
+    /// Last commit that changed the value, or None if the value has
+    /// uncommitted changes
+    pub commit: Option<Commit>,
+}
+
+/// Returns the commits that semantically changed `path`, newest first.
+///
+/// Each commit is compared with the previous commit that touched the file;
+/// the first commit reports every value as added, and a commit that deletes
+/// the file reports every value as removed. Only changes that pass `filter`
+/// are kept, and commits left without changes are omitted. Versions that fail
+/// to parse are skipped.
+pub fn file_history(
+    path: &str,
+    config: &DiffConfig,
+    filter: &FilterConfig,
+) -> Result<Vec<HistoryEntry>, SdiffError> {
+    let format = detect_format(Path::new(path));
+    let mut commits = git::file_log(path)?;
+    commits.reverse();
+
+    let mut entries = Vec::new();
+    let mut previous: Option<Node> = None;
+
+    for commit in commits {
+        let current = match git::read_blob(&commit.hash, path) {
+            Ok(content) => {
+                let source = format!("{}:{}", commit.hash, path);
+                match parse_content(&content, format, &source) {
+                    Ok(node) => Some(node),
+                    Err(_) => continue,
+                }
+            }
+            Err(_) => None,
+        };
+
+        let diff = match (&previous, &current) {
+            (Some(old), Some(new)) => compute_diff_filtered(old, new, config, filter),
+            (None, Some(new)) => filter_diff_in(&compute_added_diff(new), filter, &Node::Null, new),
+            (Some(old), None) => {
+                filter_diff_in(&compute_removed_diff(old), filter, old, &Node::Null)
+            }
+            (None, None) => continue,
+        };
+        previous = current;
+
+        if !diff.is_empty() {
+            entries.push(HistoryEntry { commit, diff });
+        }
+    }
+
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Finds the last commit that changed each leaf value in `path`.
+///
+/// The file is read from the working tree. Values that differ from `HEAD`
+/// are reported without a commit. Commits that only reformatted the file are
+/// never blamed. Entries are returned in path order, restricted to the paths
+/// that pass `filter`.
+pub fn blame(
+    path: &str,
+    config: &DiffConfig,
+    filter: &FilterConfig,
+) -> Result<Vec<BlameEntry>, SdiffError> {
+    let current = parse_file(Path::new(path))?;
+    let history = file_history(path, config, &FilterConfig::new())?;
+
+    let uncommitted = match git::read_blob("HEAD", path) {
+        Ok(content) => {
+            let source = format!("HEAD:{}", path);
+            let head = parse_content(&content, detect_format(Path::new(path)), &source)?;
+            compute_diff(&head, &current, config)
+        }
+        Err(_) => compute_added_diff(&current),
+    };
+
+    let entries = compute_added_diff(&current)
+        .changes
+        .into_iter()
+        .filter(|leaf| filter.should_include_in(&leaf.path, &Node::Null, &current))
+        .map(|leaf| {
+            let commit = if touches(&uncommitted, &leaf.path) {
+                None
+            } else {
+                history
+                    .iter()
+                    .find(|entry| touches(&entry.diff, &leaf.path))
+                    .map(|entry| entry.commit.clone())
+            };
+            BlameEntry {
+                path: leaf.path,
+                value: leaf.new_value.unwrap_or(Node::Null),
+                commit,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Returns true if any change in `diff` affects the value at `path`, either
+/// directly or by changing one of its ancestors or descendants.
+fn touches(diff: &Diff, path: &[PathSegment]) -> bool {
+    diff.changes.iter().any(|change| {
+        let len = change.path.len().min(path.len());
+        change.path[..len] == path[..len]
+    })
+}
@@ -25,19 +25,35 @@
 //! # }
 //! ```
 
+pub mod config;
 pub mod diff;
+pub mod dir;
 pub mod error;
 pub mod filter;
 pub mod git;
+pub mod history;
+pub mod merge;
 pub mod output;
 pub mod parser;
+pub mod path;
+pub mod policy;
+pub mod serializer;
 pub mod tree;
+pub mod writer;
 
 // Re-export commonly used types for convenience
-pub use diff::{compute_diff, ArrayDiffStrategy, Change, ChangeType, Diff, DiffConfig};
-pub use error::{OutputError, ParseError, SdiffError};
+pub use diff::{
+    compute_diff, compute_diff_filtered, diff_values, ArrayDiffStrategy, Change, ChangeType, Diff,
+    DiffConfig,
+};
+pub use error::{OutputError, ParseError, SdiffError, SerializeError};
+pub use merge::{merge, MergeConflict, MergeResult};
 pub use output::{format_diff, OutputFormat, OutputOptions};
 pub use parser::{
-    parse_content, parse_file, parse_json, parse_stdin, parse_toml, parse_yaml, FormatHint,
+    detect_format, parse_content, parse_content_with_format, parse_file, parse_file_with_format,
+    parse_json, parse_stdin, parse_toml, parse_yaml, FormatHint,
 };
+pub use path::{Path, PathSegment};
+pub use serializer::to_node;
 pub use tree::Node;
+pub use writer::write_content;
@@ -1,17 +1,27 @@
 use anyhow::{bail, Context, Result};
-use clap::{Parser, ValueEnum};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use sdiff_rs::{
-    compute_diff,
-    filter::filter_diff,
-    filter::FilterConfig,
-    format_diff,
+    compute_diff_filtered,
+    config::{ConfigFiles, Settings},
+    detect_format,
+    diff::{compute_added_diff, compute_removed_diff},
+    dir::{diff_sources_filtered, Source},
+    filter::{filter_diff_in, FilterAction, HiddenChanges},
     git::{self, detect_git_diff_driver_args, is_null_file},
-    parse_content, parse_file, ArrayDiffStrategy, DiffConfig, FormatHint, OutputFormat,
-    OutputOptions,
+    history::{blame, file_history},
+    merge,
+    output::{
+        format_blame, format_conflicts, format_diff_with_context, format_dir_diff_with_context,
+        format_history, format_textconv, format_violations, DiffContext, InputInfo,
+    },
+    parse_content_with_format, parse_file, parse_file_with_format, parse_stdin,
+    policy::{Policy, POLICY_FILE_NAME},
+    write_content, ArrayDiffStrategy, Diff, FormatHint, Node, OutputFormat,
 };
 use std::env;
+use std::fs;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 /// SDIFF - Semantic diff tool for structured data
@@ -25,67 +35,179 @@ use std::process;
 #[command(version)]
 #[command(about = "Semantic diff tool for structured data", long_about = None)]
 #[command(author = "SDIFF Contributors")]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
-    /// First file to compare (use "-" for stdin)
-    #[arg(value_name = "FILE1", required_unless_present_any = ["git_install", "git_uninstall", "git_status"])]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Apply a named profile from the configuration files
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Print the effective configuration and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// First file, directory or git revision to compare (use "-" for stdin)
+    #[arg(value_name = "FILE1", required_unless_present_any = ["git_install", "git_uninstall", "git_status", "git_merge_driver", "textconv", "git_install_hook", "git_pre_commit", "print_config"])]
     file1: Option<String>,
 
-    /// Second file to compare (use "-" for stdin)
-    #[arg(value_name = "FILE2", required_unless_present_any = ["git_install", "git_uninstall", "git_status"])]
+    /// Second file, directory or git revision to compare (use "-" for stdin)
+    #[arg(value_name = "FILE2", required_unless_present_any = ["git_install", "git_uninstall", "git_status", "git_merge_driver", "textconv", "git_install_hook", "git_pre_commit", "print_config", "git_paths"])]
     file2: Option<String>,
 
-    /// Output format
-    #[arg(short = 'f', long, value_enum, default_value = "terminal")]
-    format: OutputFormatArg,
+    /// Output format [default: terminal]
+    #[arg(short = 'f', long, value_enum, global = true)]
+    format: Option<OutputFormatArg>,
 
     /// Input format for stdin (required when using "-")
     #[arg(long, value_enum)]
     input_format: Option<InputFormatArg>,
 
-    /// Show only changes (hide unchanged fields)
-    #[arg(short, long, default_value = "true")]
+    /// Show only changes (hide unchanged fields, the default)
+    #[arg(short, long, overrides_with = "no_compact")]
     compact: bool,
 
+    /// Show unchanged fields, overriding `compact` from configuration
+    #[arg(long, overrides_with = "compact")]
+    no_compact: bool,
+
     /// Show full values instead of previews
-    #[arg(long)]
+    #[arg(long, global = true, overrides_with = "no_show_values")]
     show_values: bool,
 
-    /// Maximum length for displayed values
-    #[arg(long, default_value = "80")]
-    max_value_length: usize,
+    /// Show previews of long values, overriding `show-values` from configuration
+    #[arg(long, global = true, overrides_with = "show_values")]
+    no_show_values: bool,
+
+    /// Maximum length for displayed values [default: 80]
+    #[arg(long, global = true)]
+    max_value_length: Option<usize>,
 
     /// Treat null values as missing keys
-    #[arg(long)]
+    #[arg(long, global = true, overrides_with = "no_null_as_missing")]
     null_as_missing: bool,
 
+    /// Compare null values and missing keys as different
+    #[arg(long, global = true, overrides_with = "null_as_missing")]
+    no_null_as_missing: bool,
+
     /// Ignore whitespace differences in strings
-    #[arg(long)]
+    #[arg(long, global = true, overrides_with = "no_ignore_whitespace")]
     ignore_whitespace: bool,
 
-    /// Array comparison strategy
-    #[arg(long, value_enum, default_value = "positional")]
-    array_strategy: ArrayStrategyArg,
+    /// Compare whitespace in strings
+    #[arg(long, global = true, overrides_with = "ignore_whitespace")]
+    no_ignore_whitespace: bool,
 
-    /// Ignore paths matching these patterns (can be used multiple times)
-    #[arg(long = "ignore", value_name = "PATTERN")]
+    /// Array comparison strategy [default: positional]
+    #[arg(long, value_enum, global = true)]
+    array_strategy: Option<ArrayStrategyArg>,
+
+    /// Ignore paths matching these patterns (can be used multiple times).
+    /// --ignore and --only patterns are evaluated in the order given, the
+    /// last matching one winning
+    #[arg(long = "ignore", value_name = "PATTERN", global = true)]
     ignore_patterns: Vec<String>,
 
-    /// Only show paths matching these patterns (can be used multiple times)
-    #[arg(long = "only", value_name = "PATTERN")]
+    /// Only show paths matching these patterns (can be used multiple times).
+    /// A later --ignore hides part of what they select, and a later --only
+    /// shows part of what an earlier --ignore hid
+    #[arg(long = "only", value_name = "PATTERN", global = true)]
     only_patterns: Vec<String>,
 
+    /// --ignore and --only patterns in command-line order
+    #[arg(skip)]
+    filter_patterns: Vec<(FilterAction, String)>,
+
+    /// Read ignore patterns from this file, in addition to any discovered
+    /// .sdiffignore files (can be used multiple times)
+    #[arg(long, value_name = "PATH", global = true)]
+    ignore_file: Vec<String>,
+
+    /// Match keys in --ignore and --only patterns case-insensitively
+    #[arg(long, global = true, overrides_with = "no_case_insensitive_paths")]
+    case_insensitive_paths: bool,
+
+    /// Match keys in --ignore and --only patterns case-sensitively
+    #[arg(long, global = true, overrides_with = "case_insensitive_paths")]
+    no_case_insensitive_paths: bool,
+
+    /// Only show changes of these types: added, removed, modified
+    #[arg(long, value_name = "TYPES", value_delimiter = ',', global = true)]
+    only_type: Vec<String>,
+
+    /// Only show changes whose old value matches: a regex for strings, or a
+    /// comparison such as ">= 3" for numbers (can be used multiple times)
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true, global = true)]
+    old_value: Vec<String>,
+
+    /// Only show changes whose new value matches: a regex for strings, or a
+    /// comparison such as ">= 3" for numbers (can be used multiple times)
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true, global = true)]
+    new_value: Vec<String>,
+
     /// Verbose output (show parsing progress)
-    #[arg(short, long)]
+    #[arg(short, long, global = true, overrides_with = "no_verbose")]
     verbose: bool,
 
+    /// Turn off verbose output set in configuration
+    #[arg(long, global = true, overrides_with = "verbose")]
+    no_verbose: bool,
+
     /// Quiet mode (only show changes, suppress summary)
-    #[arg(short, long)]
+    #[arg(short, long, global = true, overrides_with = "no_quiet")]
     quiet: bool,
 
+    /// Show the summary, overriding `quiet` from configuration
+    #[arg(long, global = true, overrides_with = "quiet")]
+    no_quiet: bool,
+
     /// Install sdiff as a git difftool
     #[arg(long)]
     git_install: bool,
 
+    /// With --git-install, also register sdiff as a git textconv filter
+    #[arg(long, requires = "git_install")]
+    git_textconv: bool,
+
+    /// Install into (or uninstall from) the repository's git config
+    #[arg(long, conflicts_with = "system")]
+    local: bool,
+
+    /// Install into (or uninstall from) the system-wide git config
+    #[arg(long)]
+    system: bool,
+
+    /// With --git-install/--git-uninstall, add or remove sdiff entries in .gitattributes
+    #[arg(long)]
+    git_attributes: bool,
+
+    /// File extensions to configure in .gitattributes
+    #[arg(
+        long,
+        value_name = "EXT",
+        value_delimiter = ',',
+        default_value = "json,yaml,yml,toml"
+    )]
+    git_extensions: Vec<String>,
+
+    /// Install a git pre-commit hook that runs --git-pre-commit
+    #[arg(long)]
+    git_install_hook: bool,
+
+    /// Check staged changes against the policy file and fail if protected paths changed
+    #[arg(long)]
+    git_pre_commit: bool,
+
+    /// Policy file for --git-pre-commit (default: .sdiff-policy.toml at the repository root)
+    #[arg(long, value_name = "FILE", requires = "git_pre_commit")]
+    policy: Option<PathBuf>,
+
+    /// Print a canonical, line-oriented rendering of a file (for git textconv)
+    #[arg(long, value_name = "FILE")]
+    textconv: Option<String>,
+
     /// Uninstall sdiff from git configuration
     #[arg(long)]
     git_uninstall: bool,
@@ -94,9 +216,52 @@ struct Cli {
     #[arg(long)]
     git_status: bool,
 
-    /// Additional arguments (for git diff driver 7-arg mode)
-    #[arg(hide = true, trailing_var_arg = true)]
-    extra_args: Vec<String>,
+    /// Run as a git merge driver (configured by --git-install)
+    #[arg(long, num_args = 4, value_names = ["BASE", "OURS", "THEIRS", "PATH"])]
+    git_merge_driver: Option<Vec<String>>,
+
+    /// Three-way merge FILE1 (ours) and FILE2 (theirs) against a common base
+    #[arg(long, value_name = "BASE")]
+    merge: Option<String>,
+
+    /// Write the merged document to a file instead of stdout
+    #[arg(short = 'o', long, value_name = "FILE", requires = "merge")]
+    output: Option<PathBuf>,
+
+    /// Files to compare between git revisions: `sdiff REV1 [REV2] -- PATH...`
+    ///
+    /// FILE1 and FILE2 are treated as revisions. Without FILE2, the revision
+    /// is compared against the working tree.
+    #[arg(last = true, value_name = "PATH")]
+    git_paths: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show the commits that changed values in a file
+    ///
+    /// Walks the git history of FILE and lists every commit where a value
+    /// matching one of the PATTERNs changed, with its old and new value.
+    /// Commits that only reformatted the file are not shown.
+    Log {
+        /// File to inspect
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        /// Paths to follow, e.g. `spec.replicas` (all paths if omitted)
+        #[arg(value_name = "PATTERN")]
+        patterns: Vec<String>,
+    },
+
+    /// Show the last commit that changed each value in a file
+    ///
+    /// Like `git blame`, but per path instead of per line, so commits that
+    /// only reformatted the file are never blamed.
+    Blame {
+        /// File to inspect
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
 }
 
 /// Output format argument for clap
@@ -165,72 +330,81 @@ impl From<ArrayStrategyArg> for ArrayDiffStrategy {
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
     if let Some((old_file, new_file)) = detect_git_diff_driver_args(&args) {
-        match run_git_diff_driver(&old_file, &new_file) {
+        match run_git_diff_driver(&args[0], &old_file, &new_file) {
             Ok(exit_code) => process::exit(exit_code),
             Err(err) => {
-                eprintln!("Error: {}", err);
+                eprintln!("Error: {:#}", err);
                 process::exit(2);
             }
         }
     }
 
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    cli.filter_patterns = filter_patterns(&matches);
 
     match run(cli) {
         Ok(exit_code) => process::exit(exit_code),
         Err(err) => {
-            eprintln!("Error: {}", err);
+            eprintln!("Error: {:#}", err);
             process::exit(2);
         }
     }
 }
 
-fn run_git_diff_driver(old_file: &str, new_file: &str) -> Result<i32> {
-    if is_null_file(old_file) {
-        println!("File added");
-        let new = parse_file(&PathBuf::from(new_file))
-            .with_context(|| format!("Failed to parse new file: {}", new_file))?;
-        println!("New content: {}", new.preview(200));
-        return Ok(1);
-    }
+/// Runs as git's external diff command on `path`.
+///
+/// Always exits with 0 once the diff has been printed: git treats any other
+/// exit code from an external diff as a failure and aborts the whole diff.
+fn run_git_diff_driver(path: &str, old_file: &str, new_file: &str) -> Result<i32> {
+    let settings = Settings::for_git_driver(path).context("Failed to load sdiff configuration")?;
+
+    let parse = |file: &str, side: &str| -> Result<Option<(Node, FormatHint)>> {
+        if is_null_file(file) {
+            return Ok(None);
+        }
+        let parsed = parse_file_with_format(&PathBuf::from(file))
+            .with_context(|| format!("Failed to parse {} file: {}", side, file))?;
+        Ok(Some(parsed))
+    };
+    let old = parse(old_file, "old")?;
+    let new = parse(new_file, "new")?;
+    let diff = diff_file(
+        &settings,
+        old.as_ref().map(|o| &o.0),
+        new.as_ref().map(|n| &n.0),
+    )?;
+    let (old_format, new_format) = (old.map(|o| o.1), new.map(|n| n.1));
+
+    let context = diff_context(
+        &settings,
+        InputInfo::new(old_file, old_format),
+        InputInfo::new(new_file, new_format),
+    )?;
+    print_computed_diff(&settings, false, &diff, &context)?;
+
+    Ok(0)
+}
 
-    if is_null_file(new_file) {
-        println!("File deleted");
-        let old = parse_file(&PathBuf::from(old_file))
-            .with_context(|| format!("Failed to parse old file: {}", old_file))?;
-        println!("Deleted content: {}", old.preview(200));
-        return Ok(1);
+fn run(cli: Cli) -> Result<i32> {
+    if let Some(command) = &cli.command {
+        return match command {
+            Command::Log { file, patterns } => run_log(&cli, file, patterns),
+            Command::Blame { file } => run_blame(&cli, file),
+        };
     }
 
-    let old = parse_file(&PathBuf::from(old_file))
-        .with_context(|| format!("Failed to parse old file: {}", old_file))?;
-    let new = parse_file(&PathBuf::from(new_file))
-        .with_context(|| format!("Failed to parse new file: {}", new_file))?;
-
-    let config = DiffConfig::default();
-    let diff = compute_diff(&old, &new, &config);
-
-    let output_options = OutputOptions::default();
-    let output = format_diff(&diff, &OutputFormat::Terminal, &output_options)
-        .context("Failed to format diff output")?;
-
-    println!("{}", output);
-
-    if diff.is_empty() {
-        Ok(0)
-    } else {
-        Ok(1)
+    if cli.print_config {
+        return print_config(&cli);
     }
-}
 
-fn run(cli: Cli) -> Result<i32> {
     if cli.git_install {
-        git::install().context("Failed to install git integration")?;
+        git::install(&install_options(&cli)).context("Failed to install git integration")?;
         return Ok(0);
     }
 
     if cli.git_uninstall {
-        git::uninstall().context("Failed to uninstall git integration")?;
+        git::uninstall(&install_options(&cli)).context("Failed to uninstall git integration")?;
         return Ok(0);
     }
 
@@ -239,9 +413,71 @@ fn run(cli: Cli) -> Result<i32> {
         return Ok(0);
     }
 
+    if cli.git_install_hook {
+        let hook = git::install_hook()?;
+        println!("Installed pre-commit hook at {}", hook.display());
+        return Ok(0);
+    }
+
+    if cli.git_pre_commit {
+        return run_pre_commit(&cli);
+    }
+
+    if let Some(file) = &cli.textconv {
+        let node = if file == "-" {
+            let format_hint = cli.input_format.map(Into::into).unwrap_or(FormatHint::Auto);
+            parse_stdin(format_hint).context("Failed to parse stdin")?
+        } else {
+            parse_file(Path::new(file)).with_context(|| format!("Failed to parse {}", file))?
+        };
+        print!("{}", format_textconv(&node));
+        return Ok(0);
+    }
+
+    if let Some(args) = &cli.git_merge_driver {
+        let settings =
+            Settings::for_git_driver(&args[3]).context("Failed to load sdiff configuration")?;
+        let result = git::run_merge_driver(
+            &args[0],
+            &args[1],
+            &args[2],
+            &args[3],
+            &settings.diff_config(),
+        )
+        .context("Failed to run git merge driver")?;
+        if result.is_clean() {
+            return Ok(0);
+        }
+        eprintln!(
+            "sdiff could not merge {} automatically; the merged file keeps our value at each conflicting path.",
+            &args[3]
+        );
+        eprint!(
+            "{}",
+            format_conflicts(&result.conflicts, &settings.output_options())
+        );
+        return Ok(1);
+    }
+
     let file1 = cli.file1.as_ref().expect("file1 is required for diff");
+
+    if !cli.git_paths.is_empty() {
+        if cli.merge.is_some() {
+            bail!("--merge cannot be combined with git revisions");
+        }
+        return run_revisions(&cli, file1, cli.file2.as_deref());
+    }
+
     let file2 = cli.file2.as_ref().expect("file2 is required for diff");
 
+    if let Some(base) = &cli.merge {
+        return run_merge(&cli, base, file1, file2);
+    }
+
+    if let (Some(old), Some(new)) = (tree_source(file1), tree_source(file2)) {
+        return run_trees(&cli, &old, &new);
+    }
+
     let file1_is_stdin = file1 == "-";
     let file2_is_stdin = file2 == "-";
 
@@ -249,7 +485,9 @@ fn run(cli: Cli) -> Result<i32> {
         bail!("Cannot read both inputs from stdin");
     }
 
-    let format_hint: FormatHint = cli.input_format.map(Into::into).unwrap_or(FormatHint::Auto);
+    let settings = load_settings(&cli, &[file1, file2])?;
+    let verbose = settings.verbose.unwrap_or(false);
+    let format_hint = settings.input_format.unwrap_or_default();
 
     let stdin_content = if file1_is_stdin || file2_is_stdin {
         let mut content = String::new();
@@ -261,69 +499,215 @@ fn run(cli: Cli) -> Result<i32> {
         None
     };
 
-    if cli.verbose {
+    if verbose {
         eprintln!("Parsing {}...", file1);
     }
 
-    let old = if file1_is_stdin {
-        parse_content(stdin_content.as_ref().unwrap(), format_hint, "<stdin>")
+    let (old, old_format) = if file1_is_stdin {
+        parse_content_with_format(stdin_content.as_ref().unwrap(), format_hint, "<stdin>")
             .context("Failed to parse stdin")?
     } else {
-        parse_file(&PathBuf::from(file1))
+        parse_file_with_format(&PathBuf::from(file1))
             .with_context(|| format!("Failed to parse first file: {}", file1))?
     };
 
-    if cli.verbose {
+    if verbose {
         eprintln!("Parsing {}...", file2);
     }
 
-    let new = if file2_is_stdin {
-        parse_content(stdin_content.as_ref().unwrap(), format_hint, "<stdin>")
+    let (new, new_format) = if file2_is_stdin {
+        parse_content_with_format(stdin_content.as_ref().unwrap(), format_hint, "<stdin>")
             .context("Failed to parse stdin")?
     } else {
-        parse_file(&PathBuf::from(file2))
+        parse_file_with_format(&PathBuf::from(file2))
             .with_context(|| format!("Failed to parse second file: {}", file2))?
     };
 
-    if cli.verbose {
-        eprintln!("Computing diff...");
+    let context = diff_context(
+        &settings,
+        InputInfo::new(file1, Some(old_format)),
+        InputInfo::new(file2, Some(new_format)),
+    )?;
+    let diff = print_diff(&settings, verbose, &old, &new, &context)?;
+    warn_unmatched(&cli, &diff.hidden);
+
+    if diff.is_empty() {
+        Ok(0)
+    } else {
+        Ok(1)
     }
+}
 
-    let diff_config = DiffConfig {
-        ignore_whitespace: cli.ignore_whitespace,
-        treat_null_as_missing: cli.null_as_missing,
-        array_diff_strategy: cli.array_strategy.into(),
+fn install_options(cli: &Cli) -> git::InstallOptions {
+    let scope = if cli.local {
+        git::ConfigScope::Local
+    } else if cli.system {
+        git::ConfigScope::System
+    } else {
+        git::ConfigScope::Global
     };
 
-    let mut diff = compute_diff(&old, &new, &diff_config);
-
-    if !cli.ignore_patterns.is_empty() || !cli.only_patterns.is_empty() {
-        let mut filter_config = FilterConfig::new();
-        for pattern in &cli.ignore_patterns {
-            filter_config = filter_config.ignore(pattern);
-        }
-        for pattern in &cli.only_patterns {
-            filter_config = filter_config.only(pattern);
-        }
-        diff = filter_diff(&diff, &filter_config);
+    git::InstallOptions {
+        scope,
+        textconv: cli.git_textconv,
+        attributes: cli.git_attributes,
+        extensions: cli
+            .git_extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_string())
+            .collect(),
     }
+}
 
-    if cli.verbose {
-        eprintln!("Formatting output...");
+/// Collects the diff options given on the command line.
+///
+/// A flag and its `--no-` counterpart override each other, the last one
+/// given winning; with neither, the configured value is kept.
+fn cli_settings(cli: &Cli) -> Settings {
+    let flag = |on: bool, off: bool| match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    };
+    Settings {
+        format: cli.format.map(Into::into),
+        compact: flag(cli.compact, cli.no_compact),
+        show_values: flag(cli.show_values, cli.no_show_values),
+        max_value_length: cli.max_value_length,
+        null_as_missing: flag(cli.null_as_missing, cli.no_null_as_missing),
+        ignore_whitespace: flag(cli.ignore_whitespace, cli.no_ignore_whitespace),
+        array_strategy: cli.array_strategy.map(Into::into),
+        quiet: flag(cli.quiet, cli.no_quiet),
+        verbose: flag(cli.verbose, cli.no_verbose),
+        input_format: cli.input_format.map(Into::into),
+        ignore: Vec::new(),
+        only: Vec::new(),
+        patterns: cli.filter_patterns.clone(),
+        case_insensitive_paths: flag(cli.case_insensitive_paths, cli.no_case_insensitive_paths),
+        only_type: cli.only_type.clone(),
+        old_value: cli.old_value.clone(),
+        new_value: cli.new_value.clone(),
+        ignore_file: cli.ignore_file.clone(),
+        rules: Vec::new(),
     }
+}
 
-    let output_options = OutputOptions {
-        compact: cli.compact,
-        show_values: cli.show_values,
-        max_value_length: cli.max_value_length,
-        context_lines: 0,
+/// Returns the `--ignore` and `--only` patterns in the order they were given.
+fn filter_patterns(matches: &ArgMatches) -> Vec<(FilterAction, String)> {
+    let given = |id: &str, action: FilterAction| {
+        let indices = matches.indices_of(id).into_iter().flatten();
+        let values = matches.get_many::<String>(id).into_iter().flatten();
+        indices
+            .zip(values)
+            .map(move |(index, pattern)| (index, (action, pattern.clone())))
     };
+    let mut patterns: Vec<_> = given("ignore_patterns", FilterAction::Ignore)
+        .chain(given("only_patterns", FilterAction::Only))
+        .collect();
+    patterns.sort_by_key(|(index, _)| *index);
+    patterns.into_iter().map(|(_, pattern)| pattern).collect()
+}
+
+/// Loads the effective settings for comparing `paths`: configuration files,
+/// then the selected profile and matching file rules, then command-line flags.
+fn load_settings(cli: &Cli, paths: &[&str]) -> Result<Settings> {
+    let cwd = env::current_dir().context("Failed to read current directory")?;
+    let files = ConfigFiles::discover(&cwd)?;
+    let mut settings = files.settings(cli.profile.as_deref(), paths)?;
+    settings.merge(cli_settings(cli));
+    settings.load_ignore_files(paths)?;
+    settings.validate_filters()?;
+    Ok(settings)
+}
+
+/// Prints the effective configuration, listing the files it was read from.
+fn print_config(cli: &Cli) -> Result<i32> {
+    let cwd = env::current_dir().context("Failed to read current directory")?;
+    let files = ConfigFiles::discover(&cwd)?;
+
+    if files.files.is_empty() {
+        println!("# No configuration files found");
+    }
+    for (path, _) in &files.files {
+        println!("# {}", path.display());
+    }
+    if let Some(profile) = &cli.profile {
+        println!("# profile: {}", profile);
+    }
 
-    let output_format: OutputFormat = cli.format.into();
-    let output = format_diff(&diff, &output_format, &output_options)
-        .context("Failed to format diff output")?;
+    let paths: Vec<&str> = [cli.file1.as_deref(), cli.file2.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut settings = files.settings(cli.profile.as_deref(), &paths)?;
+    settings.merge(cli_settings(cli));
+    print!("{}", settings.resolved().to_toml()?);
+    Ok(0)
+}
 
-    if !cli.quiet {
+/// Describes two compared inputs and the settings used, for JSON output.
+fn diff_context(settings: &Settings, old: InputInfo, new: InputInfo) -> Result<DiffContext> {
+    Ok(DiffContext::new()
+        .inputs(old, new)
+        .config(settings.diff_config())
+        .filter(settings.filter_config()?))
+}
+
+/// Computes the filtered diff of a file that may not exist on one side, in
+/// which case every value on the other side is added or removed.
+fn diff_file(settings: &Settings, old: Option<&Node>, new: Option<&Node>) -> Result<Diff> {
+    let filter = settings.filter_config()?;
+    Ok(match (old, new) {
+        (Some(old), Some(new)) => compute_diff_filtered(old, new, &settings.diff_config(), &filter),
+        (None, Some(new)) => filter_diff_in(&compute_added_diff(new), &filter, &Node::Null, new),
+        (Some(old), None) => filter_diff_in(&compute_removed_diff(old), &filter, old, &Node::Null),
+        (None, None) => bail!("Nothing to compare"),
+    })
+}
+
+/// Computes, filters and prints the diff between two nodes.
+/// Returns the printed diff.
+fn print_diff(
+    settings: &Settings,
+    verbose: bool,
+    old: &Node,
+    new: &Node,
+    context: &DiffContext,
+) -> Result<Diff> {
+    if verbose {
+        eprintln!("Computing diff...");
+    }
+
+    let diff = compute_diff_filtered(
+        old,
+        new,
+        &settings.diff_config(),
+        &settings.filter_config()?,
+    );
+    print_computed_diff(settings, verbose, &diff, context)?;
+    Ok(diff)
+}
+
+/// Prints a diff that has already been computed and filtered.
+fn print_computed_diff(
+    settings: &Settings,
+    verbose: bool,
+    diff: &Diff,
+    context: &DiffContext,
+) -> Result<()> {
+    if verbose {
+        eprintln!("Formatting output...");
+    }
+
+    let output = format_diff_with_context(
+        diff,
+        &settings.output_format(),
+        &settings.output_options(),
+        context,
+    )
+    .context("Failed to format diff output")?;
+
+    if !settings.quiet.unwrap_or(false) {
         println!("{}", output);
     } else {
         let lines: Vec<&str> = output.lines().collect();
@@ -334,9 +718,305 @@ fn run(cli: Cli) -> Result<i32> {
         }
     }
 
-    if diff.is_empty() {
+    Ok(())
+}
+
+/// Warns about `--ignore` and `--only` patterns that matched no path in the
+/// compared files, which are likely typos. Patterns from configuration and
+/// ignore files are shared between many files and are not reported.
+fn warn_unmatched(cli: &Cli, hidden: &HiddenChanges) {
+    for rule in hidden.unmatched() {
+        let pattern = &rule.pattern;
+        if cli.ignore_patterns.contains(pattern) || cli.only_patterns.contains(pattern) {
+            eprintln!(
+                "Warning: pattern '{}' does not match any path in the compared files",
+                pattern
+            );
+        }
+    }
+}
+
+/// Compares files between two git revisions, or between a revision and the
+/// working tree when `new_rev` is None.
+fn run_revisions(cli: &Cli, old_rev: &str, new_rev: Option<&str>) -> Result<i32> {
+    let paths: Vec<&str> = cli.git_paths.iter().map(String::as_str).collect();
+    let settings = load_settings(cli, &paths)?;
+    let verbose = settings.verbose.unwrap_or(false);
+    let mut has_changes = false;
+    let mut hidden = HiddenChanges::default();
+
+    for (i, path) in cli.git_paths.iter().enumerate() {
+        let format = detect_format(Path::new(path));
+
+        if verbose {
+            eprintln!("Reading {}:{}...", old_rev, path);
+        }
+        // A path missing from one side is shown as added or removed
+        let read = |rev: &str| -> Result<(String, Option<(Node, FormatHint)>)> {
+            let source = format!("{}:{}", rev, path);
+            let content = git::read_blob_if_exists(rev, path)
+                .with_context(|| format!("Failed to read {}", source))?;
+            let parsed = content
+                .map(|content| parse_content_with_format(&content, format, &source))
+                .transpose()
+                .with_context(|| format!("Failed to parse {}", source))?;
+            Ok((source, parsed))
+        };
+        let (old_source, old) = read(old_rev)?;
+
+        let (new_source, new) = match new_rev {
+            Some(rev) => {
+                if verbose {
+                    eprintln!("Reading {}:{}...", rev, path);
+                }
+                read(rev)?
+            }
+            None => {
+                if verbose {
+                    eprintln!("Parsing {}...", path);
+                }
+                let parsed = if Path::new(path).exists() {
+                    let parsed = parse_file_with_format(Path::new(path))
+                        .with_context(|| format!("Failed to parse file: {}", path))?;
+                    Some(parsed)
+                } else {
+                    None
+                };
+                (path.clone(), parsed)
+            }
+        };
+        if old.is_none() && new.is_none() {
+            bail!("{} does not exist on either side", path);
+        }
+
+        if cli.git_paths.len() > 1 {
+            if i > 0 {
+                println!();
+            }
+            println!("{}", path);
+        }
+
+        let context = diff_context(
+            &settings,
+            InputInfo::new(&old_source, old.as_ref().map(|o| o.1)),
+            InputInfo::new(&new_source, new.as_ref().map(|n| n.1)),
+        )?;
+        if verbose {
+            eprintln!("Computing diff...");
+        }
+        let diff = diff_file(
+            &settings,
+            old.as_ref().map(|o| &o.0),
+            new.as_ref().map(|n| &n.0),
+        )?;
+        print_computed_diff(&settings, verbose, &diff, &context)?;
+        has_changes |= !diff.is_empty();
+        hidden.merge(&diff.hidden);
+    }
+    warn_unmatched(cli, &hidden);
+
+    if has_changes {
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
+
+/// Prints the commits that changed values matching `patterns` in `file`.
+fn run_log(cli: &Cli, file: &str, patterns: &[String]) -> Result<i32> {
+    let mut settings = load_settings(cli, &[file])?;
+    let verbose = settings.verbose.unwrap_or(false);
+    settings.only.extend(patterns.iter().cloned());
+
+    if verbose {
+        eprintln!("Reading history of {}...", file);
+    }
+
+    let entries = file_history(file, &settings.diff_config(), &settings.filter_config()?)
+        .with_context(|| format!("Failed to read history of {}", file))?;
+
+    let output = format_history(
+        &entries,
+        &settings.output_format(),
+        &settings.output_options(),
+    )
+    .context("Failed to format history")?;
+    println!("{}", output);
+
+    Ok(0)
+}
+
+/// Prints the last commit that changed each value in `file`.
+fn run_blame(cli: &Cli, file: &str) -> Result<i32> {
+    let settings = load_settings(cli, &[file])?;
+    let verbose = settings.verbose.unwrap_or(false);
+
+    if verbose {
+        eprintln!("Reading history of {}...", file);
+    }
+
+    let entries = blame(file, &settings.diff_config(), &settings.filter_config()?)
+        .with_context(|| format!("Failed to blame {}", file))?;
+
+    let output = format_blame(
+        &entries,
+        &settings.output_format(),
+        &settings.output_options(),
+    )
+    .context("Failed to format blame")?;
+    print!("{}", output);
+
+    Ok(0)
+}
+
+/// Checks staged changes against the policy file.
+/// Returns 1 if any protected path changed.
+fn run_pre_commit(cli: &Cli) -> Result<i32> {
+    let policy_path = match &cli.policy {
+        Some(path) => path.clone(),
+        None => git::repo_root()
+            .context("Failed to find repository root")?
+            .join(POLICY_FILE_NAME),
+    };
+    let policy = Policy::from_file(&policy_path).context("Failed to load policy")?;
+
+    let settings = load_settings(cli, &[])?;
+    let violations = policy.check_staged(&settings.diff_config())?;
+
+    if violations.is_empty() {
+        return Ok(0);
+    }
+
+    eprintln!("Commit blocked: protected paths changed");
+    eprintln!();
+    eprint!(
+        "{}",
+        format_violations(&violations, &settings.output_options())
+    );
+    eprintln!();
+    eprintln!(
+        "Update {} or use `git commit --no-verify` to bypass this check.",
+        policy_path.display()
+    );
+    Ok(1)
+}
+
+/// Interprets a FILE argument as a directory, or as a git revision when no
+/// such file exists.
+fn tree_source(arg: &str) -> Option<Source> {
+    let path = Path::new(arg);
+    if path.is_dir() {
+        Some(Source::Directory(path.to_path_buf()))
+    } else if arg != "-" && !path.exists() && git::is_revision(arg) {
+        Some(Source::Revision(arg.to_string()))
+    } else {
+        None
+    }
+}
+
+/// Describes a compared tree for JSON output.
+fn tree_input(source: &Source) -> InputInfo {
+    match source {
+        Source::Directory(dir) => InputInfo::new(&dir.to_string_lossy(), None),
+        Source::Revision(rev) => InputInfo::new(rev, None),
+    }
+}
+
+/// Compares every structured file in two directories or git revisions.
+fn run_trees(cli: &Cli, old: &Source, new: &Source) -> Result<i32> {
+    let dirs: Vec<&str> = [old, new]
+        .into_iter()
+        .filter_map(|source| match source {
+            Source::Directory(dir) => dir.to_str(),
+            Source::Revision(_) => None,
+        })
+        .collect();
+    let settings = load_settings(cli, &dirs)?;
+    let verbose = settings.verbose.unwrap_or(false);
+
+    if verbose {
+        eprintln!("Comparing {:?} with {:?}...", old, new);
+    }
+
+    let result = diff_sources_filtered(
+        old,
+        new,
+        &settings.diff_config(),
+        &settings.filter_config()?,
+    )
+    .context("Failed to compare trees")?;
+
+    let context = diff_context(&settings, tree_input(old), tree_input(new))?;
+    let output = format_dir_diff_with_context(
+        &result,
+        &settings.output_format(),
+        &settings.output_options(),
+        &context,
+    )
+    .context("Failed to format diff output")?;
+
+    if !settings.quiet.unwrap_or(false) {
+        println!("{}", output);
+    } else {
+        for line in output.lines() {
+            if !line.starts_with("Summary:")
+                && !line.starts_with("Files:")
+                && !line.trim().is_empty()
+            {
+                println!("{}", line);
+            }
+        }
+    }
+    warn_unmatched(cli, &result.hidden);
+
+    if result.is_empty() {
         Ok(0)
     } else {
         Ok(1)
     }
 }
+
+fn run_merge(cli: &Cli, base_file: &str, ours_file: &str, theirs_file: &str) -> Result<i32> {
+    if [base_file, ours_file, theirs_file].contains(&"-") {
+        bail!("Reading from stdin is not supported when merging");
+    }
+
+    let base = parse_file(Path::new(base_file))
+        .with_context(|| format!("Failed to parse base file: {}", base_file))?;
+    let ours = parse_file(Path::new(ours_file))
+        .with_context(|| format!("Failed to parse first file: {}", ours_file))?;
+    let theirs = parse_file(Path::new(theirs_file))
+        .with_context(|| format!("Failed to parse second file: {}", theirs_file))?;
+
+    let settings = load_settings(cli, &[base_file, ours_file, theirs_file])?;
+    let result = merge(&base, &ours, &theirs, &settings.diff_config());
+
+    let output_format = match cli.output.as_deref().map(detect_format) {
+        Some(format) if format != FormatHint::Auto => format,
+        _ => detect_format(Path::new(ours_file)),
+    };
+    let document =
+        write_content(&result.merged, output_format).context("Failed to write merged document")?;
+
+    match &cli.output {
+        Some(path) => fs::write(path, document)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", document),
+    }
+
+    if result.is_clean() {
+        return Ok(0);
+    }
+
+    eprint!(
+        "{}",
+        format_conflicts(&result.conflicts, &settings.output_options())
+    );
+    eprintln!(
+        "Merge completed with {} conflict(s); conflicting paths keep the value from {}",
+        result.conflicts.len(),
+        ours_file
+    );
+
+    Ok(1)
+}
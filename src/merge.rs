@@ -0,0 +1,271 @@
+//! Three-way structural merge.
+//!
+//! This module merges two documents that were both derived from a common
+//! base. It computes a diff from the base to each side, applies every change
+//! that does not collide with a change from the other side, and reports the
+//! remaining collisions as conflicts.
+//!
+//! Two changes conflict when one path is equal to or nested inside the other
+//! and the changes are not identical, or when both sides grow or shrink the
+//! same array. Conflicting regions keep the value from `ours`.
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::merge::merge;
+//! use sdiff_rs::{DiffConfig, Node};
+//! use std::collections::HashMap;
+//!
+//! let object = |pairs: &[(&str, f64)]| {
+//!     let map: HashMap<String, Node> = pairs
+//!         .iter()
+//!         .map(|(k, v)| (k.to_string(), Node::Number(*v)))
+//!         .collect();
+//!     Node::Object(map)
+//! };
+//!
+//! let base = object(&[("replicas", 1.0), ("port", 80.0)]);
+//! let ours = object(&[("replicas", 3.0), ("port", 80.0)]);
+//! let theirs = object(&[("replicas", 1.0), ("port", 8080.0)]);
+//!
+//! let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+//! assert!(result.is_clean());
+//! assert_eq!(result.merged, object(&[("replicas", 3.0), ("port", 8080.0)]));
+//! ```
+
+use crate::diff::{compute_diff, ArrayDiffStrategy, Change, ChangeType, DiffConfig, RuleSet};
+use crate::path::{Path, PathSegment};
+use crate::tree::Node;
+
+/// A path where both sides made incompatible changes.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    /// Path to the conflicting value
+    pub path: Path,
+    /// Value in the base document (None if absent)
+    pub base: Option<Node>,
+    /// Value in our document (None if absent)
+    pub ours: Option<Node>,
+    /// Value in their document (None if absent)
+    pub theirs: Option<Node>,
+}
+
+/// The result of a three-way merge.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The merged document. Conflicting paths hold the value from `ours`.
+    pub merged: Node,
+    /// Paths that could not be merged automatically
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// Returns true if the merge completed without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges `ours` and `theirs`, which were both derived from `base`.
+///
+/// Arrays are always compared positionally, regardless of
+/// `config.array_diff_strategy` and any array rules, so that index paths from
+/// both sides refer to the same base elements.
+pub fn merge(base: &Node, ours: &Node, theirs: &Node, config: &DiffConfig) -> MergeResult {
+    let config = DiffConfig {
+        array_diff_strategy: ArrayDiffStrategy::Positional,
+        rules: config
+            .rules
+            .iter()
+            .map(|(pattern, rules)| {
+                let rules = RuleSet {
+                    array_strategy: None,
+                    array_key: None,
+                    ..rules.clone()
+                };
+                (pattern.clone(), rules)
+            })
+            .collect(),
+        ..config.clone()
+    };
+
+    let ours_changes = significant_changes(base, ours, &config);
+    let theirs_changes = significant_changes(base, theirs, &config);
+
+    let mut conflict_paths: Vec<Path> = Vec::new();
+    let mut theirs_skipped = vec![false; theirs_changes.len()];
+
+    for ours_change in &ours_changes {
+        for (i, theirs_change) in theirs_changes.iter().enumerate() {
+            if ours_change.path == theirs_change.path && same_change(ours_change, theirs_change) {
+                theirs_skipped[i] = true;
+                continue;
+            }
+
+            if let Some(path) = conflict_path(ours_change, theirs_change) {
+                theirs_skipped[i] = true;
+                if !conflict_paths.contains(&path) {
+                    conflict_paths.push(path);
+                }
+            }
+        }
+    }
+
+    let mut to_apply: Vec<&Change> = ours_changes.iter().collect();
+    to_apply.extend(
+        theirs_changes
+            .iter()
+            .zip(theirs_skipped)
+            .filter(|(_, skipped)| !skipped)
+            .map(|(change, _)| change),
+    );
+
+    let mut merged = base.clone();
+    apply_changes(&mut merged, to_apply);
+
+    conflict_paths.sort();
+    let conflicts = conflict_paths
+        .into_iter()
+        .map(|path| MergeConflict {
+            base: base.get(&path).cloned(),
+            ours: ours.get(&path).cloned(),
+            theirs: theirs.get(&path).cloned(),
+            path,
+        })
+        .collect();
+
+    MergeResult { merged, conflicts }
+}
+
+fn significant_changes(old: &Node, new: &Node, config: &DiffConfig) -> Vec<Change> {
+    compute_diff(old, new, config)
+        .changes
+        .into_iter()
+        .filter(|c| c.change_type != ChangeType::Unchanged)
+        .collect()
+}
+
+fn same_change(a: &Change, b: &Change) -> bool {
+    if a.change_type != b.change_type {
+        return false;
+    }
+
+    match (&a.new_value, &b.new_value) {
+        (Some(x), Some(y)) => x.semantic_equals(y),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Returns the path at which two changes conflict, if they do.
+fn conflict_path(a: &Change, b: &Change) -> Option<Path> {
+    if is_prefix(&a.path, &b.path) {
+        return Some(a.path.clone());
+    }
+    if is_prefix(&b.path, &a.path) {
+        return Some(b.path.clone());
+    }
+
+    // Positional additions and removals change the length of the array they
+    // belong to, so two of them on the same array cannot both be applied.
+    if resizes_array(a) && resizes_array(b) {
+        let parent_a = a.path.parent();
+        if parent_a == b.path.parent() {
+            return parent_a;
+        }
+    }
+
+    None
+}
+
+fn resizes_array(change: &Change) -> bool {
+    matches!(change.change_type, ChangeType::Added | ChangeType::Removed)
+        && matches!(change.path.last(), Some(PathSegment::Index(_)))
+}
+
+fn is_prefix(prefix: &[PathSegment], path: &[PathSegment]) -> bool {
+    prefix.len() <= path.len() && prefix.iter().zip(path).all(|(a, b)| a == b)
+}
+
+/// Applies changes so that array indices stay valid: modifications first,
+/// then removals from the highest index down, then additions in order.
+fn apply_changes(root: &mut Node, changes: Vec<&Change>) {
+    let mut modified = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for change in changes {
+        match change.change_type {
+            ChangeType::Modified => modified.push(change),
+            ChangeType::Removed => removed.push(change),
+            ChangeType::Added => added.push(change),
+            ChangeType::Unchanged => {}
+        }
+    }
+
+    removed.sort_by(|a, b| b.path.cmp(&a.path));
+    added.sort_by(|a, b| a.path.cmp(&b.path));
+
+    for change in modified {
+        if let Some(value) = &change.new_value {
+            set_path(root, &change.path, value.clone());
+        }
+    }
+    for change in removed {
+        remove_path(root, &change.path);
+    }
+    for change in added {
+        if let Some(value) = &change.new_value {
+            set_path(root, &change.path, value.clone());
+        }
+    }
+}
+
+fn get_path_mut<'a>(node: &'a mut Node, path: &[PathSegment]) -> Option<&'a mut Node> {
+    let mut current = node;
+    for segment in path {
+        current = match (current, segment) {
+            (Node::Array(arr), PathSegment::Index(index)) => arr.get_mut(*index)?,
+            (Node::Object(map), PathSegment::Key(key)) => map.get_mut(key)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn set_path(root: &mut Node, path: &[PathSegment], value: Node) {
+    let Some((last, parent_path)) = path.split_last() else {
+        *root = value;
+        return;
+    };
+
+    match (get_path_mut(root, parent_path), last) {
+        (Some(Node::Array(arr)), PathSegment::Index(index)) => {
+            if *index < arr.len() {
+                arr[*index] = value;
+            } else {
+                arr.push(value);
+            }
+        }
+        (Some(Node::Object(map)), PathSegment::Key(key)) => {
+            map.insert(key.clone(), value);
+        }
+        _ => {}
+    }
+}
+
+fn remove_path(root: &mut Node, path: &[PathSegment]) {
+    let Some((last, parent_path)) = path.split_last() else {
+        return;
+    };
+
+    match (get_path_mut(root, parent_path), last) {
+        (Some(Node::Array(arr)), PathSegment::Index(index)) if *index < arr.len() => {
+            arr.remove(*index);
+        }
+        (Some(Node::Object(map)), PathSegment::Key(key)) => {
+            map.remove(key);
+        }
+        _ => {}
+    }
+}
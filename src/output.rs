@@ -1,11 +1,57 @@
 //! Output formatting for diff results.
-
-use crate::diff::{Change, ChangeType, Diff};
+//!
+//! # JSON output
+//!
+//! `--format json` output follows a versioned schema, shipped with the crate
+//! as [`JSON_SCHEMA`]. Every document has a `schema_version` (currently
+//! [`SCHEMA_VERSION`]) and a `kind`: `"diff"` for two documents, `"tree"`
+//! for two directories or revisions. Fields may be added within a version;
+//! the version changes when a field is removed, renamed or changes meaning.
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "kind": "diff",
+//!   "old": { "path": "old.yaml", "format": "yaml" },
+//!   "new": { "path": "new.yaml", "format": "yaml" },
+//!   "config": { "diff": { ... }, "filter": { ... } },
+//!   "changes": [
+//!     { "path": ["spec", "replicas"], "type": "modified", "old_value": 2, "new_value": 3 }
+//!   ],
+//!   "stats": { "added": 0, "removed": 0, "modified": 1, "unchanged": 0, "total": 1 },
+//!   "hidden": { "total": 0, "patterns": [], "unselected": 0, "by_change": 0 }
+//! }
+//! ```
+//!
+//! Paths are arrays of keys (strings) and indices (numbers). `old_value` is
+//! left out of added changes and `new_value` out of removed ones, so a
+//! `null` value is never confused with a missing one. `old`, `new` and the
+//! parts of `config` are `null` when the caller did not describe them (see
+//! [`DiffContext`]).
+
+use crate::diff::{compute_added_diff, Change, ChangeType, Diff, DiffConfig, DiffStats};
+use crate::dir::{DirDiff, FileDiff, FileStatus};
 use crate::error::OutputError;
+use crate::filter::{FilterConfig, HiddenChanges};
+use crate::history::{BlameEntry, HistoryEntry};
+use crate::merge::MergeConflict;
+use crate::parser::{detect_format, FormatHint};
+use crate::path::Path;
+use crate::policy::Violation;
 use crate::tree::Node;
+use crate::writer::is_integral;
 use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Version of the JSON output schema, given as `schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema describing `--format json` output for diffs and trees.
+pub const JSON_SCHEMA: &str = include_str!("../schema/sdiff-output.schema.json");
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Terminal,
     Json,
@@ -31,15 +77,101 @@ impl Default for OutputOptions {
     }
 }
 
+/// A compared document or tree, as described in JSON output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputInfo {
+    /// File or directory path, `-` for stdin, or a git revision
+    /// (`HEAD:app.json` for a file, `HEAD` for a tree)
+    pub path: String,
+    /// Format the document was parsed as; None for trees
+    pub format: Option<FormatHint>,
+}
+
+impl InputInfo {
+    pub fn new(path: &str, format: Option<FormatHint>) -> Self {
+        Self {
+            path: path.to_string(),
+            format,
+        }
+    }
+}
+
+/// What was compared and how, for the metadata in JSON output.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::output::{format_diff_with_context, DiffContext, InputInfo};
+/// use sdiff_rs::{compute_diff, parse_json, DiffConfig, FormatHint, OutputFormat, OutputOptions};
+///
+/// let old = parse_json(r#"{"replicas": 2}"#).unwrap();
+/// let new = parse_json(r#"{"replicas": 3}"#).unwrap();
+/// let config = DiffConfig::default();
+/// let diff = compute_diff(&old, &new, &config);
+///
+/// let context = DiffContext::new()
+///     .inputs(
+///         InputInfo::new("old.json", Some(FormatHint::Json)),
+///         InputInfo::new("new.json", Some(FormatHint::Json)),
+///     )
+///     .config(config);
+/// let json = format_diff_with_context(&diff, &OutputFormat::Json, &OutputOptions::default(), &context)
+///     .unwrap();
+///
+/// assert!(json.contains(r#""schema_version": 1"#));
+/// assert!(json.contains(r#""path": "old.json""#));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DiffContext {
+    pub old: Option<InputInfo>,
+    pub new: Option<InputInfo>,
+    /// Settings the diff was computed with
+    pub config: Option<DiffConfig>,
+    /// Filter applied to the diff
+    pub filter: Option<FilterConfig>,
+}
+
+impl DiffContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn inputs(mut self, old: InputInfo, new: InputInfo) -> Self {
+        self.old = Some(old);
+        self.new = Some(new);
+        self
+    }
+
+    pub fn config(mut self, config: DiffConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn filter(mut self, filter: FilterConfig) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+}
+
 /// Formats a diff according to the specified format and options.
 pub fn format_diff(
     diff: &Diff,
     format: &OutputFormat,
     options: &OutputOptions,
+) -> Result<String, OutputError> {
+    format_diff_with_context(diff, format, options, &DiffContext::default())
+}
+
+/// Like [`format_diff`], describing the inputs and settings in JSON output.
+pub fn format_diff_with_context(
+    diff: &Diff,
+    format: &OutputFormat,
+    options: &OutputOptions,
+    context: &DiffContext,
 ) -> Result<String, OutputError> {
     match format {
         OutputFormat::Terminal => Ok(format_terminal(diff, options)),
-        OutputFormat::Json => format_json(diff),
+        OutputFormat::Json => format_json(diff, context),
         OutputFormat::Plain => Ok(format_plain(diff, options)),
     }
 }
@@ -54,7 +186,8 @@ fn format_terminal(diff: &Diff, options: &OutputOptions) -> String {
         .collect();
 
     if changes.is_empty() {
-        return "No changes detected.".dimmed().to_string();
+        let message = format!("No changes detected.{}", hidden_note(&diff.hidden));
+        return message.dimmed().to_string();
     }
 
     for change in changes {
@@ -64,7 +197,7 @@ fn format_terminal(diff: &Diff, options: &OutputOptions) -> String {
     }
 
     output.push('\n');
-    output.push_str(&format_summary(&diff.stats));
+    output.push_str(&format_summary(&diff.stats, &diff.hidden));
 
     output
 }
@@ -102,36 +235,72 @@ fn format_change_terminal(change: &Change, options: &OutputOptions) -> String {
     }
 }
 
-fn format_json(diff: &Diff) -> Result<String, OutputError> {
-    use serde_json::json;
+/// A JSON document describing the diff of two documents.
+#[derive(Serialize)]
+struct DiffDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    old: Option<InputDocument<'a>>,
+    new: Option<InputDocument<'a>>,
+    config: ConfigDocument<'a>,
+    #[serde(flatten)]
+    diff: &'a Diff,
+}
 
-    let changes: Vec<serde_json::Value> = diff
-        .changes
-        .iter()
-        .map(|c| {
-            json!({
-                "path": c.path,
-                "type": format!("{:?}", c.change_type).to_lowercase(),
-                "old_value": c.old_value.as_ref().map(node_to_json_value),
-                "new_value": c.new_value.as_ref().map(node_to_json_value),
-            })
-        })
-        .collect();
+#[derive(Serialize)]
+struct InputDocument<'a> {
+    path: &'a str,
+    format: Option<&'static str>,
+}
 
-    let output = json!({
-        "changes": changes,
-        "stats": {
-            "added": diff.stats.added,
-            "removed": diff.stats.removed,
-            "modified": diff.stats.modified,
-            "unchanged": diff.stats.unchanged,
+impl<'a> InputDocument<'a> {
+    fn new(input: &'a InputInfo) -> Self {
+        Self {
+            path: &input.path,
+            format: format_name(input.format),
         }
-    });
+    }
+}
+
+#[derive(Serialize)]
+struct ConfigDocument<'a> {
+    diff: Option<&'a DiffConfig>,
+    filter: Option<&'a FilterConfig>,
+}
 
-    serde_json::to_string_pretty(&output)
+impl<'a> ConfigDocument<'a> {
+    fn new(context: &'a DiffContext) -> Self {
+        Self {
+            diff: context.config.as_ref(),
+            filter: context.filter.as_ref(),
+        }
+    }
+}
+
+fn format_json(diff: &Diff, context: &DiffContext) -> Result<String, OutputError> {
+    let document = DiffDocument {
+        schema_version: SCHEMA_VERSION,
+        kind: "diff",
+        old: context.old.as_ref().map(InputDocument::new),
+        new: context.new.as_ref().map(InputDocument::new),
+        config: ConfigDocument::new(context),
+        diff,
+    };
+
+    serde_json::to_string_pretty(&document)
         .map_err(|e| OutputError::JsonSerializationError { source: e })
 }
 
+/// Returns the name of a parsed format, or None if it is not known.
+fn format_name(format: Option<FormatHint>) -> Option<&'static str> {
+    match format? {
+        FormatHint::Json => Some("json"),
+        FormatHint::Yaml => Some("yaml"),
+        FormatHint::Toml => Some("toml"),
+        FormatHint::Auto => None,
+    }
+}
+
 fn format_plain(diff: &Diff, options: &OutputOptions) -> String {
     let mut output = String::new();
 
@@ -142,7 +311,7 @@ fn format_plain(diff: &Diff, options: &OutputOptions) -> String {
         .collect();
 
     if changes.is_empty() {
-        return "No changes detected.".to_string();
+        return format!("No changes detected.{}", hidden_note(&diff.hidden));
     }
 
     for change in changes {
@@ -152,7 +321,7 @@ fn format_plain(diff: &Diff, options: &OutputOptions) -> String {
     }
 
     output.push('\n');
-    output.push_str(&format_summary(&diff.stats));
+    output.push_str(&format_summary(&diff.stats, &diff.hidden));
 
     output
 }
@@ -183,23 +352,408 @@ fn format_change_plain(change: &Change, options: &OutputOptions) -> String {
     }
 }
 
-fn format_path(path: &[String]) -> String {
+/// Formats a path for display, e.g. `spec.containers[0].image`.
+///
+/// Keys that contain dots are quoted (`labels."app.kubernetes.io/name"`), so
+/// the result can be used as a filter pattern that matches exactly this path.
+pub fn format_path(path: &Path) -> String {
     if path.is_empty() {
         return "(root)".to_string();
     }
+    path.to_dotted()
+}
 
-    let mut result = String::new();
-    for (i, component) in path.iter().enumerate() {
-        if component.starts_with('[') {
-            result.push_str(component);
-        } else {
-            if i > 0 {
-                result.push('.');
+/// Formats a directory or revision diff as one report with a section per file.
+pub fn format_dir_diff(
+    dir: &DirDiff,
+    format: &OutputFormat,
+    options: &OutputOptions,
+) -> Result<String, OutputError> {
+    format_dir_diff_with_context(dir, format, options, &DiffContext::default())
+}
+
+/// Like [`format_dir_diff`], describing the compared trees and settings in
+/// JSON output.
+pub fn format_dir_diff_with_context(
+    dir: &DirDiff,
+    format: &OutputFormat,
+    options: &OutputOptions,
+    context: &DiffContext,
+) -> Result<String, OutputError> {
+    match format {
+        OutputFormat::Json => format_dir_json(dir, context),
+        OutputFormat::Terminal | OutputFormat::Plain => {
+            let colored = *format == OutputFormat::Terminal;
+            let mut output = String::new();
+
+            for file in &dir.files {
+                let header = format!("=== {} ===", file_header(file));
+                if colored {
+                    output.push_str(&header.bold().to_string());
+                } else {
+                    output.push_str(&header);
+                }
+                output.push('\n');
+
+                for change in file
+                    .diff
+                    .changes
+                    .iter()
+                    .filter(|c| should_show_change(c, options))
+                {
+                    if colored {
+                        output.push_str(&format_change_terminal(change, options));
+                    } else {
+                        output.push_str(&format_change_plain(change, options));
+                    }
+                    output.push('\n');
+                }
+                output.push('\n');
             }
-            result.push_str(component);
+
+            for skipped in &dir.skipped {
+                output.push_str(&format!("Skipped {}: {}\n", skipped.path, skipped.reason));
+            }
+            if !dir.skipped.is_empty() {
+                output.push('\n');
+            }
+
+            if dir.files.is_empty() {
+                let message = "No changes detected.";
+                if colored {
+                    output.push_str(&message.dimmed().to_string());
+                } else {
+                    output.push_str(message);
+                }
+                output.push('\n');
+            }
+
+            let mut files = format!(
+                "Files: {} changed, {} unchanged",
+                dir.files.len(),
+                dir.unchanged_files
+            );
+            if !dir.unsupported.is_empty() {
+                files.push_str(&format!(", {} unsupported", dir.unsupported.len()));
+            }
+            output.push_str(&files);
+            output.push('\n');
+            output.push_str(&format_summary(&dir.stats, &dir.hidden));
+            Ok(output)
         }
     }
-    result
+}
+
+fn file_header(file: &FileDiff) -> String {
+    match &file.status {
+        FileStatus::Added => format!("{} (added)", file.path),
+        FileStatus::Removed => format!("{} (removed)", file.path),
+        FileStatus::Modified => format!("{} (modified)", file.path),
+        FileStatus::Renamed { from } => format!("{} (renamed from {})", file.path, from),
+    }
+}
+
+/// A JSON document describing the diff of two trees.
+#[derive(Serialize)]
+struct TreeDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    old: Option<InputDocument<'a>>,
+    new: Option<InputDocument<'a>>,
+    config: ConfigDocument<'a>,
+    files: Vec<FileDocument<'a>>,
+    skipped: Vec<SkippedDocument<'a>>,
+    unsupported: &'a [String],
+    unchanged_files: usize,
+    stats: &'a DiffStats,
+    hidden: &'a HiddenChanges,
+}
+
+#[derive(Serialize)]
+struct FileDocument<'a> {
+    path: &'a str,
+    status: &'static str,
+    from: Option<&'a str>,
+    format: Option<&'static str>,
+    #[serde(flatten)]
+    diff: &'a Diff,
+}
+
+#[derive(Serialize)]
+struct SkippedDocument<'a> {
+    path: &'a str,
+    reason: &'a str,
+}
+
+fn format_dir_json(dir: &DirDiff, context: &DiffContext) -> Result<String, OutputError> {
+    let files = dir
+        .files
+        .iter()
+        .map(|file| {
+            let (status, from) = match &file.status {
+                FileStatus::Added => ("added", None),
+                FileStatus::Removed => ("removed", None),
+                FileStatus::Modified => ("modified", None),
+                FileStatus::Renamed { from } => ("renamed", Some(from.as_str())),
+            };
+            FileDocument {
+                path: &file.path,
+                status,
+                from,
+                format: format_name(Some(detect_format(std::path::Path::new(&file.path)))),
+                diff: &file.diff,
+            }
+        })
+        .collect();
+
+    let skipped = dir
+        .skipped
+        .iter()
+        .map(|s| SkippedDocument {
+            path: &s.path,
+            reason: &s.reason,
+        })
+        .collect();
+
+    let document = TreeDocument {
+        schema_version: SCHEMA_VERSION,
+        kind: "tree",
+        old: context.old.as_ref().map(InputDocument::new),
+        new: context.new.as_ref().map(InputDocument::new),
+        config: ConfigDocument::new(context),
+        files,
+        skipped,
+        unsupported: &dir.unsupported,
+        unchanged_files: dir.unchanged_files,
+        stats: &dir.stats,
+        hidden: &dir.hidden,
+    };
+
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| OutputError::JsonSerializationError { source: e })
+}
+
+/// Formats the semantic history of a file, one block per commit.
+pub fn format_history(
+    entries: &[HistoryEntry],
+    format: &OutputFormat,
+    options: &OutputOptions,
+) -> Result<String, OutputError> {
+    if *format == OutputFormat::Json {
+        let commits: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "commit": entry.commit.hash,
+                    "author": entry.commit.author,
+                    "date": entry.commit.date,
+                    "subject": entry.commit.subject,
+                    "changes": entry.diff.changes,
+                })
+            })
+            .collect();
+        return serde_json::to_string_pretty(&commits)
+            .map_err(|e| OutputError::JsonSerializationError { source: e });
+    }
+
+    let colored = *format == OutputFormat::Terminal;
+    if entries.is_empty() {
+        let message = "No changes found in history.";
+        return Ok(if colored {
+            message.dimmed().to_string()
+        } else {
+            message.to_string()
+        });
+    }
+
+    let mut output = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+
+        let hash = &entry.commit.hash[..entry.commit.hash.len().min(7)];
+        let date = &entry.commit.date[..entry.commit.date.len().min(10)];
+        if colored {
+            output.push_str(&format!(
+                "{} {} {} {}\n",
+                hash.yellow(),
+                date,
+                entry.commit.author.bold(),
+                entry.commit.subject
+            ));
+        } else {
+            output.push_str(&format!(
+                "{} {} {} {}\n",
+                hash, date, entry.commit.author, entry.commit.subject
+            ));
+        }
+
+        for change in &entry.diff.changes {
+            let line = if colored {
+                format_change_terminal(change, options)
+            } else {
+                format_change_plain(change, options)
+            };
+            output.push_str("  ");
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
+
+/// Formats a structural blame as a table with one row per path, or as a JSON
+/// object keyed by path.
+pub fn format_blame(
+    entries: &[BlameEntry],
+    format: &OutputFormat,
+    options: &OutputOptions,
+) -> Result<String, OutputError> {
+    if *format == OutputFormat::Json {
+        let mut paths = serde_json::Map::new();
+        for entry in entries {
+            let commit = entry.commit.as_ref();
+            paths.insert(
+                format_path(&entry.path),
+                json!({
+                    "value": entry.value,
+                    "commit": commit.map(|c| &c.hash),
+                    "author": commit.map(|c| &c.author),
+                    "date": commit.map(|c| &c.date),
+                    "subject": commit.map(|c| &c.subject),
+                }),
+            );
+        }
+        return serde_json::to_string_pretty(&paths)
+            .map_err(|e| OutputError::JsonSerializationError { source: e });
+    }
+
+    let colored = *format == OutputFormat::Terminal;
+    let rows: Vec<(String, String, String)> = entries
+        .iter()
+        .map(|entry| {
+            let (hash, date, author) = match &entry.commit {
+                Some(c) => (
+                    c.hash[..c.hash.len().min(7)].to_string(),
+                    c.date[..c.date.len().min(10)].to_string(),
+                    c.author.clone(),
+                ),
+                None => (
+                    "0000000".to_string(),
+                    "-".repeat(10),
+                    "Not Committed Yet".to_string(),
+                ),
+            };
+            (
+                hash,
+                format!("{} {}", date, author),
+                entry_line(entry, options),
+            )
+        })
+        .collect();
+
+    let width = rows
+        .iter()
+        .map(|(_, who, _)| who.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for (hash, who, line) in rows {
+        let padding = " ".repeat(width - who.chars().count());
+        if colored {
+            output.push_str(&format!("{} {}{}  {}\n", hash.yellow(), who, padding, line));
+        } else {
+            output.push_str(&format!("{} {}{}  {}\n", hash, who, padding, line));
+        }
+    }
+    Ok(output)
+}
+
+fn entry_line(entry: &BlameEntry, options: &OutputOptions) -> String {
+    format!(
+        "{}: {}",
+        format_path(&entry.path),
+        format_value(&entry.value, options.max_value_length)
+    )
+}
+
+/// Formats merge conflicts as a readable report, one block per conflicting path.
+pub fn format_conflicts(conflicts: &[MergeConflict], options: &OutputOptions) -> String {
+    let value = |node: &Option<Node>| match node {
+        Some(node) => format_value(node, options.max_value_length),
+        None => "(absent)".to_string(),
+    };
+
+    let mut output = String::new();
+    for conflict in conflicts {
+        output.push_str(&format!("CONFLICT {}\n", format_path(&conflict.path)));
+        output.push_str(&format!("  base:   {}\n", value(&conflict.base)));
+        output.push_str(&format!("  ours:   {}\n", value(&conflict.ours)));
+        output.push_str(&format!("  theirs: {}\n", value(&conflict.theirs)));
+    }
+    output
+}
+
+/// Formats policy violations as a report grouped by file.
+pub fn format_violations(violations: &[Violation], options: &OutputOptions) -> String {
+    let mut output = String::new();
+    let mut current_file: Option<&str> = None;
+
+    for violation in violations {
+        if current_file != Some(violation.file.as_str()) {
+            output.push_str(&format!("{}:\n", violation.file));
+            current_file = Some(&violation.file);
+        }
+        output.push_str("  ");
+        output.push_str(&format_change_plain(&violation.change, options));
+        output.push('\n');
+    }
+    output
+}
+
+/// Renders a document as sorted `path = value` lines, one per leaf value.
+///
+/// Object keys are sorted and array elements keep their order, so two
+/// documents with the same content always render identically regardless of
+/// formatting or key order. Values are written as JSON. This is the output
+/// used for `git diff` textconv.
+///
+/// # Examples
+///
+/// ```
+/// use sdiff_rs::output::format_textconv;
+/// use sdiff_rs::parse_yaml;
+///
+/// let node = parse_yaml("name: app\nports: [80, 443]\n").unwrap();
+/// assert_eq!(
+///     format_textconv(&node),
+///     "name = \"app\"\nports[0] = 80\nports[1] = 443\n"
+/// );
+/// ```
+pub fn format_textconv(node: &Node) -> String {
+    let mut output = String::new();
+    for change in compute_added_diff(node).changes {
+        if let Some(value) = &change.new_value {
+            output.push_str(&format_path(&change.path));
+            output.push_str(" = ");
+            output.push_str(&canonical_value(value));
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn canonical_value(node: &Node) -> String {
+    match node {
+        Node::Null => "null".to_string(),
+        Node::Bool(b) => b.to_string(),
+        Node::Number(n) if is_integral(*n) => format!("{}", *n as i64),
+        Node::Number(n) => n.to_string(),
+        Node::String(s) => serde_json::Value::String(s.clone()).to_string(),
+        Node::Object(_) => "{}".to_string(),
+        Node::Array(_) => "[]".to_string(),
+    }
 }
 
 fn format_value(node: &Node, max_length: usize) -> String {
@@ -214,9 +768,9 @@ fn should_show_change(change: &Change, options: &OutputOptions) -> bool {
     }
 }
 
-fn format_summary(stats: &crate::diff::DiffStats) -> String {
+fn format_summary(stats: &DiffStats, hidden: &HiddenChanges) -> String {
     if stats.is_empty() {
-        return "Summary: No changes".to_string();
+        return format!("Summary: No changes{}", hidden_note(hidden));
     }
 
     let mut parts = Vec::new();
@@ -233,27 +787,15 @@ fn format_summary(stats: &crate::diff::DiffStats) -> String {
         parts.push(format!("{} unchanged", stats.unchanged));
     }
 
-    format!("Summary: {}", parts.join(", "))
+    format!("Summary: {}{}", parts.join(", "), hidden_note(hidden))
 }
 
-fn node_to_json_value(node: &Node) -> serde_json::Value {
-    use serde_json::json;
-
-    match node {
-        Node::Null => json!(null),
-        Node::Bool(b) => json!(b),
-        Node::Number(n) => json!(n),
-        Node::String(s) => json!(s),
-        Node::Array(arr) => {
-            let values: Vec<serde_json::Value> = arr.iter().map(node_to_json_value).collect();
-            json!(values)
-        }
-        Node::Object(map) => {
-            let obj: serde_json::Map<String, serde_json::Value> = map
-                .iter()
-                .map(|(k, v)| (k.clone(), node_to_json_value(v)))
-                .collect();
-            json!(obj)
-        }
+/// Returns ` (N changes hidden by filters)`, or nothing if no change was
+/// hidden.
+fn hidden_note(hidden: &HiddenChanges) -> String {
+    match hidden.total() {
+        0 => String::new(),
+        1 => " (1 change hidden by filters)".to_string(),
+        n => format!(" ({} changes hidden by filters)", n),
     }
 }
@@ -22,13 +22,15 @@
 
 use crate::error::ParseError;
 use crate::tree::Node;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Read};
 use std::path::Path;
 
 /// Hint for the input format when auto-detection is not possible.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FormatHint {
     /// Automatically detect format (try JSON, then YAML, then TOML)
     #[default]
@@ -53,7 +55,18 @@ pub fn parse_stdin(hint: FormatHint) -> Result<Node, ParseError> {
 
 /// Parses content string with the given format hint.
 pub fn parse_content(content: &str, hint: FormatHint, source: &str) -> Result<Node, ParseError> {
-    match hint {
+    parse_content_with_format(content, hint, source).map(|(node, _)| node)
+}
+
+/// Parses content string with the given format hint, also returning the
+/// format it was parsed as. With `FormatHint::Auto` this is the first of
+/// JSON, YAML and TOML that parses; it is never `Auto`.
+pub fn parse_content_with_format(
+    content: &str,
+    hint: FormatHint,
+    source: &str,
+) -> Result<(Node, FormatHint), ParseError> {
+    let node = match hint {
         FormatHint::Json => {
             parse_json(content).map_err(|e| ParseError::json_error(source.to_string(), e))
         }
@@ -63,16 +76,51 @@ pub fn parse_content(content: &str, hint: FormatHint, source: &str) -> Result<No
         FormatHint::Toml => {
             parse_toml(content).map_err(|e| ParseError::toml_error(source.to_string(), e))
         }
-        FormatHint::Auto => parse_json(content)
-            .map_err(|_| ())
-            .or_else(|_| parse_yaml(content).map_err(|_| ()))
-            .or_else(|_| parse_toml(content).map_err(|_| ()))
-            .map_err(|_| ParseError::unknown_format(source.to_string())),
+        FormatHint::Auto => {
+            return parse_json(content)
+                .map(|node| (node, FormatHint::Json))
+                .map_err(|_| ())
+                .or_else(|_| {
+                    parse_yaml(content)
+                        .map(|node| (node, FormatHint::Yaml))
+                        .map_err(|_| ())
+                })
+                .or_else(|_| {
+                    parse_toml(content)
+                        .map(|node| (node, FormatHint::Toml))
+                        .map_err(|_| ())
+                })
+                .map_err(|_| ParseError::unknown_format(source.to_string()));
+        }
+    }?;
+    Ok((node, hint))
+}
+
+/// Detects the format of a file from its extension.
+///
+/// Returns `FormatHint::Auto` when the extension is missing or not recognized.
+pub fn detect_format(path: &Path) -> FormatHint {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|s| s.to_lowercase());
+
+    match extension.as_deref() {
+        Some("json") => FormatHint::Json,
+        Some("yaml") | Some("yml") => FormatHint::Yaml,
+        Some("toml") => FormatHint::Toml,
+        _ => FormatHint::Auto,
     }
 }
 
 /// Parses a file into a Node AST. Format is detected by file extension.
 pub fn parse_file(path: &Path) -> Result<Node, ParseError> {
+    parse_file_with_format(path).map(|(node, _)| node)
+}
+
+/// Parses a file into a Node AST, also returning the format it was parsed
+/// as (see [`parse_content_with_format`]).
+pub fn parse_file_with_format(path: &Path) -> Result<(Node, FormatHint), ParseError> {
     if !path.exists() {
         return Err(ParseError::file_not_found(
             path.to_string_lossy().to_string(),
@@ -82,24 +130,7 @@ pub fn parse_file(path: &Path) -> Result<Node, ParseError> {
     let content = fs::read_to_string(path)
         .map_err(|e| ParseError::read_error(path.to_string_lossy().to_string(), e))?;
 
-    let extension = path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|s| s.to_lowercase());
-
-    match extension.as_deref() {
-        Some("json") => parse_json(&content)
-            .map_err(|e| ParseError::json_error(path.to_string_lossy().to_string(), e)),
-        Some("yaml") | Some("yml") => parse_yaml(&content)
-            .map_err(|e| ParseError::yaml_error(path.to_string_lossy().to_string(), e)),
-        Some("toml") => parse_toml(&content)
-            .map_err(|e| ParseError::toml_error(path.to_string_lossy().to_string(), e)),
-        _ => parse_json(&content)
-            .map_err(|_| ())
-            .or_else(|_| parse_yaml(&content).map_err(|_| ()))
-            .or_else(|_| parse_toml(&content).map_err(|_| ()))
-            .map_err(|_| ParseError::unknown_format(path.to_string_lossy().to_string())),
-    }
+    parse_content_with_format(&content, detect_format(path), &path.to_string_lossy())
 }
 
 /// Parses a JSON string into a Node.
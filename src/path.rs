@@ -0,0 +1,229 @@
+//! Paths to values inside a document.
+//!
+//! A [`Path`] is a sequence of [`PathSegment`]s, each either an object key or
+//! an array index, so keys and indices can never be confused (a key literally
+//! named `[3]` is still a key). Paths can be rendered in three notations:
+//!
+//! - dotted, as used by the output formatters and filter patterns:
+//!   `spec.containers[0]."app.kubernetes.io/name"`
+//! - JSON Pointer (RFC 6901): `/spec/containers/0/app.kubernetes.io~1name`
+//! - JSONPath: `$.spec.containers[0]['app.kubernetes.io/name']`
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::path::{Path, PathSegment};
+//!
+//! let path = Path::root().key("spec").key("containers").index(0).key("image");
+//!
+//! assert_eq!(path[2], PathSegment::Index(0));
+//! assert_eq!(path.to_string(), "spec.containers[0].image");
+//! assert_eq!(path.to_json_pointer(), "/spec/containers/0/image");
+//! assert_eq!(path.to_jsonpath(), "$.spec.containers[0].image");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// A single step in a path.
+///
+/// Serialized as a string for keys and a number for indices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PathSegment {
+    /// An object key
+    Key(String),
+    /// An array index
+    Index(usize),
+}
+
+impl PathSegment {
+    /// Returns the key, or None for an index.
+    pub fn as_key(&self) -> Option<&str> {
+        match self {
+            PathSegment::Key(key) => Some(key),
+            PathSegment::Index(_) => None,
+        }
+    }
+
+    /// Returns the index, or None for a key.
+    pub fn as_index(&self) -> Option<usize> {
+        match self {
+            PathSegment::Key(_) => None,
+            PathSegment::Index(index) => Some(*index),
+        }
+    }
+}
+
+/// The location of a value in a document. The empty path is the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Returns the path to the root of a document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Returns this path extended by an object key.
+    pub fn key(mut self, key: &str) -> Self {
+        self.push(PathSegment::Key(key.to_string()));
+        self
+    }
+
+    /// Returns this path extended by an array index.
+    pub fn index(mut self, index: usize) -> Self {
+        self.push(PathSegment::Index(index));
+        self
+    }
+
+    /// Returns a copy of this path extended by `segment`.
+    pub fn child(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// Returns the path of the enclosing container, or None for the root.
+    pub fn parent(&self) -> Option<Path> {
+        let (_, parent) = self.segments.split_last()?;
+        Some(Path::from(parent.to_vec()))
+    }
+
+    /// Renders the path in dotted notation, the syntax accepted by filter
+    /// patterns. Keys that are empty, contain `.`, `[`, `*` or `?`, start
+    /// with a quote, `/`, `!` or `#`, or are the JSONPath root `$` are
+    /// double-quoted, with `"` and `\` escaped by a backslash. The root
+    /// renders as an empty string.
+    pub fn to_dotted(&self) -> String {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) => {
+                    if !result.is_empty() {
+                        result.push('.');
+                    }
+                    result.push_str(&quote_key(key));
+                }
+                PathSegment::Index(index) => result.push_str(&format!("[{}]", index)),
+            }
+        }
+        result
+    }
+
+    /// Renders the path as a JSON Pointer (RFC 6901). The root renders as an
+    /// empty string.
+    pub fn to_json_pointer(&self) -> String {
+        let mut result = String::new();
+        for segment in &self.segments {
+            result.push('/');
+            match segment {
+                PathSegment::Key(key) => {
+                    result.push_str(&key.replace('~', "~0").replace('/', "~1"))
+                }
+                PathSegment::Index(index) => result.push_str(&index.to_string()),
+            }
+        }
+        result
+    }
+
+    /// Renders the path as a JSONPath expression starting at `$`. Keys that
+    /// are not plain identifiers use bracket notation (`$['a.b']`).
+    pub fn to_jsonpath(&self) -> String {
+        let mut result = String::from("$");
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) if is_identifier(key) => {
+                    result.push('.');
+                    result.push_str(key);
+                }
+                PathSegment::Key(key) => {
+                    result.push_str("['");
+                    for c in key.chars() {
+                        if c == '\'' || c == '\\' {
+                            result.push('\\');
+                        }
+                        result.push(c);
+                    }
+                    result.push_str("']");
+                }
+                PathSegment::Index(index) => result.push_str(&format!("[{}]", index)),
+            }
+        }
+        result
+    }
+}
+
+impl Deref for Path {
+    type Target = [PathSegment];
+
+    fn deref(&self) -> &[PathSegment] {
+        &self.segments
+    }
+}
+
+impl From<Vec<PathSegment>> for Path {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl From<&[PathSegment]> for Path {
+    fn from(segments: &[PathSegment]) -> Self {
+        Self {
+            segments: segments.to_vec(),
+        }
+    }
+}
+
+impl FromIterator<PathSegment> for Path {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Self {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_dotted())
+    }
+}
+
+/// Quotes a key for dotted notation if it would otherwise be read back as
+/// something else.
+pub(crate) fn quote_key(key: &str) -> String {
+    let needs_quotes = key.is_empty()
+        || key == "$"
+        || key.starts_with(['"', '/', '!', '#'])
+        || key.contains(['.', '[', '*', '?']);
+    if !needs_quotes {
+        return key.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in key.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
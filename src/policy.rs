@@ -0,0 +1,295 @@
+//! Change policies for protected paths.
+//!
+//! A policy lists path patterns that must not change, optionally restricted
+//! to files matching a glob, together with exceptions that are allowed to
+//! change anyway. It is used by the pre-commit hook to block commits that
+//! touch protected values.
+//!
+//! A path is protected when it, or one of its ancestors, matches a protected
+//! pattern. Changes to a container that holds a protected path (for example
+//! removing `database` when `database.host` is protected) are violations too.
+//!
+//! # Policy file
+//!
+//! ```toml
+//! # Applies to every file
+//! protected = ["metadata.name"]
+//!
+//! [[rules]]
+//! files = "config/prod/*.yaml"
+//! protected = ["database.**"]
+//! allowed = ["database.pool_size"]
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::policy::Policy;
+//! use sdiff_rs::{compute_diff, parse_json, DiffConfig};
+//!
+//! let policy = Policy::from_toml(r#"protected = ["database.host"]"#).unwrap();
+//!
+//! let old = parse_json(r#"{"database": {"host": "db1", "port": 5432}}"#).unwrap();
+//! let new = parse_json(r#"{"database": {"host": "db2", "port": 5433}}"#).unwrap();
+//! let diff = compute_diff(&old, &new, &DiffConfig::default());
+//!
+//! let violations = policy.check("app.json", &diff);
+//! assert_eq!(violations.len(), 1);
+//! assert_eq!(violations[0].change.path.to_string(), "database.host");
+//! ```
+
+use crate::diff::{
+    compute_added_diff, compute_diff, compute_removed_diff, Change, Diff, DiffConfig,
+};
+use crate::error::SdiffError;
+use crate::filter::{glob_match, PathPattern};
+use crate::git;
+use crate::parser::{detect_format, parse_content, FormatHint};
+use crate::path::PathSegment;
+use crate::tree::Node;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Name of the policy file, looked up at the repository root.
+pub const POLICY_FILE_NAME: &str = ".sdiff-policy.toml";
+
+/// Protected and allowed patterns for a set of files.
+#[derive(Debug, Clone)]
+pub struct PolicyRule {
+    /// Glob for the files this rule applies to (all files if None)
+    pub files: Option<String>,
+    /// Paths that must not change
+    pub protected: Vec<PathPattern>,
+    /// Paths that may change even though they are protected
+    pub allowed: Vec<PathPattern>,
+}
+
+impl PolicyRule {
+    /// Returns true if this rule applies to `file`.
+    pub fn applies_to(&self, file: &str) -> bool {
+        self.files
+            .as_ref()
+            .is_none_or(|glob| glob_match(glob, file))
+    }
+
+    /// Returns true if a change at `path` violates this rule.
+    ///
+    /// Without the documents, filter segments are assumed to match when
+    /// deciding what is protected and not to match when deciding what is
+    /// allowed, so the rule errs on the side of forbidding.
+    pub fn forbids(&self, path: &[PathSegment]) -> bool {
+        covers(
+            &self.protected,
+            path,
+            |pattern, path| pattern.matches_prefix(path),
+            |pattern, path| pattern.matches(path),
+        ) && !allowed(&self.allowed, path, |pattern, path| pattern.matches(path))
+    }
+
+    /// Like [`PolicyRule::forbids`], evaluating filter segments in the
+    /// `old` and `new` documents. A pattern applies if it matches in either.
+    pub fn forbids_in(&self, path: &[PathSegment], old: &Node, new: &Node) -> bool {
+        let matches = |pattern: &PathPattern, path: &[PathSegment]| {
+            pattern.matches_in(path, old) || pattern.matches_in(path, new)
+        };
+        let matches_prefix = |pattern: &PathPattern, path: &[PathSegment]| {
+            pattern.matches_prefix_in(path, old) || pattern.matches_prefix_in(path, new)
+        };
+        covers(&self.protected, path, matches_prefix, matches)
+            && !allowed(&self.allowed, path, matches)
+    }
+}
+
+/// A change that violates the policy.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// File containing the change, relative to the repository root
+    pub file: String,
+    /// The forbidden change
+    pub change: Change,
+}
+
+/// A set of rules describing which paths may not change.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    protected: Vec<String>,
+    #[serde(default)]
+    allowed: Vec<String>,
+    #[serde(default)]
+    rules: Vec<RuleFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    files: Option<String>,
+    #[serde(default)]
+    protected: Vec<String>,
+    #[serde(default)]
+    allowed: Vec<String>,
+}
+
+impl Policy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule protecting `protected` in files matching `files`, except
+    /// for paths matching `allowed`. Fails if a pattern is malformed.
+    pub fn rule(
+        mut self,
+        files: Option<&str>,
+        protected: &[&str],
+        allowed: &[&str],
+    ) -> Result<Self, SdiffError> {
+        self.rules.push(PolicyRule {
+            files: files.map(str::to_string),
+            protected: parse_patterns(protected)?,
+            allowed: parse_patterns(allowed)?,
+        });
+        Ok(self)
+    }
+
+    /// Parses a policy from TOML content. Malformed patterns are an error,
+    /// so a typo cannot leave a path unprotected.
+    pub fn from_toml(content: &str) -> Result<Self, SdiffError> {
+        let file: PolicyFile = toml::from_str(content).map_err(|e| SdiffError::ConfigError {
+            message: e.to_string(),
+        })?;
+
+        let mut policy = Policy::new();
+        if !file.protected.is_empty() {
+            policy.rules.push(PolicyRule {
+                files: None,
+                protected: parse_patterns(&file.protected)?,
+                allowed: parse_patterns(&file.allowed)?,
+            });
+        }
+        for rule in file.rules {
+            policy.rules.push(PolicyRule {
+                files: rule.files,
+                protected: parse_patterns(&rule.protected)?,
+                allowed: parse_patterns(&rule.allowed)?,
+            });
+        }
+        Ok(policy)
+    }
+
+    /// Reads a policy from a TOML file.
+    pub fn from_file(path: &Path) -> Result<Self, SdiffError> {
+        let content = fs::read_to_string(path).map_err(|e| SdiffError::ConfigError {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+        Self::from_toml(&content).map_err(|e| SdiffError::ConfigError {
+            message: format!("{}: {}", path.display(), e),
+        })
+    }
+
+    /// Returns the changes in `diff` that the policy forbids for `file`.
+    ///
+    /// Filter segments are evaluated as [`PolicyRule::forbids`] describes;
+    /// use [`Policy::check_in`] when the documents are at hand.
+    pub fn check(&self, file: &str, diff: &Diff) -> Vec<Violation> {
+        self.violations(file, diff, |rule, path| rule.forbids(path))
+    }
+
+    /// Like [`Policy::check`], evaluating filter segments in the `old` and
+    /// `new` documents that `diff` was computed from.
+    pub fn check_in(&self, file: &str, diff: &Diff, old: &Node, new: &Node) -> Vec<Violation> {
+        self.violations(file, diff, |rule, path| rule.forbids_in(path, old, new))
+    }
+
+    fn violations(
+        &self,
+        file: &str,
+        diff: &Diff,
+        forbids: impl Fn(&PolicyRule, &[PathSegment]) -> bool,
+    ) -> Vec<Violation> {
+        let rules: Vec<&PolicyRule> = self.rules.iter().filter(|r| r.applies_to(file)).collect();
+
+        diff.changes
+            .iter()
+            .filter(|change| rules.iter().any(|rule| forbids(rule, &change.path)))
+            .map(|change| Violation {
+                file: file.to_string(),
+                change: change.clone(),
+            })
+            .collect()
+    }
+
+    /// Checks every staged JSON, YAML and TOML file against `HEAD`.
+    ///
+    /// A file that fails to parse on either side is an error, so the hook
+    /// blocks the commit rather than letting an unchecked file through.
+    pub fn check_staged(&self, config: &DiffConfig) -> Result<Vec<Violation>, SdiffError> {
+        let mut violations = Vec::new();
+
+        for file in git::staged_files()? {
+            let format = detect_format(Path::new(&file));
+            if format == FormatHint::Auto || !self.rules.iter().any(|r| r.applies_to(&file)) {
+                continue;
+            }
+
+            let parse = |content: String, source: String| parse_content(&content, format, &source);
+            let old = match git::read_repo_file("HEAD", &file) {
+                Ok(content) => Some(parse(content, format!("HEAD:{}", file))?),
+                Err(_) => None,
+            };
+            let new = match git::read_staged_file(&file) {
+                Ok(content) => Some(parse(content, format!(":{}", file))?),
+                Err(_) => None,
+            };
+
+            let diff = match (&old, &new) {
+                (Some(old), Some(new)) => compute_diff(old, new, config),
+                (None, Some(new)) => compute_added_diff(new),
+                (Some(old), None) => compute_removed_diff(old),
+                (None, None) => continue,
+            };
+            let (old, new) = (old.unwrap_or(Node::Null), new.unwrap_or(Node::Null));
+            violations.extend(self.check_in(&file, &diff, &old, &new));
+        }
+
+        Ok(violations)
+    }
+}
+
+fn parse_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<PathPattern>, SdiffError> {
+    patterns
+        .iter()
+        .map(|p| PathPattern::try_parse(p.as_ref()))
+        .collect()
+}
+
+/// Returns true if `path` is inside, equal to, or an ancestor of a path
+/// matching one of `patterns`.
+fn covers(
+    patterns: &[PathPattern],
+    path: &[PathSegment],
+    matches_prefix: impl Fn(&PathPattern, &[PathSegment]) -> bool,
+    matches: impl Fn(&PathPattern, &[PathSegment]) -> bool,
+) -> bool {
+    patterns.iter().any(|pattern| {
+        matches_prefix(pattern, path) || (0..path.len()).any(|n| matches(pattern, &path[..n]))
+    })
+}
+
+/// Returns true if `path` is equal to or inside a path matching one of
+/// `patterns`.
+fn allowed(
+    patterns: &[PathPattern],
+    path: &[PathSegment],
+    matches: impl Fn(&PathPattern, &[PathSegment]) -> bool,
+) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| (0..=path.len()).any(|n| matches(pattern, &path[..n])))
+}
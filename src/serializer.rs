@@ -0,0 +1,414 @@
+//! Conversion of any serializable Rust value into a node.
+//!
+//! [`NodeSerializer`] is a `serde::Serializer` whose output is a [`Node`],
+//! so in-memory values can be diffed without writing them out as JSON text
+//! and parsing them again. Values map the way `serde_json` maps them:
+//! structs and maps become objects, sequences and tuples become arrays,
+//! `None` and `()` become null, unit enum variants become strings and other
+//! variants become an object with the variant name as its only key.
+//!
+//! Numbers are stored as `f64`, so integers whose magnitude exceeds 2^53 are
+//! rejected rather than rounded. Map keys that are numbers or booleans are
+//! written as strings, and two keys that end up the same are an error.
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::parse_json;
+//! use sdiff_rs::serializer::to_node;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Service {
+//!     name: String,
+//!     replicas: u32,
+//! }
+//!
+//! let node = to_node(&Service { name: "web".to_string(), replicas: 3 }).unwrap();
+//! assert_eq!(node, parse_json(r#"{"name": "web", "replicas": 3}"#).unwrap());
+//! ```
+
+use crate::error::SerializeError;
+use crate::tree::{integer_number, Node};
+use serde::ser::{self, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Converts a serializable value into a node.
+pub fn to_node<T: Serialize + ?Sized>(value: &T) -> Result<Node, SerializeError> {
+    value.serialize(NodeSerializer)
+}
+
+/// A `serde::Serializer` that produces a [`Node`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeSerializer;
+
+impl ser::Serializer for NodeSerializer {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> Result<Node, SerializeError> {
+        Ok(Node::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node, SerializeError> {
+        self.serialize_i128(i128::from(v))
+    }
+
+    /// Fails on integers that an `f64` cannot hold exactly.
+    fn serialize_i128(self, v: i128) -> Result<Node, SerializeError> {
+        exact_integer(v < 0, v.unsigned_abs(), &v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node, SerializeError> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    /// Fails on integers that an `f64` cannot hold exactly.
+    fn serialize_u128(self, v: u128) -> Result<Node, SerializeError> {
+        exact_integer(false, v, &v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node, SerializeError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    /// Fails on NaN and infinities, which no supported format can hold.
+    fn serialize_f64(self, v: f64) -> Result<Node, SerializeError> {
+        if v.is_finite() {
+            Ok(Node::Number(v))
+        } else {
+            Err(SerializeError::UnsupportedValue {
+                value: v.to_string(),
+            })
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node, SerializeError> {
+        Ok(Node::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node, SerializeError> {
+        Ok(Node::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node, SerializeError> {
+        Ok(Node::Array(
+            v.iter().map(|&b| Node::Number(f64::from(b))).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Node, SerializeError> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Node, SerializeError> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, SerializeError> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Node, SerializeError> {
+        Ok(Node::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Node, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node, SerializeError> {
+        Ok(tagged(variant, to_node(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerializeError> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerializeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTupleVariant, SerializeError> {
+        Ok(SerializeTupleVariant {
+            variant,
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject, SerializeError> {
+        Ok(SerializeObject {
+            map: HashMap::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, SerializeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructVariant, SerializeError> {
+        Ok(SerializeStructVariant {
+            variant,
+            map: HashMap::with_capacity(len),
+        })
+    }
+}
+
+/// Wraps the value of an enum variant as `{variant: value}`.
+fn tagged(variant: &str, value: Node) -> Node {
+    Node::Object(HashMap::from([(variant.to_string(), value)]))
+}
+
+/// Converts an integer into a number node, failing if it would round.
+fn exact_integer(
+    negative: bool,
+    magnitude: u128,
+    value: &dyn std::fmt::Display,
+) -> Result<Node, SerializeError> {
+    integer_number(negative, magnitude)
+        .map(Node::Number)
+        .ok_or_else(|| SerializeError::UnsupportedValue {
+            value: value.to_string(),
+        })
+}
+
+/// Adds an entry to an object, failing if the key is already present.
+fn insert_unique(
+    map: &mut HashMap<String, Node>,
+    key: String,
+    value: Node,
+) -> Result<(), SerializeError> {
+    match map.entry(key) {
+        Entry::Occupied(entry) => Err(SerializeError::DuplicateKey {
+            key: entry.key().clone(),
+        }),
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+            Ok(())
+        }
+    }
+}
+
+/// Converts a map key into an object key.
+fn object_key<T: Serialize + ?Sized>(key: &T) -> Result<String, SerializeError> {
+    match to_node(key)? {
+        Node::String(s) => Ok(s),
+        Node::Bool(b) => Ok(b.to_string()),
+        Node::Number(n) => Ok(n.to_string()),
+        other => Err(SerializeError::UnsupportedKey {
+            key: other.type_name().to_string(),
+        }),
+    }
+}
+
+/// Builds an array from a sequence, tuple or tuple struct.
+pub struct SerializeArray {
+    items: Vec<Node>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.items.push(to_node(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        Ok(Node::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Builds `{variant: [...]}` from a tuple variant.
+pub struct SerializeTupleVariant {
+    variant: &'static str,
+    items: Vec<Node>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.items.push(to_node(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        Ok(tagged(self.variant, Node::Array(self.items)))
+    }
+}
+
+/// Builds an object from a map or struct.
+pub struct SerializeObject {
+    map: HashMap<String, Node>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.next_key = Some(object_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+        insert_unique(&mut self.map, key, to_node(value)?)
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        Ok(Node::Object(self.map))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        insert_unique(&mut self.map, key.to_string(), to_node(value)?)
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        Ok(Node::Object(self.map))
+    }
+}
+
+/// Builds `{variant: {...}}` from a struct variant.
+pub struct SerializeStructVariant {
+    variant: &'static str,
+    map: HashMap<String, Node>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Node;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        insert_unique(&mut self.map, key.to_string(), to_node(value)?)
+    }
+
+    fn end(self) -> Result<Node, SerializeError> {
+        Ok(tagged(self.variant, Node::Object(self.map)))
+    }
+}
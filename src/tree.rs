@@ -1,6 +1,16 @@
 //! Abstract Syntax Tree representation for structured data.
+//!
+//! A [`Node`] serializes as the plain value it holds, so it can be written
+//! and read with any serde format. Object keys are written in sorted order,
+//! and whole numbers are written as integers. Reading an integer whose
+//! magnitude exceeds 2^53 fails, since a number cannot hold it exactly.
 
+use crate::path::PathSegment;
+use crate::writer::is_integral;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use std::collections::HashMap;
+use std::fmt;
 
 /// A node representing a value in structured data (JSON, YAML, TOML).
 #[derive(Debug, Clone, PartialEq)]
@@ -114,4 +124,128 @@ impl Node {
             }
         }
     }
+
+    /// Returns the value at `path` below this node, if there is one.
+    pub fn get(&self, path: &[PathSegment]) -> Option<&Node> {
+        let mut current = self;
+        for segment in path {
+            current = match (current, segment) {
+                (Node::Object(map), PathSegment::Key(key)) => map.get(key)?,
+                (Node::Array(arr), PathSegment::Index(index)) => arr.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Null => serializer.serialize_unit(),
+            Node::Bool(b) => serializer.serialize_bool(*b),
+            Node::Number(n) if is_integral(*n) => serializer.serialize_i64(*n as i64),
+            Node::Number(n) => serializer.serialize_f64(*n),
+            Node::String(s) => serializer.serialize_str(s),
+            Node::Array(arr) => serializer.collect_seq(arr),
+            Node::Object(map) => {
+                let mut entries: Vec<(&String, &Node)> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                let mut object = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    object.serialize_entry(key, value)?;
+                }
+                object.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+/// Largest integer magnitude an `f64` holds exactly, 2^53.
+const MAX_EXACT_INTEGER: u128 = 1 << 53;
+
+/// Returns the integer with the given sign and magnitude as an `f64`, or
+/// None if it cannot be held exactly.
+pub(crate) fn integer_number(negative: bool, magnitude: u128) -> Option<f64> {
+    let n = (magnitude <= MAX_EXACT_INTEGER).then_some(magnitude as f64)?;
+    Some(if negative { -n } else { n })
+}
+
+fn inexact_integer<E: de::Error>(value: impl fmt::Display) -> E {
+    E::custom(format_args!(
+        "Cannot represent {} exactly as a number",
+        value
+    ))
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a null, boolean, number, string, array or object")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        Node::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Node, E> {
+        integer_number(value < 0, u128::from(value.unsigned_abs()))
+            .map(Node::Number)
+            .ok_or_else(|| inexact_integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Node, E> {
+        integer_number(false, u128::from(value))
+            .map(Node::Number)
+            .ok_or_else(|| inexact_integer(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Node, E> {
+        Ok(Node::Number(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Node, E> {
+        Ok(Node::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut object = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Node::Object(object))
+    }
 }
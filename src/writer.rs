@@ -0,0 +1,141 @@
+//! Serialization of nodes back into JSON, YAML, and TOML documents.
+//!
+//! This is the inverse of the parser module and is used wherever sdiff has to
+//! produce a document rather than a diff report, such as writing the result of
+//! a three-way merge. Object keys are emitted in sorted order, since `Node`
+//! does not preserve the original key ordering. Integral numbers are written
+//! without a fractional part so that `3` round-trips as `3` rather than `3.0`.
+//!
+//! # Examples
+//!
+//! ```
+//! use sdiff_rs::writer::write_content;
+//! use sdiff_rs::{FormatHint, Node};
+//! use std::collections::HashMap;
+//!
+//! let mut map = HashMap::new();
+//! map.insert("replicas".to_string(), Node::Number(3.0));
+//! let node = Node::Object(map);
+//!
+//! let yaml = write_content(&node, FormatHint::Yaml).unwrap();
+//! assert_eq!(yaml.trim(), "replicas: 3");
+//! ```
+
+use crate::error::OutputError;
+use crate::parser::FormatHint;
+use crate::tree::Node;
+
+/// Serializes a node into a document of the given format.
+///
+/// `FormatHint::Auto` is written as JSON.
+pub fn write_content(node: &Node, format: FormatHint) -> Result<String, OutputError> {
+    match format {
+        FormatHint::Json | FormatHint::Auto => write_json(node),
+        FormatHint::Yaml => write_yaml(node),
+        FormatHint::Toml => write_toml(node),
+    }
+}
+
+/// Serializes a node into pretty-printed JSON.
+pub fn write_json(node: &Node) -> Result<String, OutputError> {
+    let mut output = serde_json::to_string_pretty(&node_to_json(node))
+        .map_err(|e| OutputError::JsonSerializationError { source: e })?;
+    output.push('\n');
+    Ok(output)
+}
+
+/// Serializes a node into YAML.
+pub fn write_yaml(node: &Node) -> Result<String, OutputError> {
+    serde_yaml::to_string(&node_to_yaml(node))
+        .map_err(|e| OutputError::YamlSerializationError { source: e })
+}
+
+/// Serializes a node into TOML.
+///
+/// TOML documents must be tables and cannot contain null values, so those
+/// are reported as `OutputError::UnsupportedValue`.
+pub fn write_toml(node: &Node) -> Result<String, OutputError> {
+    if !matches!(node, Node::Object(_)) {
+        return Err(OutputError::UnsupportedValue {
+            format: "TOML".to_string(),
+            value: format!("a top-level {}", node.type_name()),
+        });
+    }
+
+    let value = node_to_toml(node)?;
+    toml::to_string(&value).map_err(|e| OutputError::TomlSerializationError { source: e })
+}
+
+/// Returns true if `n` is a whole number that fits in an `i64`, so it can be
+/// written without a fractional part.
+pub(crate) fn is_integral(n: f64) -> bool {
+    n.is_finite() && n.fract() == 0.0 && n.abs() < i64::MAX as f64
+}
+
+fn sorted_entries(map: &std::collections::HashMap<String, Node>) -> Vec<(&String, &Node)> {
+    let mut entries: Vec<(&String, &Node)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn node_to_json(node: &Node) -> serde_json::Value {
+    match node {
+        Node::Null => serde_json::Value::Null,
+        Node::Bool(b) => serde_json::Value::Bool(*b),
+        Node::Number(n) if is_integral(*n) => serde_json::Value::from(*n as i64),
+        Node::Number(n) => serde_json::Number::from_f64(*n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Node::String(s) => serde_json::Value::String(s.clone()),
+        Node::Array(arr) => serde_json::Value::Array(arr.iter().map(node_to_json).collect()),
+        Node::Object(map) => serde_json::Value::Object(
+            sorted_entries(map)
+                .into_iter()
+                .map(|(k, v)| (k.clone(), node_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn node_to_yaml(node: &Node) -> serde_yaml::Value {
+    match node {
+        Node::Null => serde_yaml::Value::Null,
+        Node::Bool(b) => serde_yaml::Value::Bool(*b),
+        Node::Number(n) if is_integral(*n) => serde_yaml::Value::Number((*n as i64).into()),
+        Node::Number(n) => serde_yaml::Value::Number((*n).into()),
+        Node::String(s) => serde_yaml::Value::String(s.clone()),
+        Node::Array(arr) => serde_yaml::Value::Sequence(arr.iter().map(node_to_yaml).collect()),
+        Node::Object(map) => serde_yaml::Value::Mapping(
+            sorted_entries(map)
+                .into_iter()
+                .map(|(k, v)| (serde_yaml::Value::String(k.clone()), node_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn node_to_toml(node: &Node) -> Result<toml::Value, OutputError> {
+    let value = match node {
+        Node::Null => {
+            return Err(OutputError::UnsupportedValue {
+                format: "TOML".to_string(),
+                value: "null".to_string(),
+            })
+        }
+        Node::Bool(b) => toml::Value::Boolean(*b),
+        Node::Number(n) if is_integral(*n) => toml::Value::Integer(*n as i64),
+        Node::Number(n) => toml::Value::Float(*n),
+        Node::String(s) => toml::Value::String(s.clone()),
+        Node::Array(arr) => {
+            toml::Value::Array(arr.iter().map(node_to_toml).collect::<Result<_, _>>()?)
+        }
+        Node::Object(map) => {
+            let mut table = toml::value::Table::new();
+            for (k, v) in sorted_entries(map) {
+                table.insert(k.clone(), node_to_toml(v)?);
+            }
+            toml::Value::Table(table)
+        }
+    };
+    Ok(value)
+}
@@ -0,0 +1,374 @@
+use sdiff_rs::config::{
+    find_ignore_files, find_project_config, parse_ignore_file, ConfigFile, ConfigFiles, Settings,
+    CONFIG_FILE_NAME, IGNORE_FILE_NAME,
+};
+use sdiff_rs::filter::FilterAction;
+use sdiff_rs::{ArrayDiffStrategy, ChangeType, OutputFormat, Path, SdiffError};
+
+#[test]
+fn test_settings_default() {
+    let settings = Settings::default();
+    let diff_config = settings.diff_config();
+    assert!(!diff_config.ignore_whitespace);
+    assert_eq!(
+        diff_config.array_diff_strategy,
+        ArrayDiffStrategy::Positional
+    );
+    assert!(!settings.filter_config().unwrap().has_filters());
+    assert_eq!(settings.output_format(), OutputFormat::Terminal);
+    assert!(settings.output_options().compact);
+}
+
+#[test]
+fn test_settings_from_toml() {
+    let settings = Settings::from_toml(
+        r#"
+        format = "plain"
+        array-strategy = "lcs"
+        ignore-whitespace = true
+        max-value-length = 20
+        ignore = ["metadata.**", "**.timestamp"]
+        only = ["spec.**"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(settings.output_format(), OutputFormat::Plain);
+    assert_eq!(
+        settings.diff_config().array_diff_strategy,
+        ArrayDiffStrategy::Lcs
+    );
+    assert!(settings.diff_config().ignore_whitespace);
+    assert_eq!(settings.output_options().max_value_length, 20);
+
+    let filter = settings.filter_config().unwrap();
+    assert_eq!(filter.patterns(FilterAction::Ignore).count(), 2);
+    assert_eq!(filter.patterns(FilterAction::Only).count(), 1);
+    assert_eq!(filter.rules[0].action, FilterAction::Only);
+}
+
+#[test]
+fn test_settings_from_toml_rejects_unknown_keys() {
+    let err = Settings::from_toml("colour = true").unwrap_err();
+    assert!(matches!(err, SdiffError::ConfigError { .. }));
+
+    assert!(Settings::from_toml("array-strategy = \"fancy\"").is_err());
+}
+
+#[test]
+fn test_settings_set_key_spellings() {
+    let mut settings = Settings::default();
+    settings.set("arrayStrategy", "LCS").unwrap();
+    settings.set("ignore_whitespace", "yes").unwrap();
+    settings.set("max-value-length", "10").unwrap();
+    settings.set("IGNORE", "a.b").unwrap();
+    settings.set("ignore", "c.d").unwrap();
+
+    assert_eq!(settings.array_strategy, Some(ArrayDiffStrategy::Lcs));
+    assert_eq!(settings.ignore_whitespace, Some(true));
+    assert_eq!(settings.max_value_length, Some(10));
+    assert_eq!(settings.ignore, vec!["a.b".to_string(), "c.d".to_string()]);
+}
+
+#[test]
+fn test_case_insensitive_paths_setting() {
+    let mut settings = Settings::from_toml("ignore = [\"Status.**\"]\n").unwrap();
+    let status = Path::root().key("status").key("ready");
+    assert!(settings.filter_config().unwrap().should_include(&status));
+
+    settings.set("caseInsensitivePaths", "true").unwrap();
+    assert_eq!(settings.case_insensitive_paths, Some(true));
+    assert!(!settings.filter_config().unwrap().should_include(&status));
+}
+
+#[test]
+fn test_change_type_and_value_settings() {
+    let settings =
+        Settings::from_toml("only-type = [\"removed\"]\nold-value = [\"^v1\"]\n").unwrap();
+    let filter = settings.filter_config().unwrap();
+    assert_eq!(filter.change_types, vec![ChangeType::Removed]);
+    assert_eq!(filter.old_value_predicates.len(), 1);
+    assert!(settings.validate_filters().is_ok());
+
+    let mut settings = Settings::default();
+    settings.set("onlyType", "deleted").unwrap();
+    let err = settings.validate_filters().unwrap_err();
+    assert!(err.to_string().contains("Unknown change type 'deleted'"));
+    assert!(settings.filter_config().is_err());
+
+    let mut settings = Settings::default();
+    settings.set("oldValue", "(").unwrap();
+    assert!(settings.filter_config().is_err());
+}
+
+#[test]
+fn test_settings_set_invalid_values() {
+    let mut settings = Settings::default();
+    assert!(settings.set("compact", "maybe").is_err());
+    assert!(settings.set("max-value-length", "-1").is_err());
+    assert!(settings.set("format", "xml").is_err());
+
+    let err = settings.set("colour", "true").unwrap_err();
+    assert!(err.to_string().contains("Unknown option 'colour'"));
+}
+
+#[test]
+fn test_settings_merge_overrides() {
+    let mut base = Settings::from_toml(
+        r#"
+        format = "json"
+        compact = false
+        ignore = ["a"]
+        "#,
+    )
+    .unwrap();
+    let overrides = Settings::from_toml(
+        r#"
+        format = "plain"
+        ignore = ["b"]
+        "#,
+    )
+    .unwrap();
+
+    base.merge(overrides);
+
+    assert_eq!(base.format, Some(OutputFormat::Plain));
+    assert_eq!(base.compact, Some(false));
+    assert_eq!(base.ignore, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn test_settings_from_env() {
+    std::env::set_var("SDIFF_ARRAY_STRATEGY", "lcs");
+    std::env::set_var("SDIFF_ONLY", "spec.**, metadata.name");
+    let settings = Settings::from_env().unwrap();
+    std::env::remove_var("SDIFF_ARRAY_STRATEGY");
+    std::env::remove_var("SDIFF_ONLY");
+
+    assert_eq!(settings.array_strategy, Some(ArrayDiffStrategy::Lcs));
+    assert_eq!(
+        settings.only,
+        vec!["spec.**".to_string(), "metadata.name".to_string()]
+    );
+}
+
+const PROJECT_CONFIG: &str = r#"
+array-strategy = "lcs"
+ignore = ["status.**"]
+
+[profiles.k8s]
+ignore = ["metadata.managedFields"]
+null-as-missing = true
+
+[[files]]
+pattern = "*.toml"
+format = "plain"
+"#;
+
+#[test]
+fn test_config_file_sections() {
+    let file = ConfigFile::from_toml(PROJECT_CONFIG).unwrap();
+    assert_eq!(file.settings.array_strategy, Some(ArrayDiffStrategy::Lcs));
+    assert_eq!(file.profiles["k8s"].null_as_missing, Some(true));
+    assert_eq!(file.files.len(), 1);
+    assert_eq!(file.files[0].pattern, "*.toml");
+    assert_eq!(file.files[0].settings.format, Some(OutputFormat::Plain));
+}
+
+#[test]
+fn test_config_file_rejects_invalid_sections() {
+    assert!(ConfigFile::from_toml("[profiles.k8s]\nunknown = 1\n").is_err());
+    assert!(ConfigFile::from_toml("[[files]]\nformat = \"plain\"\n").is_err());
+}
+
+#[test]
+fn test_config_files_layering() {
+    let user =
+        ConfigFile::from_toml("format = \"json\"\narray-strategy = \"positional\"\n").unwrap();
+    let project = ConfigFile::from_toml(PROJECT_CONFIG).unwrap();
+    let files = ConfigFiles {
+        files: vec![("user".into(), user), ("project".into(), project)],
+    };
+
+    let settings = files.settings(None, &["a.json", "b.json"]).unwrap();
+    assert_eq!(settings.format, Some(OutputFormat::Json));
+    assert_eq!(settings.array_strategy, Some(ArrayDiffStrategy::Lcs));
+    assert_eq!(settings.ignore, vec!["status.**"]);
+    assert_eq!(settings.null_as_missing, None);
+
+    let settings = files.settings(Some("k8s"), &["./a.toml"]).unwrap();
+    assert_eq!(settings.format, Some(OutputFormat::Plain));
+    assert_eq!(settings.null_as_missing, Some(true));
+    assert_eq!(settings.ignore, vec!["status.**", "metadata.managedFields"]);
+}
+
+#[test]
+fn test_config_files_unknown_profile() {
+    let files = ConfigFiles::default();
+    let err = files.settings(Some("missing"), &[]).unwrap_err();
+    assert!(err.to_string().contains("Unknown profile 'missing'"));
+}
+
+#[test]
+fn test_find_project_config_searches_parents() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("a").join("b");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(dir.path().join(CONFIG_FILE_NAME), "quiet = true\n").unwrap();
+
+    assert_eq!(
+        find_project_config(&nested),
+        Some(dir.path().join(CONFIG_FILE_NAME))
+    );
+}
+
+#[test]
+fn test_parse_ignore_file() {
+    let content = "# Generated fields\n\nstatus.**\n  metadata.**  \n!metadata.labels.**\n";
+    assert_eq!(
+        parse_ignore_file(content),
+        vec!["status.**", "metadata.**", "!metadata.labels.**"]
+    );
+}
+
+#[test]
+fn test_find_ignore_files_at_root_of_compared_repository() {
+    let repo = tempfile::tempdir().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(repo.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let nested = repo.path().join("a/b");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(repo.path().join(IGNORE_FILE_NAME), "a\n").unwrap();
+
+    // The repository is found from the compared file, not the current directory
+    let file = nested.join("config.json");
+    let files = find_ignore_files(&[file.to_str().unwrap()]);
+    let root = repo.path().canonicalize().unwrap().join(IGNORE_FILE_NAME);
+    assert!(
+        files.iter().any(|f| f.canonicalize().unwrap() == root),
+        "{:?}",
+        files
+    );
+}
+
+#[test]
+fn test_config_file_ignore_files_are_relative_to_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(CONFIG_FILE_NAME);
+    std::fs::write(
+        &path,
+        "ignore-file = [\"ignores/base\"]\n\n[profiles.k8s]\nignore-file = [\"/etc/k8s-ignore\"]\n\n[[files]]\npattern = \"*.yaml\"\nignore-file = [\"yaml-ignore\"]\n",
+    )
+    .unwrap();
+
+    let file = ConfigFile::from_file(&path).unwrap();
+    let resolved = |p: &std::path::Path| p.to_string_lossy().into_owned();
+    assert_eq!(
+        file.settings.ignore_file,
+        vec![resolved(&dir.path().join("ignores/base"))]
+    );
+    assert_eq!(file.profiles["k8s"].ignore_file, vec!["/etc/k8s-ignore"]);
+    assert_eq!(
+        file.files[0].settings.ignore_file,
+        vec![resolved(&dir.path().join("yaml-ignore"))]
+    );
+}
+
+#[test]
+fn test_find_ignore_files_next_to_compared_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(dir.path().join(IGNORE_FILE_NAME), "a\n").unwrap();
+    std::fs::write(nested.join(IGNORE_FILE_NAME), "b\n").unwrap();
+
+    let old = dir.path().join("old.json");
+    let new = nested.join("new.json");
+    let files = find_ignore_files(&[old.to_str().unwrap(), new.to_str().unwrap()]);
+    let expected = vec![
+        dir.path().join(IGNORE_FILE_NAME),
+        nested.join(IGNORE_FILE_NAME),
+    ];
+    assert!(files.ends_with(&expected), "{:?}", files);
+
+    // A directory is searched itself, and each file is only read once
+    let files = find_ignore_files(&[nested.to_str().unwrap(), new.to_str().unwrap()]);
+    assert!(
+        files.ends_with(&[nested.join(IGNORE_FILE_NAME)]),
+        "{:?}",
+        files
+    );
+    assert_eq!(
+        files
+            .iter()
+            .filter(|f| **f == nested.join(IGNORE_FILE_NAME))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_load_ignore_files_precede_configured_patterns() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("custom-ignore");
+    std::fs::write(&file, "metadata.**\n!metadata.labels.**\n").unwrap();
+
+    let mut settings = Settings::default();
+    settings.set("ignore", "metadata.labels.tmp").unwrap();
+    settings.set("ignore-file", file.to_str().unwrap()).unwrap();
+    settings.load_ignore_files(&[]).unwrap();
+
+    let n = settings.ignore.len();
+    assert_eq!(
+        settings.ignore[n - 3..],
+        ["metadata.**", "!metadata.labels.**", "metadata.labels.tmp"]
+    );
+
+    let filter = settings.filter_config().unwrap();
+    let path = |key: &str| Path::root().key("metadata").key("labels").key(key);
+    assert!(filter.should_include(&path("app")));
+    assert!(!filter.should_include(&path("tmp")));
+    assert!(!filter.should_include(&Path::root().key("metadata").key("uid")));
+
+    settings.set("ignore-file", "does-not-exist").unwrap();
+    assert!(settings.load_ignore_files(&[]).is_err());
+}
+
+#[test]
+fn test_resolved_settings_round_trip() {
+    let settings = Settings::from_toml("array-strategy = \"lcs\"\n")
+        .unwrap()
+        .resolved();
+    assert_eq!(settings.max_value_length, Some(80));
+    assert_eq!(settings.format, Some(OutputFormat::Terminal));
+
+    let toml = settings.to_toml().unwrap();
+    assert!(toml.contains("array-strategy = \"lcs\""));
+    assert_eq!(Settings::from_toml(&toml).unwrap(), settings);
+}
+
+#[test]
+fn test_path_rules_from_toml() {
+    let settings = Settings::from_toml(
+        r#"
+[[rules]]
+path = "spec.ports"
+array-key = "port"
+
+[[rules]]
+path = "resources.**"
+numeric-tolerance = 0.1
+"#,
+    )
+    .unwrap();
+
+    let config = settings.diff_config();
+    assert_eq!(config.rules.len(), 2);
+    let rules = config.rules_for(&Path::root().key("spec").key("ports"));
+    assert_eq!(rules.array_key.as_deref(), Some("port"));
+    assert!(Settings::from_toml("[[rules]]\npath = \"a\"\nunknown = 1\n").is_err());
+}
@@ -1,4 +1,8 @@
-use sdiff_rs::{compute_diff, ArrayDiffStrategy, ChangeType, DiffConfig, Node};
+use sdiff_rs::diff::{compute_diff_traced, RuleSet};
+use sdiff_rs::filter::{filter_diff, FilterConfig, ValuePredicate};
+use sdiff_rs::{
+    compute_diff, compute_diff_filtered, ArrayDiffStrategy, ChangeType, DiffConfig, Node, Path,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -91,7 +95,7 @@ fn test_diff_object_added_field() {
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.added, 1);
     assert_eq!(diff.changes.len(), 1);
-    assert_eq!(diff.changes[0].path, vec!["name"]);
+    assert_eq!(diff.changes[0].path.to_string(), "name");
     assert_eq!(diff.changes[0].change_type, ChangeType::Added);
 }
 
@@ -108,7 +112,7 @@ fn test_diff_object_removed_field() {
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.removed, 1);
     assert_eq!(diff.changes.len(), 1);
-    assert_eq!(diff.changes[0].path, vec!["name"]);
+    assert_eq!(diff.changes[0].path.to_string(), "name");
     assert_eq!(diff.changes[0].change_type, ChangeType::Removed);
 }
 
@@ -127,7 +131,7 @@ fn test_diff_object_modified_field() {
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.modified, 1);
     assert_eq!(diff.changes.len(), 1);
-    assert_eq!(diff.changes[0].path, vec!["age"]);
+    assert_eq!(diff.changes[0].path.to_string(), "age");
     assert_eq!(diff.changes[0].change_type, ChangeType::Modified);
 }
 
@@ -149,7 +153,7 @@ fn test_diff_nested_objects() {
 
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.modified, 1);
-    assert_eq!(diff.changes[0].path, vec!["user", "age"]);
+    assert_eq!(diff.changes[0].path.to_string(), "user.age");
 }
 
 #[test]
@@ -186,7 +190,7 @@ fn test_diff_arrays_modified_element() {
 
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.modified, 1);
-    assert_eq!(diff.changes[0].path, vec!["[1]"]);
+    assert_eq!(diff.changes[0].path.to_string(), "[1]");
 }
 
 #[test]
@@ -201,7 +205,7 @@ fn test_diff_arrays_added_element() {
 
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.added, 1);
-    assert_eq!(diff.changes[0].path, vec!["[2]"]);
+    assert_eq!(diff.changes[0].path.to_string(), "[2]");
 }
 
 #[test]
@@ -216,7 +220,7 @@ fn test_diff_arrays_removed_element() {
 
     let diff = compute_diff(&old, &new, &config);
     assert_eq!(diff.stats.removed, 1);
-    assert_eq!(diff.changes[0].path, vec!["[2]"]);
+    assert_eq!(diff.changes[0].path.to_string(), "[2]");
 }
 
 #[test]
@@ -269,14 +273,14 @@ fn test_diff_complex_structure() {
     let age_change = diff
         .changes
         .iter()
-        .find(|c| c.path == vec!["users", "[0]", "age"])
+        .find(|c| c.path.to_string() == "users[0].age")
         .unwrap();
     assert_eq!(age_change.change_type, ChangeType::Modified);
 
     let active_change = diff
         .changes
         .iter()
-        .find(|c| c.path == vec!["active"])
+        .find(|c| c.path.to_string() == "active")
         .unwrap();
     assert_eq!(active_change.change_type, ChangeType::Added);
 }
@@ -477,3 +481,411 @@ fn test_lcs_vs_positional_comparison() {
     assert_eq!(lcs_diff.stats.added, 1);
     assert_eq!(lcs_diff.stats.removed, 0);
 }
+
+#[test]
+fn test_compute_added_diff_lists_all_leaves() {
+    let new = sdiff_rs::parse_json(
+        r#"{"name": "app", "spec": {"ports": [80, 443], "labels": {}}, "tags": []}"#,
+    )
+    .unwrap();
+    let diff = sdiff_rs::diff::compute_added_diff(&new);
+
+    assert_eq!(diff.stats.added, 5);
+    assert_eq!(diff.stats.total_changes(), 5);
+    assert!(diff
+        .changes
+        .iter()
+        .all(|c| c.change_type == ChangeType::Added && c.old_value.is_none()));
+
+    let paths: Vec<Path> = diff.changes.iter().map(|c| c.path.clone()).collect();
+    assert!(paths.contains(&Path::root().key("spec").key("ports").index(1)));
+    assert!(paths.contains(&Path::root().key("spec").key("labels")));
+    assert!(paths.contains(&Path::root().key("tags")));
+}
+
+#[test]
+fn test_compute_removed_diff_scalar_root() {
+    let diff = sdiff_rs::diff::compute_removed_diff(&Node::Number(1.0));
+    assert_eq!(diff.stats.removed, 1);
+    assert!(diff.changes[0].path.is_empty());
+    assert_eq!(diff.changes[0].old_value, Some(Node::Number(1.0)));
+}
+
+fn json(content: &str) -> Node {
+    sdiff_rs::parse_json(content).unwrap()
+}
+
+#[test]
+fn test_rule_case_insensitive_applies_to_nested_paths() {
+    let config =
+        DiffConfig::default().rule("metadata.labels", RuleSet::new().case_insensitive(true));
+    let old = json(r#"{"metadata": {"labels": {"tier": "Web"}, "name": "App"}}"#);
+    let new = json(r#"{"metadata": {"labels": {"tier": "WEB"}, "name": "app"}}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "metadata.name");
+}
+
+#[test]
+fn test_rule_numeric_tolerance() {
+    let config = DiffConfig::default().rule("resources.**", RuleSet::new().numeric_tolerance(0.5));
+    let old = json(r#"{"resources": {"cpu": 1.0, "memory": 10}, "replicas": 1}"#);
+    let new = json(r#"{"resources": {"cpu": 1.4, "memory": 12}, "replicas": 1.4}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    let mut paths: Vec<String> = diff.changes.iter().map(|c| c.path.to_string()).collect();
+    paths.sort();
+    assert_eq!(paths, vec!["replicas", "resources.memory"]);
+}
+
+#[test]
+fn test_rule_ignore_skips_subtree() {
+    let config = DiffConfig::default().rule("status", RuleSet::new().ignore(true));
+    let old = json(r#"{"status": {"ready": 1, "old": true}, "spec": 1}"#);
+    let new = json(r#"{"status": {"ready": 2, "new": true}, "spec": 2}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "spec");
+
+    let diff = compute_diff(
+        &json(r#"{"a": 1}"#),
+        &json(r#"{"a": 1, "status": {}}"#),
+        &config,
+    );
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn test_rule_keyed_array() {
+    let config = DiffConfig::default().rule("ports", RuleSet::new().array_key("port"));
+    let old = json(r#"{"ports": [{"port": 80, "name": "http"}, {"port": 443, "name": "https"}]}"#);
+    let new = json(
+        r#"{"ports": [{"port": 8080, "name": "alt"}, {"port": 443, "name": "tls"}, {"port": 80, "name": "http"}]}"#,
+    );
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.stats.added, 1);
+    assert_eq!(diff.stats.modified, 1);
+    assert_eq!(diff.stats.removed, 0);
+
+    let modified = diff
+        .changes
+        .iter()
+        .find(|c| c.change_type == ChangeType::Modified)
+        .unwrap();
+    assert_eq!(modified.path.to_string(), "ports[1].name");
+}
+
+#[test]
+fn test_rule_keyed_array_falls_back_without_keys() {
+    let config = DiffConfig::default().rule("items", RuleSet::new().array_key("id"));
+    let old = json(r#"{"items": [{"id": 1}, {"name": "x"}]}"#);
+    let new = json(r#"{"items": [{"id": 2}, {"name": "x"}]}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "items[0].id");
+}
+
+#[test]
+fn test_rule_keyed_array_compares_keys_under_rules() {
+    let config = DiffConfig::default().rule(
+        "items",
+        RuleSet::new()
+            .array_key("name")
+            .case_insensitive(true)
+            .ignore_whitespace(true),
+    );
+    let old = json(r#"{"items": [{"name": "Web", "v": 1}, {"name": "db", "v": 1}]}"#);
+    let new = json(r#"{"items": [{"name": " DB ", "v": 1}, {"name": "web", "v": 2}]}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "items[1].v");
+}
+
+#[test]
+fn test_rule_keyed_array_requires_scalar_keys() {
+    let config = DiffConfig::default().rule("items", RuleSet::new().array_key("id"));
+    let old = json(r#"{"items": [{"id": {"a": 1}, "v": 1}, {"id": {"a": 2}, "v": 2}]}"#);
+    let new = json(r#"{"items": [{"id": {"a": 2}, "v": 2}, {"id": {"a": 1}, "v": 1}]}"#);
+
+    // Object keys are not used for pairing, so elements are compared by position
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.stats.modified, 4);
+}
+
+#[test]
+fn test_rule_array_strategy_per_path() {
+    let config =
+        DiffConfig::default().rule("lcs", RuleSet::new().array_strategy(ArrayDiffStrategy::Lcs));
+    let old = json(r#"{"lcs": [1, 2, 3], "positional": [1, 2, 3]}"#);
+    let new = json(r#"{"lcs": [0, 1, 2, 3], "positional": [0, 1, 2, 3]}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    let lcs: Vec<_> = diff
+        .changes
+        .iter()
+        .filter(|c| c.path[0].as_key() == Some("lcs"))
+        .collect();
+    let positional: Vec<_> = diff
+        .changes
+        .iter()
+        .filter(|c| c.path[0].as_key() == Some("positional"))
+        .collect();
+    assert_eq!(lcs.len(), 1);
+    assert_eq!(positional.len(), 4);
+}
+
+#[test]
+fn test_later_rules_override_earlier_rules() {
+    let config = DiffConfig::default()
+        .rule("**", RuleSet::new().case_insensitive(true))
+        .rule("name", RuleSet::new().case_insensitive(false));
+    let old = json(r#"{"name": "App", "tier": "Web"}"#);
+    let new = json(r#"{"name": "app", "tier": "web"}"#);
+
+    let diff = compute_diff(&old, &new, &config);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "name");
+
+    let rules = config.rules_for(&Path::root().key("tier"));
+    assert_eq!(rules.case_insensitive, Some(true));
+}
+
+#[test]
+fn test_filtered_diff_never_compares_ignored_subtrees() {
+    let old = json(r#"{"name": "app", "status": {"pods": [1, 2, 3], "ready": false}}"#);
+    let new = json(r#"{"name": "web", "status": {"pods": [4, 5, 6], "ready": true}}"#);
+    let config = DiffConfig::default();
+
+    let (_, full) = compute_diff_traced(&old, &new, &config, &FilterConfig::new());
+    let filter = FilterConfig::new().ignore("status.**");
+    let (filtered, traversal) = compute_diff_traced(&old, &new, &config, &filter);
+
+    assert_eq!(filtered.changes.len(), 1);
+    assert_eq!(filtered.changes[0].path.to_string(), "name");
+    // The root and "name" are the only pairs compared
+    assert_eq!(traversal.compared, 2);
+    assert!(full.compared > traversal.compared);
+}
+
+#[test]
+fn test_filtered_diff_matches_post_filtering() {
+    let old = json(r#"{"spec": {"replicas": 1, "image": "a"}, "meta": {"x": 1}}"#);
+    let new = json(r#"{"spec": {"replicas": 2, "image": "b"}, "meta": {"x": 2}}"#);
+    let config = DiffConfig::default();
+
+    for filter in [
+        FilterConfig::new().ignore("meta.**"),
+        FilterConfig::new().only("spec.replicas"),
+        FilterConfig::new().only("spec.*").ignore("spec.image"),
+    ] {
+        let mut expected: Vec<_> = filter_diff(&compute_diff(&old, &new, &config), &filter)
+            .changes
+            .into_iter()
+            .map(|c| c.path)
+            .collect();
+        let mut actual: Vec<_> = compute_diff_filtered(&old, &new, &config, &filter)
+            .changes
+            .into_iter()
+            .map(|c| c.path)
+            .collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn test_filtered_diff_reports_leaves_of_added_containers() {
+    let old = json(r#"{"name": "app"}"#);
+    let new = json(r#"{"name": "app", "spec": {"replicas": 3, "image": "web"}}"#);
+
+    let filter = FilterConfig::new().only("spec.replicas");
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "spec.replicas");
+    assert_eq!(diff.changes[0].change_type, ChangeType::Added);
+    assert_eq!(diff.changes[0].new_value, Some(Node::Number(3.0)));
+}
+
+#[test]
+fn test_filtered_diff_does_not_report_leaves_of_ignored_containers() {
+    let old = json(r#"{"name": "app"}"#);
+    let new = json(r#"{"name": "web", "tags": ["a", "b"]}"#);
+
+    let filter = FilterConfig::new().ignore("tags");
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "name");
+}
+
+#[test]
+fn test_filtered_diff_evaluates_predicates() {
+    let old = json(
+        r#"{"containers": [{"name": "web", "image": "nginx:1"}, {"name": "db", "image": "pg:1"}]}"#,
+    );
+    let new = json(
+        r#"{"containers": [{"name": "web", "image": "nginx:2"}, {"name": "db", "image": "pg:2"}]}"#,
+    );
+    let config = DiffConfig::default();
+
+    let filter = FilterConfig::new().only("containers[?(@.name == 'web')].image");
+    let diff = compute_diff_filtered(&old, &new, &config, &filter);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "containers[0].image");
+
+    let filter = FilterConfig::new().ignore("$.containers[?(@.name == 'web')]..*");
+    let diff = compute_diff_filtered(&old, &new, &config, &filter);
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "containers[1].image");
+}
+
+#[test]
+fn test_filtered_diff_by_new_value_reports_matching_leaves() {
+    let old = json(r#"{"containers": [{"image": "web:1"}]}"#);
+    let new =
+        json(r#"{"containers": [{"image": "web:latest"}, {"image": "db:latest", "port": 5432}]}"#);
+
+    let filter = FilterConfig::new()
+        .only_type(ChangeType::Added)
+        .new_value(ValuePredicate::parse(":latest$").unwrap());
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].path.to_string(), "containers[1].image");
+}
+
+#[test]
+fn test_filtered_diff_counts_changes_in_pruned_subtrees() {
+    let old = json(
+        r#"{"name": "app", "status": {"pods": [1, 2], "ready": false}, "spec": {"a": 1}, "x": 1}"#,
+    );
+    let new =
+        json(r#"{"name": "web", "status": {"pods": [1, 3], "ready": true}, "spec": {"a": 1}}"#);
+    let filter = FilterConfig::new()
+        .ignore("status.**")
+        .ignore("x")
+        .ignore("spec.**")
+        .ignore("status.pods");
+
+    let (diff, traversal) = compute_diff_traced(&old, &new, &DiffConfig::default(), &filter);
+
+    assert_eq!(diff.changes.len(), 1);
+    // Both changes inside the pruned status count, the same as when only
+    // its values are ignored, and the unchanged spec not at all
+    let by_values = FilterConfig::new().ignore("status.*").ignore("status.*[*]");
+    let unpruned = compute_diff_filtered(&old, &new, &DiffConfig::default(), &by_values);
+    assert_eq!(unpruned.hidden.total(), 2);
+    assert_eq!(diff.hidden.rules[0].hidden, 2);
+    assert_eq!(diff.hidden.rules[1].hidden, 1);
+    assert_eq!(diff.hidden.rules[2].hidden, 0);
+    assert_eq!(diff.hidden.total(), 3);
+    // Counting hidden changes does not add to the compared pairs
+    assert_eq!(traversal.compared, 2);
+    // A pattern that only matches inside a pruned subtree still matched
+    assert!(diff.hidden.rules.iter().all(|rule| rule.matched));
+}
+
+#[test]
+fn test_filtered_diff_marks_matched_rules_during_walk() {
+    let old = json(r#"{"a": {"b": 1}, "gone": {"c": [1]}}"#);
+    let new = json(r#"{"a": 2, "added": {"d": true}}"#);
+    let filter = FilterConfig::new()
+        .ignore("a.b")
+        .ignore("gone.c[0]")
+        .ignore("added.d")
+        .ignore("typo");
+
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    let matched: Vec<bool> = diff.hidden.rules.iter().map(|rule| rule.matched).collect();
+    assert_eq!(matched, vec![true, true, true, false]);
+}
+
+#[test]
+fn test_filtered_diff_counts_hidden_leaves_of_added_container() {
+    let old = json(r#"{}"#);
+    let new = json(r#"{"spec": {"replicas": 3, "image": "web", "ports": [80]}}"#);
+    let filter = FilterConfig::new().only("spec.replicas");
+
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.hidden.unselected, 2);
+    assert!(diff.hidden.rules[0].matched);
+}
+
+#[test]
+fn test_diff_serde_round_trip() {
+    let old = sdiff_rs::parse_json(r#"{"a": 1, "b": null, "c": {"d": [1, 2]}}"#).unwrap();
+    let new = sdiff_rs::parse_json(r#"{"a": 2, "e": "x", "c": {"d": [1]}}"#).unwrap();
+    let filter = FilterConfig::new().ignore("c.**");
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+
+    let value = serde_json::to_value(&diff).unwrap();
+    assert_eq!(value["stats"]["total"], 3);
+    assert_eq!(value["hidden"]["patterns"][0]["hidden"], 1);
+    let removed = value["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["type"] == "removed")
+        .unwrap();
+    assert!(removed["old_value"].is_null());
+    assert!(removed.get("new_value").is_none());
+
+    let back: sdiff_rs::Diff = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(back.stats, diff.stats);
+    assert_eq!(back.hidden, diff.hidden);
+    assert_eq!(back.changes.len(), diff.changes.len());
+    assert_eq!(back.changes[0].path, diff.changes[0].path);
+    assert_eq!(serde_json::to_value(&back).unwrap(), value);
+}
+
+#[test]
+fn test_diff_config_serde_round_trip() {
+    let config = DiffConfig {
+        treat_null_as_missing: true,
+        array_diff_strategy: ArrayDiffStrategy::Lcs,
+        ..DiffConfig::default()
+    }
+    .rule("spec.containers", RuleSet::new().array_key("name"))
+    .rule("**.replicas", RuleSet::new().numeric_tolerance(1.0));
+
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "ignore_whitespace": false,
+            "null_as_missing": true,
+            "array_strategy": "lcs",
+            "rules": [
+                {"pattern": "spec.containers", "array_key": "name"},
+                {"pattern": "**.replicas", "numeric_tolerance": 1.0}
+            ]
+        })
+    );
+
+    let back: DiffConfig = serde_json::from_value(json).unwrap();
+    assert!(back.treat_null_as_missing);
+    assert_eq!(back.array_diff_strategy, ArrayDiffStrategy::Lcs);
+    assert_eq!(back.rules.len(), 2);
+    assert_eq!(back.rules[0].1, RuleSet::new().array_key("name"));
+    let path = Path::root().key("spec").key("replicas");
+    assert_eq!(back.rules_for(&path).numeric_tolerance, Some(1.0));
+
+    let partial: DiffConfig = serde_json::from_str(r#"{"ignore_whitespace": true}"#).unwrap();
+    assert!(partial.ignore_whitespace);
+    assert_eq!(partial.array_diff_strategy, ArrayDiffStrategy::Positional);
+
+    assert!(
+        serde_json::from_str::<DiffConfig>(r#"{"rules": [{"pattern": "items[?(@.a ==]"}]}"#)
+            .is_err()
+    );
+}
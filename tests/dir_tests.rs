@@ -0,0 +1,218 @@
+//! Tests for directory and revision tree diffs.
+
+use sdiff_rs::dir::{diff_sources, diff_sources_filtered, similarity, FileStatus, Source};
+use sdiff_rs::filter::FilterConfig;
+use sdiff_rs::{parse_json, DiffConfig};
+use std::fs;
+use std::path::Path;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let full_path = root.join(path);
+    fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+    fs::write(full_path, content).unwrap();
+}
+
+fn trees() -> (tempfile::TempDir, tempfile::TempDir) {
+    let old = tempfile::tempdir().unwrap();
+    let new = tempfile::tempdir().unwrap();
+
+    write(
+        old.path(),
+        "app.json",
+        r#"{"replicas": 1, "image": "web:1"}"#,
+    );
+    write(
+        new.path(),
+        "app.json",
+        r#"{"replicas": 3, "image": "web:1"}"#,
+    );
+
+    write(old.path(), "same.yaml", "a: 1\nb: 2\n");
+    write(new.path(), "same.yaml", "b: 2\na: 1\n");
+
+    write(old.path(), "removed.toml", "key = \"value\"\n");
+    write(new.path(), "nested/added.json", r#"{"enabled": true}"#);
+
+    let settings = r#"{"timeout": 30, "retries": 5, "host": "db", "port": 5432}"#;
+    let moved = r#"{"timeout": 30, "retries": 5, "host": "db", "port": 5433}"#;
+    write(old.path(), "settings.json", settings);
+    write(new.path(), "config/settings.json", moved);
+
+    write(old.path(), "README.md", "# Old\n");
+    write(new.path(), "README.md", "# New\n");
+    write(new.path(), "broken.json", "{ not json");
+
+    (old, new)
+}
+
+fn diff_trees(old: &Path, new: &Path) -> sdiff_rs::dir::DirDiff {
+    diff_sources(
+        &Source::Directory(old.to_path_buf()),
+        &Source::Directory(new.to_path_buf()),
+        &DiffConfig::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_files_are_paired_by_relative_path() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    let app = result.files.iter().find(|f| f.path == "app.json").unwrap();
+    assert_eq!(app.status, FileStatus::Modified);
+    assert_eq!(app.diff.stats.modified, 1);
+
+    assert_eq!(result.unchanged_files, 1);
+    assert!(result.files.iter().all(|f| f.path != "same.yaml"));
+}
+
+#[test]
+fn test_added_and_removed_files() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    let added = result
+        .files
+        .iter()
+        .find(|f| f.path == "nested/added.json")
+        .unwrap();
+    assert_eq!(added.status, FileStatus::Added);
+    assert_eq!(added.diff.stats.added, 1);
+
+    let removed = result
+        .files
+        .iter()
+        .find(|f| f.path == "removed.toml")
+        .unwrap();
+    assert_eq!(removed.status, FileStatus::Removed);
+    assert_eq!(removed.diff.stats.removed, 1);
+}
+
+#[test]
+fn test_renamed_file_is_detected() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    let renamed = result
+        .files
+        .iter()
+        .find(|f| f.path == "config/settings.json")
+        .unwrap();
+    assert_eq!(
+        renamed.status,
+        FileStatus::Renamed {
+            from: "settings.json".to_string()
+        }
+    );
+    assert_eq!(renamed.diff.stats.modified, 1);
+    assert!(result.files.iter().all(|f| f.path != "settings.json"));
+}
+
+#[test]
+fn test_unsupported_and_invalid_files_are_skipped() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    assert_eq!(result.unsupported, vec!["README.md".to_string()]);
+    assert_eq!(result.skipped.len(), 1);
+    assert_eq!(result.skipped[0].path, "broken.json");
+}
+
+#[test]
+fn test_files_that_are_not_utf8_are_skipped() {
+    let (old, new) = trees();
+    fs::write(new.path().join("binary.json"), [0xff, 0xfe, 0x00]).unwrap();
+
+    let result = diff_trees(old.path(), new.path());
+
+    let skipped = result
+        .skipped
+        .iter()
+        .find(|s| s.path == "binary.json")
+        .unwrap();
+    assert!(skipped.reason.contains("UTF-8"), "{}", skipped.reason);
+    assert_eq!(result.files.len(), 4);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_directories_are_not_followed() {
+    let (old, new) = trees();
+    let outside = tempfile::tempdir().unwrap();
+    write(outside.path(), "outside.json", r#"{"a": 1}"#);
+    std::os::unix::fs::symlink(".", new.path().join("nested/loop")).unwrap();
+    std::os::unix::fs::symlink(outside.path(), new.path().join("elsewhere")).unwrap();
+    std::os::unix::fs::symlink("app.json", new.path().join("linked.json")).unwrap();
+
+    let result = diff_trees(old.path(), new.path());
+
+    let paths: Vec<&str> = result.files.iter().map(|f| f.path.as_str()).collect();
+    assert!(paths.contains(&"linked.json"), "{:?}", paths);
+    assert!(
+        paths
+            .iter()
+            .all(|p| !p.contains("loop") && !p.contains("elsewhere")),
+        "{:?}",
+        paths
+    );
+}
+
+#[test]
+fn test_aggregate_stats() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    let added: usize = result.files.iter().map(|f| f.diff.stats.added).sum();
+    let modified: usize = result.files.iter().map(|f| f.diff.stats.modified).sum();
+    assert_eq!(result.stats.added, added);
+    assert_eq!(result.stats.modified, modified);
+    assert_eq!(result.stats.modified, 2);
+    assert_eq!(result.files.len(), 4);
+}
+
+#[test]
+fn test_filter_drops_modified_files_without_changes() {
+    let (old, new) = trees();
+    let result = diff_trees(old.path(), new.path());
+
+    let filtered = result.filter(&FilterConfig::new().ignore("replicas"));
+    assert!(filtered.files.iter().all(|f| f.path != "app.json"));
+    assert_eq!(filtered.unchanged_files, 2);
+    assert_eq!(filtered.stats.modified, 1);
+}
+
+#[test]
+fn test_filtered_tree_counts_hidden_changes_of_dropped_files() {
+    let (old, new) = trees();
+    let filter = FilterConfig::new().ignore("replicas").ignore("b");
+
+    let result = diff_sources_filtered(
+        &Source::Directory(old.path().to_path_buf()),
+        &Source::Directory(new.path().to_path_buf()),
+        &DiffConfig::default(),
+        &filter,
+    )
+    .unwrap();
+
+    assert!(result.files.iter().all(|f| f.path != "app.json"));
+    assert_eq!(result.hidden.rules[0].hidden, 1);
+    // "b" only exists in an identical file, which still counts as a match
+    assert!(result.hidden.rules[1].matched);
+    assert_eq!(result.hidden.total(), 1);
+
+    let filtered = diff_trees(old.path(), new.path()).filter(&filter);
+    assert_eq!(filtered.hidden.total(), 1);
+}
+
+#[test]
+fn test_similarity() {
+    let config = DiffConfig::default();
+    let a = parse_json(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#).unwrap();
+    let b = parse_json(r#"{"a": 1, "b": 2, "c": 3, "d": 5}"#).unwrap();
+    let c = parse_json(r#"{"x": 1}"#).unwrap();
+
+    assert_eq!(similarity(&a, &a, &config), 1.0);
+    assert_eq!(similarity(&a, &b, &config), 0.75);
+    assert_eq!(similarity(&a, &c, &config), 0.0);
+}
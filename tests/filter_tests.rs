@@ -1,6 +1,9 @@
 use sdiff_rs::diff::{Change, ChangeType, Diff, DiffStats};
-use sdiff_rs::filter::{filter_diff, FilterConfig, PathPattern, PatternSegment};
-use sdiff_rs::Node;
+use sdiff_rs::filter::{
+    filter_diff, filter_diff_in, glob_match, CompareOp, FilterAction, FilterConfig, HiddenBy,
+    HiddenChanges, Operand, PathPattern, PatternSegment, Predicate, ValuePredicate,
+};
+use sdiff_rs::{compute_diff_filtered, parse_json, DiffConfig, Node, Path, PathSegment};
 
 #[test]
 fn test_pattern_parse_literal() {
@@ -35,40 +38,36 @@ fn test_pattern_parse_wildcards() {
 #[test]
 fn test_pattern_matches_literal() {
     let pattern = PathPattern::parse("foo.bar");
-    assert!(pattern.matches(&["foo".to_string(), "bar".to_string()]));
-    assert!(!pattern.matches(&["foo".to_string(), "baz".to_string()]));
-    assert!(!pattern.matches(&["foo".to_string()]));
-    assert!(!pattern.matches(&["foo".to_string(), "bar".to_string(), "baz".to_string()]));
+    assert!(pattern.matches(&Path::root().key("foo").key("bar")));
+    assert!(!pattern.matches(&Path::root().key("foo").key("baz")));
+    assert!(!pattern.matches(&Path::root().key("foo")));
+    assert!(!pattern.matches(&Path::root().key("foo").key("bar").key("baz")));
 }
 
 #[test]
 fn test_pattern_matches_single_wildcard() {
     let pattern = PathPattern::parse("foo.*.baz");
-    assert!(pattern.matches(&["foo".to_string(), "bar".to_string(), "baz".to_string()]));
-    assert!(pattern.matches(&["foo".to_string(), "anything".to_string(), "baz".to_string()]));
-    assert!(!pattern.matches(&["foo".to_string(), "baz".to_string()]));
+    assert!(pattern.matches(&Path::root().key("foo").key("bar").key("baz")));
+    assert!(pattern.matches(&Path::root().key("foo").key("anything").key("baz")));
+    assert!(!pattern.matches(&Path::root().key("foo").key("baz")));
 }
 
 #[test]
 fn test_pattern_matches_double_wildcard() {
     let pattern = PathPattern::parse("**.version");
-    assert!(pattern.matches(&["version".to_string()]));
-    assert!(pattern.matches(&["package".to_string(), "version".to_string()]));
-    assert!(pattern.matches(&[
-        "deep".to_string(),
-        "nested".to_string(),
-        "version".to_string()
-    ]));
-    assert!(!pattern.matches(&["package".to_string(), "name".to_string()]));
+    assert!(pattern.matches(&Path::root().key("version")));
+    assert!(pattern.matches(&Path::root().key("package").key("version")));
+    assert!(pattern.matches(&Path::root().key("deep").key("nested").key("version")));
+    assert!(!pattern.matches(&Path::root().key("package").key("name")));
 }
 
 #[test]
 fn test_pattern_matches_double_wildcard_prefix() {
     let pattern = PathPattern::parse("metadata.**");
-    assert!(pattern.matches(&["metadata".to_string()]));
-    assert!(pattern.matches(&["metadata".to_string(), "foo".to_string()]));
-    assert!(pattern.matches(&["metadata".to_string(), "foo".to_string(), "bar".to_string()]));
-    assert!(!pattern.matches(&["other".to_string(), "metadata".to_string()]));
+    assert!(pattern.matches(&Path::root().key("metadata")));
+    assert!(pattern.matches(&Path::root().key("metadata").key("foo")));
+    assert!(pattern.matches(&Path::root().key("metadata").key("foo").key("bar")));
+    assert!(!pattern.matches(&Path::root().key("other").key("metadata")));
 }
 
 #[test]
@@ -77,48 +76,48 @@ fn test_filter_config_ignore() {
         .ignore("metadata.timestamp")
         .ignore("**.internal");
 
-    assert!(!config.should_include(&["metadata".to_string(), "timestamp".to_string()]));
-    assert!(!config.should_include(&["foo".to_string(), "internal".to_string()]));
-    assert!(config.should_include(&["metadata".to_string(), "author".to_string()]));
-    assert!(config.should_include(&["data".to_string(), "value".to_string()]));
+    assert!(!config.should_include(&Path::root().key("metadata").key("timestamp")));
+    assert!(!config.should_include(&Path::root().key("foo").key("internal")));
+    assert!(config.should_include(&Path::root().key("metadata").key("author")));
+    assert!(config.should_include(&Path::root().key("data").key("value")));
 }
 
 #[test]
 fn test_filter_config_only() {
     let config = FilterConfig::new().only("spec.**").only("metadata.name");
 
-    assert!(config.should_include(&["spec".to_string(), "replicas".to_string()]));
-    assert!(config.should_include(&["metadata".to_string(), "name".to_string()]));
-    assert!(!config.should_include(&["metadata".to_string(), "timestamp".to_string()]));
-    assert!(!config.should_include(&["status".to_string()]));
+    assert!(config.should_include(&Path::root().key("spec").key("replicas")));
+    assert!(config.should_include(&Path::root().key("metadata").key("name")));
+    assert!(!config.should_include(&Path::root().key("metadata").key("timestamp")));
+    assert!(!config.should_include(&Path::root().key("status")));
 }
 
 #[test]
 fn test_filter_config_combined() {
     let config = FilterConfig::new().only("spec.**").ignore("spec.internal");
 
-    assert!(config.should_include(&["spec".to_string(), "replicas".to_string()]));
-    assert!(!config.should_include(&["spec".to_string(), "internal".to_string()]));
-    assert!(!config.should_include(&["metadata".to_string()]));
+    assert!(config.should_include(&Path::root().key("spec").key("replicas")));
+    assert!(!config.should_include(&Path::root().key("spec").key("internal")));
+    assert!(!config.should_include(&Path::root().key("metadata")));
 }
 
 #[test]
 fn test_filter_diff() {
     let changes = vec![
         Change {
-            path: vec!["metadata".to_string(), "timestamp".to_string()],
+            path: Path::root().key("metadata").key("timestamp"),
             change_type: ChangeType::Modified,
             old_value: Some(Node::String("old".to_string())),
             new_value: Some(Node::String("new".to_string())),
         },
         Change {
-            path: vec!["spec".to_string(), "replicas".to_string()],
+            path: Path::root().key("spec").key("replicas"),
             change_type: ChangeType::Modified,
             old_value: Some(Node::Number(1.0)),
             new_value: Some(Node::Number(2.0)),
         },
         Change {
-            path: vec!["data".to_string(), "value".to_string()],
+            path: Path::root().key("data").key("value"),
             change_type: ChangeType::Added,
             old_value: None,
             new_value: Some(Node::String("added".to_string())),
@@ -133,6 +132,7 @@ fn test_filter_diff() {
             modified: 2,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
 
     let config = FilterConfig::new().ignore("metadata.**");
@@ -142,3 +142,761 @@ fn test_filter_diff() {
     assert_eq!(filtered.stats.added, 1);
     assert_eq!(filtered.stats.modified, 1);
 }
+
+#[test]
+fn test_pattern_matches_prefix() {
+    let pattern = PathPattern::parse("database.host");
+    assert!(pattern.matches_prefix(&[]));
+    assert!(pattern.matches_prefix(&Path::root().key("database")));
+    assert!(pattern.matches_prefix(&Path::root().key("database").key("host")));
+    assert!(!pattern.matches_prefix(&Path::root().key("database").key("port")));
+    assert!(!pattern.matches_prefix(&Path::root().key("database").key("host").key("name")));
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("config/*.yaml", "config/app.yaml"));
+    assert!(!glob_match("config/*.yaml", "config/prod/app.yaml"));
+    assert!(glob_match("config/**/*.yaml", "config/prod/app.yaml"));
+    assert!(glob_match("config/**/*.yaml", "config/app.yaml"));
+    assert!(glob_match("*.json", "deep/nested/file.json"));
+    assert!(glob_match("app-?.toml", "app-1.toml"));
+    assert!(!glob_match("app-?.toml", "app-10.toml"));
+}
+
+#[test]
+fn test_pattern_matches_subtree() {
+    let path = |p: &str| p.split('.').fold(Path::root(), |path, key| path.key(key));
+
+    let pattern = PathPattern::parse("metadata.**");
+    assert!(pattern.matches_subtree(&path("metadata")));
+    assert!(pattern.matches_subtree(&path("metadata.labels")));
+    assert!(!pattern.matches_subtree(&[]));
+
+    assert!(!PathPattern::parse("metadata").matches_subtree(&path("metadata")));
+    assert!(!PathPattern::parse("**.version").matches_subtree(&path("package")));
+    assert!(PathPattern::parse("**").matches_subtree(&[]));
+}
+
+#[test]
+fn test_filter_config_excludes_subtree() {
+    let path = |p: &str| p.split('.').fold(Path::root(), |path, key| path.key(key));
+
+    let config = FilterConfig::new().ignore("status.**");
+    assert!(config.excludes_subtree(&path("status")));
+    assert!(!config.excludes_subtree(&path("spec")));
+
+    let config = FilterConfig::new().only("spec.replicas");
+    assert!(!config.excludes_subtree(&[]));
+    assert!(!config.excludes_subtree(&path("spec")));
+    assert!(config.excludes_subtree(&path("status")));
+    assert!(config.excludes_subtree(&path("spec.template")));
+}
+
+#[test]
+fn test_pattern_parse_quoted_and_indexed_segments() {
+    let pattern = PathPattern::parse(r#"metadata.labels."app.kubernetes.io/name""#);
+    assert_eq!(
+        pattern.segments[2],
+        PatternSegment::Literal("app.kubernetes.io/name".to_string())
+    );
+
+    let pattern = PathPattern::parse("items[0].name");
+    assert_eq!(
+        pattern.segments,
+        vec![
+            PatternSegment::Literal("items".to_string()),
+            PatternSegment::Index(0),
+            PatternSegment::Literal("name".to_string()),
+        ]
+    );
+
+    let pattern = PathPattern::parse("items[*]");
+    assert_eq!(pattern.segments[1], PatternSegment::AnyIndex);
+    assert!(pattern.matches(&Path::root().key("items").index(3)));
+    assert!(!pattern.matches(&Path::root().key("items").key("name")));
+
+    // The older dotted form of indices is still accepted
+    assert!(PathPattern::parse("items.[0]").matches(&Path::root().key("items").index(0)));
+    // A quoted key that looks like an index is still a key
+    let pattern = PathPattern::parse(r#""[0]""#);
+    assert!(pattern.matches(&Path::root().key("[0]")));
+    assert!(!pattern.matches(&Path::root().index(0)));
+    assert_eq!(
+        PathPattern::parse(r#""a\"b\\c""#).segments,
+        vec![PatternSegment::Literal(r#"a"b\c"#.to_string())]
+    );
+}
+
+#[test]
+fn test_printed_paths_round_trip_as_patterns() {
+    let paths = vec![
+        Path::root()
+            .key("spec")
+            .key("containers")
+            .index(0)
+            .key("image"),
+        Path::root().key("labels").key("app.kubernetes.io/name"),
+        Path::root().index(1).index(2),
+        Path::root().key("*").key("**").key(""),
+        Path::root()
+            .key(r#""quoted""#)
+            .key(r#"back\slash"#)
+            .key("a[b]"),
+        Path::root().key("example.com").key("x y").key("[3]"),
+        Path::root().key("$").key("$schema"),
+        Path::root().key("!important").key("#tag"),
+    ];
+
+    for path in paths {
+        let printed = path.to_string();
+        let pattern = PathPattern::parse(&printed);
+        assert!(
+            pattern.matches(&path),
+            "{} does not match {:?}",
+            printed,
+            path
+        );
+        assert_eq!(pattern.segments.len(), path.len(), "{}", printed);
+    }
+}
+
+fn containers() -> Node {
+    parse_json(
+        r#"{"spec": {"containers": [
+            {"name": "web", "image": "nginx", "replicas": 3},
+            {"name": "sidecar", "image": "envoy", "replicas": 1},
+            {"name": "db", "image": "postgres"}
+        ]}}"#,
+    )
+    .unwrap()
+}
+
+fn container(index: usize, key: &str) -> Path {
+    Path::root()
+        .key("spec")
+        .key("containers")
+        .index(index)
+        .key(key)
+}
+
+#[test]
+fn test_pattern_parse_filter() {
+    let pattern = PathPattern::parse("containers[?(@.name == 'web')].image");
+    assert_eq!(
+        pattern.segments[1],
+        PatternSegment::Filter(Predicate::Compare(
+            Operand::Current(vec![PathSegment::Key("name".to_string())]),
+            CompareOp::Eq,
+            Operand::Literal(Node::String("web".to_string())),
+        ))
+    );
+    assert_eq!(
+        pattern.segments[2],
+        PatternSegment::Literal("image".to_string())
+    );
+}
+
+#[test]
+fn test_pattern_parse_jsonpath_syntax() {
+    let pattern = PathPattern::parse("$.spec['containers'][0]..image");
+    assert_eq!(
+        pattern.segments,
+        vec![
+            PatternSegment::Literal("spec".to_string()),
+            PatternSegment::Literal("containers".to_string()),
+            PatternSegment::Index(0),
+            PatternSegment::DoubleWildcard,
+            PatternSegment::Literal("image".to_string()),
+        ]
+    );
+    assert!(pattern.matches(&container(0, "image")));
+
+    // Paths printed as JSONPath are accepted as patterns
+    let path = Path::root().key("a.b").key("it's").index(2);
+    assert!(PathPattern::parse(&path.to_jsonpath()).matches(&path));
+}
+
+#[test]
+fn test_pattern_filter_matches_in_document() {
+    let doc = containers();
+    let pattern = PathPattern::parse("spec.containers[?(@.name=='web')].image");
+    assert!(pattern.matches_in(&container(0, "image"), &doc));
+    assert!(!pattern.matches_in(&container(1, "image"), &doc));
+
+    // Without a document the filter cannot be evaluated
+    assert!(!pattern.matches(&container(0, "image")));
+    assert!(pattern.matches_prefix(&container(1, "image")));
+}
+
+#[test]
+fn test_pattern_filter_expressions() {
+    let doc = containers();
+    let selected = |filter: &str| -> Vec<usize> {
+        let pattern = PathPattern::parse(&format!("spec.containers[{}].image", filter));
+        (0..3)
+            .filter(|i| pattern.matches_in(&container(*i, "image"), &doc))
+            .collect()
+    };
+
+    assert_eq!(selected("?(@.replicas > 1)"), vec![0]);
+    assert_eq!(selected("?(@.replicas <= 3)"), vec![0, 1]);
+    assert_eq!(selected("?(@.replicas)"), vec![0, 1]);
+    assert_eq!(selected("?(!@.replicas)"), vec![2]);
+    assert_eq!(
+        selected("?(@.name != 'web' && @.image != \"envoy\")"),
+        vec![2]
+    );
+    assert_eq!(
+        selected("?(@.name == 'db' || (@.replicas == 1))"),
+        vec![1, 2]
+    );
+    assert_eq!(selected("?(@['name'] < 'm')"), vec![2]);
+    assert_eq!(selected("?(@.replicas == null)"), Vec::<usize>::new());
+}
+
+#[test]
+fn test_pattern_slices() {
+    let doc = containers();
+    let selected = |slice: &str| -> Vec<usize> {
+        let pattern = PathPattern::parse(&format!("spec.containers[{}].name", slice));
+        (0..3)
+            .filter(|i| pattern.matches_in(&container(*i, "name"), &doc))
+            .collect()
+    };
+
+    assert_eq!(selected("0:2"), vec![0, 1]);
+    assert_eq!(selected("1:"), vec![1, 2]);
+    assert_eq!(selected("::2"), vec![0, 2]);
+    assert_eq!(selected("-1"), vec![2]);
+    assert_eq!(selected("-2:"), vec![1, 2]);
+    assert_eq!(selected("::-1"), vec![0, 1, 2]);
+    assert_eq!(selected(":-1"), vec![0, 1]);
+
+    // Extreme bounds and steps do not overflow
+    assert_eq!(selected("::-9223372036854775808"), vec![2]);
+    assert_eq!(selected("::9223372036854775807"), vec![0]);
+    assert_eq!(selected("-9223372036854775808:"), vec![0, 1, 2]);
+    assert_eq!(selected("9223372036854775807::-1"), vec![0, 1, 2]);
+
+    // Non-negative slices do not need the document
+    assert!(PathPattern::parse("items[1:3]").matches(&Path::root().key("items").index(2)));
+    assert!(!PathPattern::parse("items[1:3]").matches(&Path::root().key("items").index(3)));
+}
+
+#[test]
+fn test_pattern_try_parse_reports_errors() {
+    assert!(PathPattern::try_parse("items[?(@.name == 'web')]").is_ok());
+    assert!(PathPattern::try_parse("items[1:2]").is_ok());
+
+    for pattern in [
+        "items[?(@.name == )]",
+        "items[?(@.name == 'web']",
+        "items[1:2:3:4]",
+        "items./x-(/",
+        "items./^x-/g",
+        "items./^x-",
+    ] {
+        let err = PathPattern::try_parse(pattern).unwrap_err();
+        assert!(err.to_string().contains(pattern), "{}: {}", pattern, err);
+    }
+
+    // The lenient parser reads a malformed bracket as a key
+    assert!(PathPattern::parse("items[?(@ ==)]").matches(&Path::root().key("items").key("?(@ ==)")));
+}
+
+#[test]
+fn test_filter_diff_in_either_document() {
+    let old = containers();
+    let mut new = containers();
+    if let Node::Object(root) = &mut new {
+        if let Some(Node::Object(spec)) = root.get_mut("spec") {
+            spec.insert("containers".to_string(), Node::Array(Vec::new()));
+        }
+    }
+
+    let diff = Diff {
+        changes: vec![
+            Change {
+                path: container(0, "image"),
+                change_type: ChangeType::Removed,
+                old_value: Some(Node::String("nginx".to_string())),
+                new_value: None,
+            },
+            Change {
+                path: container(1, "image"),
+                change_type: ChangeType::Removed,
+                old_value: Some(Node::String("envoy".to_string())),
+                new_value: None,
+            },
+        ],
+        stats: DiffStats::default(),
+        hidden: HiddenChanges::default(),
+    };
+
+    let config = FilterConfig::new().only("spec.containers[?(@.name == 'web')].*");
+    let filtered = filter_diff_in(&diff, &config, &old, &new);
+    assert_eq!(filtered.changes.len(), 1);
+    assert_eq!(filtered.changes[0].path, container(0, "image"));
+
+    // Tree-less filtering cannot evaluate the filter
+    assert!(filter_diff(&diff, &config).is_empty());
+}
+
+#[test]
+fn test_pattern_parse_glob_segments() {
+    let pattern = PathPattern::parse("features.feature_*_enabled");
+    assert_eq!(
+        pattern.segments[1],
+        PatternSegment::Glob("feature_*_enabled".to_string())
+    );
+    assert!(pattern.matches(&Path::root().key("features").key("feature_dark_enabled")));
+    assert!(!pattern.matches(&Path::root().key("features").key("feature_dark")));
+
+    let pattern = PathPattern::parse("v?.[abc]x[0-9].items[1]");
+    assert_eq!(pattern.segments[0], PatternSegment::Glob("v?".to_string()));
+    assert_eq!(
+        pattern.segments[1],
+        PatternSegment::Glob("[abc]x[0-9]".to_string())
+    );
+    assert_eq!(pattern.segments[3], PatternSegment::Index(1));
+    assert!(pattern.matches(&Path::root().key("v1").key("bx7").key("items").index(1)));
+    assert!(!pattern.matches(&Path::root().key("v1").key("dx7").key("items").index(1)));
+    assert!(!pattern.matches(&Path::root().key("v10").key("ax7").key("items").index(1)));
+
+    // Globs only match keys
+    assert!(!PathPattern::parse("items.?").matches(&Path::root().key("items").index(1)));
+}
+
+#[test]
+fn test_pattern_negated_character_class() {
+    let pattern = PathPattern::parse("env.[!_]*");
+    assert!(pattern.matches(&Path::root().key("env").key("HOME")));
+    assert!(!pattern.matches(&Path::root().key("env").key("_internal")));
+    assert!(PathPattern::parse("[^a-c]").matches(&Path::root().key("d")));
+}
+
+#[test]
+fn test_pattern_parse_regex_segments() {
+    let pattern = PathPattern::parse("paths.*./^x-.*$/");
+    match &pattern.segments[2] {
+        PatternSegment::Regex(regex) => assert_eq!(regex.as_str(), "^x-.*$"),
+        other => panic!("expected a regex segment, got {:?}", other),
+    }
+    assert!(pattern.matches(&Path::root().key("paths").key("/pets").key("x-internal")));
+    assert!(!pattern.matches(&Path::root().key("paths").key("/pets").key("get")));
+
+    // `\/` is a slash inside the expression
+    let pattern = PathPattern::parse(r"/^a\/b$/.id");
+    assert!(pattern.matches(&Path::root().key("a/b").key("id")));
+
+    // Regexes are not anchored unless written so
+    assert!(PathPattern::parse("/tmp/").matches(&Path::root().key("my_tmp_dir")));
+}
+
+#[test]
+fn test_pattern_case_insensitive() {
+    let path = Path::root().key("Metadata").key("X-Request-ID");
+
+    assert!(!PathPattern::parse("metadata.x-request-id").matches(&path));
+    assert!(PathPattern::parse("metadata.x-request-id")
+        .case_insensitive(true)
+        .matches(&path));
+    assert!(PathPattern::parse("META*./^x-request/")
+        .case_insensitive(true)
+        .matches(&path));
+
+    // The `i` flag makes a single regex case-insensitive
+    assert!(PathPattern::parse("Metadata./^x-request/i").matches(&path));
+    assert!(!PathPattern::parse("Metadata./^x-request/").matches(&path));
+}
+
+#[test]
+fn test_filter_config_case_insensitive() {
+    let config = FilterConfig::new()
+        .ignore("STATUS.**")
+        .case_insensitive(true)
+        .only("Spec.*");
+
+    assert!(config.should_include(&Path::root().key("spec").key("replicas")));
+    assert!(!config.should_include(&Path::root().key("status").key("ready")));
+}
+
+#[test]
+fn test_glob_match_character_classes() {
+    assert!(glob_match("config-[0-9].yaml", "config-1.yaml"));
+    assert!(!glob_match("config-[0-9].yaml", "config-a.yaml"));
+    assert!(glob_match("[!.]*", "visible"));
+    assert!(glob_match(r"literal\*", "literal*"));
+    assert!(!glob_match(r"literal\*", "literally"));
+}
+
+fn change(path: &str, change_type: ChangeType, old: Option<Node>, new: Option<Node>) -> Change {
+    Change {
+        path: Path::root().key(path),
+        change_type,
+        old_value: old,
+        new_value: new,
+    }
+}
+
+fn string(s: &str) -> Option<Node> {
+    Some(Node::String(s.to_string()))
+}
+
+#[test]
+fn test_value_predicate_parse() {
+    assert!(matches!(
+        ValuePredicate::parse(">= 3").unwrap(),
+        ValuePredicate::Compare(CompareOp::Ge, n) if n == 3.0
+    ));
+    assert!(matches!(
+        ValuePredicate::parse("!=-1.5").unwrap(),
+        ValuePredicate::Compare(CompareOp::Ne, n) if n == -1.5
+    ));
+    // Anything that is not an operator and a number is a regex
+    assert!(matches!(
+        ValuePredicate::parse(">abc").unwrap(),
+        ValuePredicate::Matches(_)
+    ));
+    assert!(ValuePredicate::parse("(unclosed").is_err());
+}
+
+#[test]
+fn test_value_predicate_evaluate() {
+    let latest = ValuePredicate::parse(":latest$").unwrap();
+    assert!(latest.evaluate(&Node::String("nginx:latest".to_string())));
+    assert!(!latest.evaluate(&Node::String("nginx:1.25".to_string())));
+    assert!(!latest.evaluate(&Node::Bool(true)));
+
+    let small = ValuePredicate::parse("< 10").unwrap();
+    assert!(small.evaluate(&Node::Number(3.0)));
+    assert!(!small.evaluate(&Node::Number(10.0)));
+    assert!(!small.evaluate(&Node::String("3".to_string())));
+}
+
+#[test]
+fn test_filter_diff_by_change_type_and_value() {
+    let changes = vec![
+        change("a", ChangeType::Added, None, string("x")),
+        change("b", ChangeType::Removed, string("y"), None),
+        change(
+            "c",
+            ChangeType::Modified,
+            string("nginx:1"),
+            string("nginx:latest"),
+        ),
+        change(
+            "d",
+            ChangeType::Modified,
+            Some(Node::Number(2.0)),
+            Some(Node::Number(5.0)),
+        ),
+    ];
+    let diff = Diff {
+        stats: DiffStats::from_changes(&changes),
+        changes,
+        hidden: HiddenChanges::default(),
+    };
+    let paths = |config: &FilterConfig| -> Vec<String> {
+        filter_diff(&diff, config)
+            .changes
+            .iter()
+            .map(|c| c.path.to_string())
+            .collect()
+    };
+
+    let config = FilterConfig::new()
+        .only_type(ChangeType::Removed)
+        .only_type(ChangeType::Modified);
+    assert_eq!(paths(&config), vec!["b", "c", "d"]);
+
+    let config = FilterConfig::new().new_value(ValuePredicate::parse(":latest$").unwrap());
+    assert_eq!(paths(&config), vec!["c"]);
+
+    let config = FilterConfig::new()
+        .old_value(ValuePredicate::parse("< 3").unwrap())
+        .new_value(ValuePredicate::parse("> 3").unwrap());
+    assert_eq!(paths(&config), vec!["d"]);
+
+    // A removed value has no new value to match
+    let config = FilterConfig::new().new_value(ValuePredicate::parse(".*").unwrap());
+    assert_eq!(paths(&config), vec!["a", "c"]);
+}
+
+#[test]
+fn test_pattern_parse_negated() {
+    let pattern = PathPattern::parse("!metadata.labels");
+    assert!(pattern.negated);
+    assert_eq!(pattern.segments.len(), 2);
+    assert!(pattern.matches(&Path::root().key("metadata").key("labels")));
+
+    assert!(!PathPattern::parse(r#""!metadata""#).negated);
+}
+
+#[test]
+fn test_filter_config_ignore_negation_last_match_wins() {
+    let config = FilterConfig::new()
+        .ignore("metadata.**")
+        .ignore("!metadata.labels.**")
+        .ignore("metadata.labels.hash");
+    let path = |keys: &[&str]| keys.iter().fold(Path::root(), |path, key| path.key(key));
+
+    assert!(!config.should_include(&path(&["metadata", "uid"])));
+    assert!(config.should_include(&path(&["metadata", "labels", "app"])));
+    assert!(!config.should_include(&path(&["metadata", "labels", "hash"])));
+
+    // The negation reaches into metadata, so it cannot be pruned
+    assert!(!config.excludes_subtree(&path(&["metadata"])));
+    assert!(config.excludes_subtree(&path(&["metadata", "annotations"])));
+}
+
+#[test]
+fn test_filter_config_rule_order() {
+    let path = |keys: &[&str]| keys.iter().fold(Path::root(), |path, key| path.key(key));
+
+    // The later rule wins where both match
+    let ignore_first = FilterConfig::new().ignore("spec.replicas").only("spec.**");
+    assert!(ignore_first.should_include(&path(&["spec", "replicas"])));
+    assert!(!ignore_first.should_include(&path(&["status"])));
+
+    let only_first = FilterConfig::new().only("spec.**").ignore("spec.replicas");
+    assert!(!only_first.should_include(&path(&["spec", "replicas"])));
+    assert!(only_first.should_include(&path(&["spec", "image"])));
+    assert!(!only_first.should_include(&path(&["status"])));
+}
+
+#[test]
+fn test_filter_config_only_negation() {
+    let config = FilterConfig::new().only("spec.**").only("!spec.secret.**");
+    let path = |keys: &[&str]| keys.iter().fold(Path::root(), |path, key| path.key(key));
+
+    assert!(config.should_include(&path(&["spec", "image"])));
+    assert!(!config.should_include(&path(&["spec", "secret", "token"])));
+    assert!(!config.should_include(&path(&["status"])));
+
+    assert!(config.excludes_subtree(&path(&["spec", "secret"])));
+    assert!(config.excludes_subtree(&path(&["status"])));
+    assert!(!config.excludes_subtree(&path(&["spec"])));
+
+    // A negated only rule alone hides its matches and nothing else
+    let config = FilterConfig::new().only("!status.**");
+    assert!(config.should_include(&path(&["spec"])));
+    assert!(!config.should_include(&path(&["status", "phase"])));
+}
+
+#[test]
+fn test_filter_config_may_include_inside() {
+    let config = FilterConfig::new()
+        .ignore("metadata")
+        .ignore("!metadata.labels");
+    let metadata = Path::root().key("metadata");
+
+    assert!(!config.should_include(&metadata));
+    assert!(config.may_include_inside_in(&metadata, &Node::Null, &Node::Null));
+
+    let config = FilterConfig::new()
+        .ignore("!metadata.labels")
+        .ignore("metadata");
+    assert!(!config.may_include_inside_in(&metadata, &Node::Null, &Node::Null));
+}
+
+#[test]
+fn test_filter_diff_negated_ignore_inside_added_container() {
+    let old = parse_json(r#"{"a": 1}"#).unwrap();
+    let new =
+        parse_json(r#"{"a": 1, "metadata": {"uid": "x", "labels": {"app": "web"}}}"#).unwrap();
+    let filter = FilterConfig::new()
+        .ignore("metadata.**")
+        .ignore("!metadata.labels.**");
+
+    let diff = compute_diff_filtered(&old, &new, &DiffConfig::default(), &filter);
+    let paths: Vec<String> = diff.changes.iter().map(|c| c.path.to_string()).collect();
+    assert_eq!(paths, vec!["metadata.labels.app"]);
+}
+
+#[test]
+fn test_filter_config_hidden_by() {
+    let config = FilterConfig::new()
+        .only("spec.**")
+        .ignore("spec.replicas")
+        .only_type(ChangeType::Modified);
+    let change = |path: Path, change_type: ChangeType| Change {
+        path,
+        change_type,
+        old_value: Some(Node::Number(1.0)),
+        new_value: Some(Node::Number(2.0)),
+    };
+
+    let replicas = change(
+        Path::root().key("spec").key("replicas"),
+        ChangeType::Modified,
+    );
+    assert_eq!(config.hidden_by(&replicas), Some(HiddenBy::Rule(1)));
+
+    let status = change(Path::root().key("status"), ChangeType::Modified);
+    assert_eq!(config.hidden_by(&status), Some(HiddenBy::Unselected));
+
+    let image = change(Path::root().key("spec").key("image"), ChangeType::Added);
+    assert_eq!(config.hidden_by(&image), Some(HiddenBy::Change));
+
+    let image = change(Path::root().key("spec").key("image"), ChangeType::Modified);
+    assert_eq!(config.hidden_by(&image), None);
+}
+
+#[test]
+fn test_filter_diff_counts_hidden_changes() {
+    let old = parse_json(r#"{"name": "a", "meta": {"x": 1, "y": 1}, "tags": [1]}"#).unwrap();
+    let new = parse_json(r#"{"name": "b", "meta": {"x": 2, "y": 2}, "tags": [2]}"#).unwrap();
+    let diff = sdiff_rs::compute_diff(&old, &new, &DiffConfig::default());
+    let config = FilterConfig::new()
+        .ignore("meta.*")
+        .ignore("tags[*]")
+        .ignore("colour");
+
+    let filtered = filter_diff_in(&diff, &config, &old, &new);
+    assert_eq!(filtered.changes.len(), 1);
+    assert_eq!(filtered.hidden.total(), 3);
+
+    let counts: Vec<(&str, usize, bool)> = filtered
+        .hidden
+        .rules
+        .iter()
+        .map(|rule| (rule.pattern.as_str(), rule.hidden, rule.matched))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("meta.*", 2, true),
+            ("tags[*]", 1, true),
+            ("colour", 0, false)
+        ]
+    );
+    assert_eq!(filtered.hidden.rules[0].action, FilterAction::Ignore);
+
+    let unmatched: Vec<&str> = filtered
+        .hidden
+        .unmatched()
+        .map(|rule| rule.pattern.as_str())
+        .collect();
+    assert_eq!(unmatched, vec!["colour"]);
+}
+
+#[test]
+fn test_hidden_changes_merge() {
+    let config = FilterConfig::new().ignore("a").only("!b");
+    let mut first = HiddenChanges::new(&config);
+    first.record(HiddenBy::Rule(0));
+    first.record(HiddenBy::Change);
+    first.rules[0].matched = true;
+
+    let mut second = HiddenChanges::new(&config);
+    second.record(HiddenBy::Rule(0));
+    second.record(HiddenBy::Rule(1));
+    second.record(HiddenBy::Unselected);
+    second.rules[1].matched = true;
+
+    let mut total = HiddenChanges::default();
+    total.merge(&first);
+    total.merge(&second);
+
+    assert_eq!(total.rules[0].hidden, 2);
+    assert_eq!(total.rules[1].hidden, 1);
+    assert!(total.rules.iter().all(|rule| rule.matched));
+    assert_eq!(total.unselected, 1);
+    assert_eq!(total.by_change, 1);
+    assert_eq!(total.total(), 5);
+}
+
+#[test]
+fn test_pattern_matches_any_in() {
+    let doc =
+        parse_json(r#"{"spec": {"containers": [{"name": "web", "image": "a"}]}, "status": {}}"#)
+            .unwrap();
+
+    assert!(PathPattern::parse("spec.containers[0].image").matches_any_in(&doc));
+    assert!(PathPattern::parse("**.image").matches_any_in(&doc));
+    assert!(PathPattern::parse("spec.containers[?(@.name == 'web')]").matches_any_in(&doc));
+    assert!(PathPattern::parse("status").matches_any_in(&doc));
+    assert!(!PathPattern::parse("spec.container").matches_any_in(&doc));
+    assert!(!PathPattern::parse("spec.containers[?(@.name == 'db')]").matches_any_in(&doc));
+    assert!(!PathPattern::parse("status.*").matches_any_in(&doc));
+}
+
+#[test]
+fn test_pattern_display_round_trips() {
+    let patterns = [
+        "spec.containers[0].image",
+        "!metadata.**",
+        "*.name",
+        "'a.b'.c",
+        "env_*.[a-c]?",
+        "/^x-.*$/i./a\\/b/",
+        "items[*]",
+        "items[1:3]",
+        "items[::2]",
+        "items[?(@.name == 'web' && (@.port > 80 || !@.enabled))]",
+        "items[?(@['odd key'] != null)]",
+    ];
+
+    for source in patterns {
+        assert_eq!(PathPattern::parse(source).to_string(), source);
+    }
+
+    assert_eq!(ValuePredicate::parse(">= 10").unwrap().to_string(), ">= 10");
+    assert_eq!(
+        ValuePredicate::parse(":latest$").unwrap().to_string(),
+        ":latest$"
+    );
+}
+
+#[test]
+fn test_filter_config_serde_round_trip() {
+    let filter = FilterConfig::new()
+        .ignore("metadata.**")
+        .ignore("!metadata.labels.**")
+        .only("Spec.**")
+        .case_insensitive(true)
+        .only_type(ChangeType::Modified)
+        .old_value(ValuePredicate::parse("> 2").unwrap());
+
+    let json = serde_json::to_value(&filter).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "rules": [
+                {"action": "ignore", "pattern": "metadata.**"},
+                {"action": "ignore", "pattern": "!metadata.labels.**"},
+                {"action": "only", "pattern": "Spec.**"}
+            ],
+            "case_insensitive": true,
+            "change_types": ["modified"],
+            "old_value": ["> 2"],
+            "new_value": []
+        })
+    );
+
+    let back: FilterConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(back.rules.len(), 3);
+    assert_eq!(back.rules[1].action, FilterAction::Ignore);
+    assert!(back.rules[1].pattern.negated);
+    assert!(back.should_include(&Path::root().key("spec").key("x")));
+    assert!(!back.should_include(&Path::root().key("status")));
+    assert_eq!(back.change_types, vec![ChangeType::Modified]);
+    assert!(back.old_value_predicates[0].evaluate(&Node::Number(3.0)));
+}
+
+#[test]
+fn test_filter_config_deserialize_rejects_bad_patterns() {
+    let error = serde_json::from_str::<FilterConfig>(
+        r#"{"rules": [{"action": "ignore", "pattern": "items[?(@.a ==]"}]}"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("items[?(@.a ==]"));
+
+    assert!(serde_json::from_str::<FilterConfig>(r#"{"old_value": ["(unclosed"]}"#).is_err());
+    assert!(serde_json::from_str::<FilterConfig>(
+        r#"{"rules": [{"action": "drop", "pattern": "a"}]}"#
+    )
+    .is_err());
+}
@@ -1,4 +1,8 @@
-use sdiff_rs::git::{detect_git_diff_driver_args, is_null_file};
+use sdiff_rs::git::{
+    add_gitattributes, detect_git_diff_driver_args, is_null_file, remove_gitattributes,
+    run_merge_driver, DEFAULT_EXTENSIONS,
+};
+use sdiff_rs::DiffConfig;
 
 #[test]
 fn test_is_git_hash_valid() {
@@ -91,3 +95,174 @@ fn test_is_null_file() {
     assert!(!is_null_file("/tmp/file.json"));
     assert!(!is_null_file("file.json"));
 }
+
+#[test]
+fn test_run_merge_driver_writes_merged_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join(".merge_file_base");
+    let ours = dir.path().join(".merge_file_ours");
+    let theirs = dir.path().join(".merge_file_theirs");
+    let pathname = dir.path().join("config.yaml");
+
+    std::fs::write(&base, "a: 1\nb: 1\n").unwrap();
+    std::fs::write(&ours, "a: 2\nb: 1\n").unwrap();
+    std::fs::write(&theirs, "{\"a\": 1, \"b\": 3}").unwrap();
+
+    let result = run_merge_driver(
+        base.to_str().unwrap(),
+        ours.to_str().unwrap(),
+        theirs.to_str().unwrap(),
+        pathname.to_str().unwrap(),
+        &DiffConfig::default(),
+    )
+    .unwrap();
+
+    assert!(result.is_clean());
+    assert_eq!(std::fs::read_to_string(&ours).unwrap(), "a: 2\nb: 3\n");
+    assert!(!dir.path().join("config.yaml.sdiff-conflicts").exists());
+}
+
+#[test]
+fn test_run_merge_driver_keeps_sniffed_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join(".merge_file_base");
+    let ours = dir.path().join(".merge_file_ours");
+    let theirs = dir.path().join(".merge_file_theirs");
+    let pathname = dir.path().join("values");
+
+    std::fs::write(
+        &base,
+        "a: 1
+b: 1
+",
+    )
+    .unwrap();
+    std::fs::write(
+        &ours,
+        "a: 2
+b: 1
+",
+    )
+    .unwrap();
+    std::fs::write(
+        &theirs,
+        "a: 1
+b: 3
+",
+    )
+    .unwrap();
+
+    let result = run_merge_driver(
+        base.to_str().unwrap(),
+        ours.to_str().unwrap(),
+        theirs.to_str().unwrap(),
+        pathname.to_str().unwrap(),
+        &DiffConfig::default(),
+    )
+    .unwrap();
+
+    assert!(result.is_clean());
+    assert_eq!(std::fs::read_to_string(&ours).unwrap(), "a: 2\nb: 3\n");
+}
+
+#[test]
+fn test_run_merge_driver_reports_conflicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join(".merge_file_base");
+    let ours = dir.path().join(".merge_file_ours");
+    let theirs = dir.path().join(".merge_file_theirs");
+    let pathname = dir.path().join("config.json");
+
+    std::fs::write(&base, r#"{"a": 1}"#).unwrap();
+    std::fs::write(&ours, r#"{"a": 2}"#).unwrap();
+    std::fs::write(&theirs, r#"{"a": 3}"#).unwrap();
+
+    let result = run_merge_driver(
+        base.to_str().unwrap(),
+        ours.to_str().unwrap(),
+        theirs.to_str().unwrap(),
+        pathname.to_str().unwrap(),
+        &DiffConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(result.conflicts.len(), 1);
+    assert!(std::fs::read_to_string(&ours).unwrap().contains("\"a\": 2"));
+    assert_eq!(result.conflicts[0].path.to_string(), "a");
+
+    let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 3, "the driver only rewrites the ours file");
+}
+
+#[test]
+fn test_detect_git_diff_driver_args_added_and_deleted_files() {
+    let hash = "a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2".to_string();
+
+    let added = vec![
+        "new.json".to_string(),
+        "/dev/null".to_string(),
+        ".".to_string(),
+        ".".to_string(),
+        "new.json".to_string(),
+        hash.clone(),
+        "100644".to_string(),
+    ];
+    let (old, new) = detect_git_diff_driver_args(&added).unwrap();
+    assert_eq!(old, "/dev/null");
+    assert_eq!(new, "new.json");
+
+    let deleted = vec![
+        "old.json".to_string(),
+        "/tmp/git-blob-abc/old.json".to_string(),
+        hash,
+        "100644".to_string(),
+        "/dev/null".to_string(),
+        ".".to_string(),
+        ".".to_string(),
+    ];
+    let (old, new) = detect_git_diff_driver_args(&deleted).unwrap();
+    assert_eq!(old, "/tmp/git-blob-abc/old.json");
+    assert_eq!(new, "/dev/null");
+
+    // "." is only accepted in place of the hash of a null file
+    let mut invalid = added.clone();
+    invalid[1] = "/tmp/old.json".to_string();
+    assert!(detect_git_diff_driver_args(&invalid).is_none());
+}
+
+#[test]
+fn test_add_gitattributes_to_empty() {
+    let extensions = vec!["json".to_string(), "yaml".to_string()];
+    assert_eq!(
+        add_gitattributes("", &extensions),
+        "*.json diff=sdiff merge=sdiff\n*.yaml diff=sdiff merge=sdiff\n"
+    );
+}
+
+#[test]
+fn test_add_gitattributes_updates_existing_lines() {
+    let content = "# comment\n*.json text diff=json -merge\n*.png binary\n";
+    let updated = add_gitattributes(content, &["json".to_string(), "toml".to_string()]);
+    assert_eq!(
+        updated,
+        "# comment\n*.json text diff=sdiff merge=sdiff\n*.png binary\n*.toml diff=sdiff merge=sdiff\n"
+    );
+}
+
+#[test]
+fn test_add_gitattributes_is_idempotent() {
+    let extensions: Vec<String> = DEFAULT_EXTENSIONS.iter().map(|s| s.to_string()).collect();
+    let once = add_gitattributes("*.md text\n", &extensions);
+    let twice = add_gitattributes(&once, &extensions);
+    assert_eq!(once, twice);
+}
+
+#[test]
+fn test_remove_gitattributes() {
+    let content = "# comment\n*.json text diff=sdiff merge=sdiff\n*.yaml diff=sdiff merge=sdiff\n*.png binary\n";
+    assert_eq!(
+        remove_gitattributes(content),
+        "# comment\n*.json text\n*.png binary\n"
+    );
+    assert_eq!(remove_gitattributes("*.yaml diff=sdiff\n"), "");
+}
@@ -227,3 +227,1095 @@ fn test_large_file() {
         .success()
         .code(0);
 }
+
+#[test]
+fn test_merge_clean() {
+    sdiff()
+        .arg("--merge")
+        .arg("tests/fixtures/merge_base.yaml")
+        .arg("tests/fixtures/merge_ours.yaml")
+        .arg("tests/fixtures/merge_theirs.yaml")
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("replicas: 3"))
+        .stdout(predicate::str::contains("image: nginx:1.25"))
+        .stdout(predicate::str::contains("- 443"));
+}
+
+#[test]
+fn test_merge_conflict_exit_1() {
+    sdiff()
+        .arg("--merge")
+        .arg("tests/fixtures/merge_base.yaml")
+        .arg("tests/fixtures/merge_ours.yaml")
+        .arg("tests/fixtures/merge_conflict.yaml")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("replicas: 3"))
+        .stderr(predicate::str::contains("CONFLICT replicas"))
+        .stderr(predicate::str::contains("theirs: 5"));
+}
+
+#[test]
+fn test_only_with_slice_pattern() {
+    sdiff()
+        .arg("tests/fixtures/array_old.json")
+        .arg("tests/fixtures/array_new.json")
+        .args(["--only", "$.tags[-1:]"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("tags[3]"))
+        .stdout(predicate::str::contains("items").not());
+}
+
+#[test]
+fn test_invalid_filter_pattern_exit_2() {
+    sdiff()
+        .arg("tests/fixtures/array_old.json")
+        .arg("tests/fixtures/array_new.json")
+        .args(["--only", "items[?(@ ==)]"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid pattern 'items[?(@ ==)]'"));
+}
+
+#[test]
+fn test_only_with_regex_and_case_insensitive_paths() {
+    sdiff()
+        .arg("tests/fixtures/array_old.json")
+        .arg("tests/fixtures/array_new.json")
+        .args(["--only", "/^TAG/.*", "--case-insensitive-paths"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("tags[3]"))
+        .stdout(predicate::str::contains("items").not());
+}
+
+#[test]
+fn test_only_type_and_value_filters() {
+    sdiff()
+        .arg("tests/fixtures/nested_old.json")
+        .arg("tests/fixtures/nested_new.json")
+        .args(["--only-type", "removed,added"])
+        .assert()
+        .code(0);
+
+    sdiff()
+        .arg("tests/fixtures/nested_old.json")
+        .arg("tests/fixtures/nested_new.json")
+        .args(["--only-type", "modified", "--new-value", "> 35"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("user.profile.age"))
+        .stdout(predicate::str::contains("city").not());
+}
+
+#[test]
+fn test_hidden_changes_summary_and_unmatched_warning() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("old.json"),
+        r#"{"name": "a", "status": {"ready": 1, "pods": 2}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("new.json"),
+        r#"{"name": "a", "status": {"ready": 2, "pods": 3}}"#,
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["old.json", "new.json", "--ignore", "status.**"])
+        .args(["--ignore", "stauts.**", "--format", "plain"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains(
+            "No changes detected. (2 changes hidden by filters)",
+        ))
+        .stderr(predicate::str::contains(
+            "Warning: pattern 'stauts.**' does not match any path",
+        ))
+        .stderr(predicate::str::contains("'status.**'").not());
+}
+
+#[test]
+fn test_invalid_only_type_exit_2() {
+    sdiff()
+        .arg("tests/fixtures/nested_old.json")
+        .arg("tests/fixtures/nested_new.json")
+        .args(["--only-type", "deleted"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Unknown change type 'deleted'"));
+}
+
+#[test]
+fn test_sdiffignore_next_to_compared_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("old.json"),
+        r#"{"replicas": 1, "metadata": {"uid": "a", "labels": {"app": "web"}}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("new.json"),
+        r#"{"replicas": 1, "metadata": {"uid": "b", "labels": {"app": "api"}}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join(".sdiffignore"),
+        "# cluster-managed\nmetadata.**\n!metadata.labels.**\n",
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["old.json", "new.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("metadata.labels.app"))
+        .stdout(predicate::str::contains("uid").not());
+
+    std::fs::write(dir.path().join("extra-ignore"), "metadata.labels.app\n").unwrap();
+    sdiff()
+        .current_dir(dir.path())
+        .args(["old.json", "new.json", "--ignore-file", "extra-ignore"])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn test_negated_patterns_on_command_line() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("old.json"),
+        r#"{"spec": {"image": "web:1", "secret": "a", "replicas": 1}, "status": "ok"}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("new.json"),
+        r#"{"spec": {"image": "web:2", "secret": "b", "replicas": 2}, "status": "failed"}"#,
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args([
+            "old.json",
+            "new.json",
+            "--only",
+            "spec.**",
+            "--only",
+            "!spec.secret",
+        ])
+        .args(["--ignore", "spec.replicas"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("spec.image"))
+        .stdout(predicate::str::contains("secret").not())
+        .stdout(predicate::str::contains("replicas").not())
+        .stdout(predicate::str::contains("status").not());
+}
+
+#[test]
+fn test_ignore_and_only_patterns_are_evaluated_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("old.json"), r#"{"spec": {"a": 1, "b": 1}}"#).unwrap();
+    std::fs::write(dir.path().join("new.json"), r#"{"spec": {"a": 2, "b": 2}}"#).unwrap();
+
+    // The last matching pattern wins, whichever option it was given with
+    for (args, shown, hidden) in [
+        (
+            ["--ignore", "spec.**", "--only", "spec.b"],
+            "spec.b",
+            "spec.a",
+        ),
+        (
+            ["--only", "spec.*", "--ignore", "spec.b"],
+            "spec.a",
+            "spec.b",
+        ),
+    ] {
+        sdiff()
+            .current_dir(dir.path())
+            .args(["old.json", "new.json", "--format", "plain"])
+            .args(args)
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains(shown))
+            .stdout(predicate::str::contains(hidden).not());
+    }
+
+    // Command-line patterns come after configured ones
+    std::fs::write(dir.path().join(".sdiff.toml"), "ignore = [\"spec.**\"]\n").unwrap();
+    sdiff()
+        .current_dir(dir.path())
+        .args([
+            "old.json", "new.json", "--format", "plain", "--only", "spec.a",
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("spec.a"))
+        .stdout(predicate::str::contains("spec.b").not());
+}
+
+#[test]
+fn test_merge_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("merged.json");
+
+    sdiff()
+        .arg("--merge")
+        .arg("tests/fixtures/merge_base.yaml")
+        .arg("tests/fixtures/merge_ours.yaml")
+        .arg("tests/fixtures/merge_theirs.yaml")
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .code(0)
+        .stdout(predicate::str::is_empty());
+
+    let merged = std::fs::read_to_string(&output).unwrap();
+    assert!(merged.contains("\"replicas\": 3"));
+}
+
+/// Runs git in `dir`, panicking on failure.
+fn git(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success() || args[0] == "merge",
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+/// Creates a git repository that uses sdiff as the merge driver for JSON files.
+fn merge_driver_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let bin = assert_cmd::cargo::cargo_bin!("sdiff-rs");
+
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+    git(
+        dir.path(),
+        &[
+            "config",
+            "merge.sdiff.driver",
+            &format!("{} --git-merge-driver %O %A %B %P", bin.display()),
+        ],
+    );
+
+    std::fs::write(dir.path().join(".gitattributes"), "*.json merge=sdiff\n").unwrap();
+    std::fs::write(dir.path().join("config.json"), "{\"a\": 1,\n\"b\": 1}\n").unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "base"]);
+    dir
+}
+
+#[test]
+fn test_git_merge_driver_clean_merge() {
+    let dir = merge_driver_repo();
+    let path = dir.path();
+
+    git(path, &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(path.join("config.json"), "{\n  \"b\": 1,\n  \"a\": 2\n}\n").unwrap();
+    git(path, &["commit", "-q", "-am", "feature"]);
+
+    git(path, &["checkout", "-q", "main"]);
+    std::fs::write(path.join("config.json"), "{\"a\": 1, \"b\": 2}\n").unwrap();
+    git(path, &["commit", "-q", "-am", "main"]);
+
+    let output = git(path, &["merge", "-q", "--no-edit", "feature"]);
+    assert!(output.status.success());
+
+    let merged = std::fs::read_to_string(path.join("config.json")).unwrap();
+    assert_eq!(merged, "{\n  \"a\": 2,\n  \"b\": 2\n}\n");
+}
+
+#[test]
+fn test_git_merge_driver_conflict() {
+    let dir = merge_driver_repo();
+    let path = dir.path();
+
+    git(path, &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(path.join("config.json"), "{\"a\": 3, \"b\": 1}\n").unwrap();
+    git(path, &["commit", "-q", "-am", "feature"]);
+
+    git(path, &["checkout", "-q", "main"]);
+    std::fs::write(path.join("config.json"), "{\"a\": 2, \"b\": 1}\n").unwrap();
+    git(path, &["commit", "-q", "-am", "main"]);
+
+    let output = git(path, &["merge", "-q", "--no-edit", "feature"]);
+    assert!(!output.status.success());
+
+    let merged = std::fs::read_to_string(path.join("config.json")).unwrap();
+    assert!(merged.contains("\"a\": 2"));
+    assert!(!merged.contains("<<<<<<<"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("CONFLICT a"), "{}", stderr);
+    assert!(!path.join("config.json.sdiff-conflicts").exists());
+}
+
+/// Creates a git repository with two commits of `config.json`.
+fn history_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+
+    git(path, &["init", "-q", "-b", "main"]);
+    git(path, &["config", "user.email", "test@example.com"]);
+    git(path, &["config", "user.name", "Test"]);
+
+    std::fs::write(
+        path.join("config.json"),
+        r#"{"replicas": 1, "name": "app"}"#,
+    )
+    .unwrap();
+    git(path, &["add", "."]);
+    git(path, &["commit", "-q", "-m", "first"]);
+
+    std::fs::write(
+        path.join("config.json"),
+        "{\n  \"name\": \"app\",\n  \"replicas\": 2\n}\n",
+    )
+    .unwrap();
+    git(path, &["commit", "-q", "-am", "second"]);
+    dir
+}
+
+#[test]
+fn test_diff_two_revisions() {
+    let dir = history_repo();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["HEAD~1", "HEAD", "--", "config.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("replicas: 1 → 2"))
+        .stdout(predicate::str::contains("name").not());
+}
+
+#[test]
+fn test_diff_revision_against_working_tree() {
+    let dir = history_repo();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{"replicas": 2, "name": "app"}"#,
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["HEAD", "--", "config.json"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("No changes"));
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["HEAD~1", "--", "config.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("replicas: 1 → 2"));
+}
+
+#[test]
+fn test_diff_revision_from_subdirectory() {
+    let dir = history_repo();
+    let subdir = dir.path().join("sub");
+    std::fs::create_dir(&subdir).unwrap();
+
+    sdiff()
+        .current_dir(&subdir)
+        .args(["HEAD~1", "HEAD", "--", "../config.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("replicas"));
+}
+
+#[test]
+fn test_diff_revisions_with_path_on_one_side() {
+    let dir = history_repo();
+    let path = dir.path();
+    std::fs::write(path.join("new.json"), r#"{"port": 8080}"#).unwrap();
+    git(path, &["add", "new.json"]);
+    git(path, &["commit", "-q", "-m", "third"]);
+
+    sdiff()
+        .current_dir(path)
+        .args(["HEAD~1", "HEAD", "--", "new.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("+ port: 8080"));
+
+    sdiff()
+        .current_dir(path)
+        .args(["HEAD", "HEAD~1", "--", "new.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("- port: 8080"));
+
+    std::fs::remove_file(path.join("new.json")).unwrap();
+    sdiff()
+        .current_dir(path)
+        .args(["HEAD", "--", "new.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("- port: 8080"));
+
+    sdiff()
+        .current_dir(path)
+        .args(["HEAD~2", "--", "new.json"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[test]
+fn test_diff_unknown_revision_exit_2() {
+    let dir = history_repo();
+
+    sdiff()
+        .current_dir(dir.path())
+        .args(["HEAD~5", "HEAD", "--", "config.json"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("HEAD~5:config.json"));
+}
+
+/// Creates a repository that uses sdiff as the diff driver for JSON files,
+/// with a change to `config.json` in the working tree.
+fn diff_driver_repo() -> tempfile::TempDir {
+    let dir = history_repo();
+    let path = dir.path();
+    let bin = assert_cmd::cargo::cargo_bin!("sdiff-rs");
+
+    git(
+        path,
+        &["config", "diff.sdiff.command", &bin.display().to_string()],
+    );
+    std::fs::write(path.join(".gitattributes"), "*.json diff=sdiff\n").unwrap();
+    std::fs::write(
+        path.join("config.json"),
+        r#"{"replicas": 3, "name": "web", "tags": ["a"]}"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_git_diff_driver_defaults() {
+    let dir = diff_driver_repo();
+    let output = git(dir.path(), &["diff", "config.json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("replicas"));
+    assert!(stdout.contains("name"));
+    assert!(stdout.contains("Summary"));
+}
+
+#[test]
+fn test_git_diff_driver_reads_environment() {
+    let dir = diff_driver_repo();
+    let output = std::process::Command::new("git")
+        .args(["diff", "config.json"])
+        .current_dir(dir.path())
+        .env("SDIFF_IGNORE", "name,tags")
+        .env("SDIFF_FORMAT", "json")
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let paths: Vec<String> = json["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["path"].to_string())
+        .collect();
+
+    assert!(paths.iter().any(|path| path.contains("\"replicas\"")));
+    assert!(!paths.iter().any(|path| path.contains("\"name\"")));
+    assert!(!paths.iter().any(|path| path.contains("\"tags\"")));
+    // The ignored values are counted, not shown
+    assert!(json["hidden"]["total"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_git_diff_driver_reads_git_config() {
+    let dir = diff_driver_repo();
+    git(dir.path(), &["config", "sdiff.only", "name"]);
+    git(dir.path(), &["config", "sdiff.format", "plain"]);
+
+    let output = git(dir.path(), &["diff", "config.json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("• name: \"app\" → \"web\""));
+    assert!(!stdout.contains("replicas"));
+}
+
+#[test]
+fn test_git_diff_driver_reads_repository_config_file() {
+    let dir = diff_driver_repo();
+    std::fs::write(
+        dir.path().join(".sdiff.toml"),
+        "ignore = [\"replicas\", \"tags\"]\nformat = \"plain\"\n",
+    )
+    .unwrap();
+
+    let output = git(dir.path(), &["diff", "config.json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("name"));
+    assert!(!stdout.contains("replicas"));
+
+    // Environment variables take precedence over the file
+    let output = std::process::Command::new("git")
+        .args(["diff", "config.json"])
+        .current_dir(dir.path())
+        .env("SDIFF_FORMAT", "json")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"changes\""));
+}
+
+#[test]
+fn test_git_diff_driver_reads_user_config_profiles_and_file_rules() {
+    let dir = diff_driver_repo();
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir(home.path().join("sdiff")).unwrap();
+    std::fs::write(
+        home.path().join("sdiff/config.toml"),
+        "format = \"plain\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join(".sdiff.toml"),
+        "[profiles.stable]\nignore = [\"name\"]\n\n[[files]]\npattern = \"*.json\"\nignore = [\"tags\"]\n",
+    )
+    .unwrap();
+
+    let output = std::process::Command::new("git")
+        .args(["diff", "config.json"])
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("SDIFF_PROFILE", "stable")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("replicas"), "{}", stdout);
+    assert!(!stdout.contains("name"));
+    assert!(!stdout.contains("tags"));
+}
+
+#[test]
+fn test_git_diff_driver_reports_invalid_filters() {
+    let dir = diff_driver_repo();
+    git(dir.path(), &["config", "sdiff.onlyType", "chnaged"]);
+
+    let output = std::process::Command::new("git")
+        .args(["diff", "config.json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        stderr.contains("Unknown change type 'chnaged'"),
+        "{}",
+        stderr
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("replicas"));
+}
+
+#[test]
+fn test_git_diff_driver_added_and_deleted_files() {
+    let dir = diff_driver_repo();
+    let path = dir.path();
+    std::fs::write(
+        path.join("new.json"),
+        r#"{"service": {"port": 8080, "hosts": ["a", "b"]}}"#,
+    )
+    .unwrap();
+    git(path, &["add", "new.json"]);
+    git(path, &["rm", "-q", "--cached", "config.json"]);
+
+    let output = git(path, &["diff", "--cached"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("+ service.port: 8080"));
+    assert!(stdout.contains("+ service.hosts[1]: \"b\""));
+    assert!(stdout.contains("- replicas: 2"));
+    assert!(stdout.contains("3 added"));
+
+    let output = std::process::Command::new("git")
+        .args(["diff", "--cached", "--", "new.json"])
+        .current_dir(path)
+        .env("SDIFF_IGNORE", "service.hosts.*")
+        .env("SDIFF_FORMAT", "json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"added\": 1"));
+}
+
+#[test]
+fn test_textconv_output() {
+    sdiff()
+        .arg("--textconv")
+        .arg("tests/fixtures/simple_old.toml")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("package.name = \"example\"\n"))
+        .stdout(predicate::str::contains("metadata.year = 2024\n"));
+}
+
+#[test]
+fn test_textconv_stdin() {
+    sdiff()
+        .args(["--textconv", "-", "--input-format", "yaml"])
+        .write_stdin("b: 1\na: [x]\n")
+        .assert()
+        .success()
+        .stdout("a[0] = \"x\"\nb = 1\n");
+}
+
+#[test]
+fn test_git_textconv_log() {
+    let dir = history_repo();
+    let bin = assert_cmd::cargo::cargo_bin!("sdiff-rs");
+    git(
+        dir.path(),
+        &[
+            "config",
+            "diff.sdiff.textconv",
+            &format!("{} --textconv", bin.display()),
+        ],
+    );
+    std::fs::write(dir.path().join(".gitattributes"), "*.json diff=sdiff\n").unwrap();
+
+    let output = git(dir.path(), &["log", "-p", "-1", "--format=", "config.json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // The reformatting in the second commit produces no textual change
+    assert!(stdout.contains("-replicas = 1\n+replicas = 2\n"));
+    assert!(!stdout.contains("-name"));
+    assert!(!stdout.contains("+name"));
+}
+
+#[test]
+fn test_git_install_local_scope_with_attributes() {
+    let dir = history_repo();
+    let path = dir.path();
+    let home = tempfile::tempdir().unwrap();
+    std::fs::write(path.join(".gitattributes"), "*.md text\n").unwrap();
+
+    sdiff()
+        .current_dir(path)
+        .env("HOME", home.path())
+        .args([
+            "--git-install",
+            "--local",
+            "--git-attributes",
+            "--git-extensions",
+            "json,yaml",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(local config)"));
+
+    let output = git(path, &["config", "--local", "--get", "diff.sdiff.command"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("sdiff-rs"));
+    assert!(!home.path().join(".gitconfig").exists());
+
+    let attributes = std::fs::read_to_string(path.join(".gitattributes")).unwrap();
+    assert_eq!(
+        attributes,
+        "*.md text\n*.json diff=sdiff merge=sdiff\n*.yaml diff=sdiff merge=sdiff\n"
+    );
+
+    sdiff()
+        .current_dir(path)
+        .env("HOME", home.path())
+        .arg("--git-status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[local]"))
+        .stdout(predicate::str::contains("*.json: diff=sdiff merge=sdiff"))
+        .stdout(predicate::str::contains("*.toml: diff=unspecified"));
+
+    sdiff()
+        .current_dir(path)
+        .env("HOME", home.path())
+        .args(["--git-uninstall", "--local", "--git-attributes"])
+        .assert()
+        .success();
+
+    let attributes = std::fs::read_to_string(path.join(".gitattributes")).unwrap();
+    assert_eq!(attributes, "*.md text\n");
+    let output = std::process::Command::new("git")
+        .args(["config", "--local", "--get", "diff.sdiff.command"])
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_directory_diff() {
+    let old = tempfile::tempdir().unwrap();
+    let new = tempfile::tempdir().unwrap();
+    std::fs::write(old.path().join("app.json"), r#"{"replicas": 1}"#).unwrap();
+    std::fs::write(new.path().join("app.json"), r#"{"replicas": 2}"#).unwrap();
+    std::fs::write(new.path().join("extra.yaml"), "enabled: true\n").unwrap();
+    std::fs::write(new.path().join("notes.txt"), "hello\n").unwrap();
+
+    sdiff()
+        .arg(old.path())
+        .arg(new.path())
+        .arg("--format")
+        .arg("plain")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("=== app.json (modified) ==="))
+        .stdout(predicate::str::contains("• replicas: 1 → 2"))
+        .stdout(predicate::str::contains("=== extra.yaml (added) ==="))
+        .stdout(predicate::str::contains(
+            "Files: 2 changed, 0 unchanged, 1 unsupported",
+        ))
+        .stdout(predicate::str::contains("Summary: 1 added, 1 modified"));
+}
+
+#[test]
+fn test_revision_tree_diff() {
+    let repo = history_repo();
+    std::fs::write(repo.path().join("extra.toml"), "debug = true\n").unwrap();
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "third"]);
+
+    sdiff()
+        .current_dir(repo.path())
+        .args(["HEAD~2", "HEAD", "--format", "json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("\"path\": \"config.json\""))
+        .stdout(predicate::str::contains("\"path\": \"extra.toml\""))
+        .stdout(predicate::str::contains("\"status\": \"added\""));
+
+    sdiff()
+        .current_dir(repo.path())
+        .args(["HEAD~2", "HEAD~1"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("extra.toml").not());
+}
+
+#[test]
+fn test_log_subcommand() {
+    let repo = history_repo();
+    std::fs::write(
+        repo.path().join("config.json"),
+        r#"{"replicas": 2, "name": "application"}"#,
+    )
+    .unwrap();
+    git(repo.path(), &["commit", "-q", "-am", "rename app"]);
+    std::fs::write(
+        repo.path().join("config.json"),
+        "{\"name\": \"application\",\n\"replicas\": 2}",
+    )
+    .unwrap();
+    git(repo.path(), &["commit", "-q", "-am", "reformat"]);
+
+    let output = sdiff()
+        .current_dir(repo.path())
+        .args(["log", "config.json", "replicas", "--format", "plain"])
+        .assert()
+        .code(0)
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("Test second\n  • replicas: 1 → 2"));
+    assert!(output.contains("Test first\n  + replicas: 1"));
+    assert!(!output.contains("rename app"));
+    assert!(!output.contains("reformat"));
+    assert!(output.find("second").unwrap() < output.find("first").unwrap());
+}
+
+#[test]
+fn test_log_subcommand_json() {
+    let repo = history_repo();
+
+    sdiff()
+        .current_dir(repo.path())
+        .args(["log", "config.json", "--format", "json"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("\"author\": \"Test\""))
+        .stdout(predicate::str::contains("\"subject\": \"second\""))
+        .stdout(predicate::str::contains("\"path\": [\n          \"name\"").count(1));
+}
+
+#[test]
+fn test_blame_subcommand() {
+    let repo = history_repo();
+    std::fs::write(
+        repo.path().join("config.json"),
+        "{\"replicas\": 2,\n \"name\": \"app\"}",
+    )
+    .unwrap();
+    git(repo.path(), &["commit", "-q", "-am", "reformat"]);
+
+    let output = sdiff()
+        .current_dir(repo.path())
+        .args(["blame", "config.json", "--format", "json"])
+        .assert()
+        .code(0)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["replicas"]["subject"], "second");
+    assert_eq!(json["replicas"]["value"], 2.0);
+    assert_eq!(json["name"]["subject"], "first");
+
+    std::fs::write(
+        repo.path().join("config.json"),
+        r#"{"replicas": 2, "name": "web"}"#,
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(repo.path())
+        .args(["blame", "config.json", "--format", "plain"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("Not Committed Yet  name: \"web\""))
+        .stdout(predicate::str::contains("Test").and(predicate::str::contains("replicas: 2")));
+}
+
+#[test]
+fn test_git_pre_commit_blocks_protected_changes() {
+    let repo = history_repo();
+    std::fs::write(
+        repo.path().join(".sdiff-policy.toml"),
+        "protected = [\"name\"]\n",
+    )
+    .unwrap();
+
+    std::fs::write(
+        repo.path().join("config.json"),
+        r#"{"replicas": 3, "name": "app"}"#,
+    )
+    .unwrap();
+    git(repo.path(), &["add", "config.json"]);
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-pre-commit")
+        .assert()
+        .code(0);
+
+    std::fs::write(
+        repo.path().join("config.json"),
+        r#"{"replicas": 3, "name": "web"}"#,
+    )
+    .unwrap();
+    git(repo.path(), &["add", "config.json"]);
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-pre-commit")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("config.json:"))
+        .stderr(predicate::str::contains("• name: \"app\" → \"web\""))
+        .stderr(predicate::str::contains("replicas").not());
+}
+
+#[test]
+fn test_git_pre_commit_blocks_unparsable_files() {
+    let repo = history_repo();
+    std::fs::write(
+        repo.path().join(".sdiff-policy.toml"),
+        "protected = [\"name\"]\n",
+    )
+    .unwrap();
+
+    std::fs::write(repo.path().join("config.json"), r#"{"name": "web""#).unwrap();
+    git(repo.path(), &["add", "config.json"]);
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-pre-commit")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid JSON in :config.json"));
+}
+
+#[test]
+fn test_git_install_hook() {
+    let repo = history_repo();
+
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-install-hook")
+        .assert()
+        .success();
+    let hook = std::fs::read_to_string(repo.path().join(".git/hooks/pre-commit")).unwrap();
+    assert!(hook.contains("--git-pre-commit"));
+
+    // Reinstalling over our own hook is fine
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-install-hook")
+        .assert()
+        .success();
+
+    std::fs::write(
+        repo.path().join(".git/hooks/pre-commit"),
+        "#!/bin/sh\nexit 0\n",
+    )
+    .unwrap();
+    sdiff()
+        .current_dir(repo.path())
+        .arg("--git-install-hook")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn test_project_config_profiles_and_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path();
+    std::fs::write(
+        path.join(".sdiff.toml"),
+        "format = \"json\"\n\n[profiles.quiet-labels]\nignore = [\"labels.**\"]\n",
+    )
+    .unwrap();
+    std::fs::create_dir(path.join("sub")).unwrap();
+    std::fs::write(path.join("sub/a.json"), r#"{"labels": {"x": 1}, "v": 1}"#).unwrap();
+    std::fs::write(path.join("sub/b.json"), r#"{"labels": {"x": 2}, "v": 2}"#).unwrap();
+
+    let home = tempfile::tempdir().unwrap();
+    let run = || {
+        let mut cmd = sdiff();
+        cmd.current_dir(path.join("sub"))
+            .env("XDG_CONFIG_HOME", home.path());
+        cmd
+    };
+
+    run()
+        .args(["a.json", "b.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("\"changes\""))
+        .stdout(predicate::str::contains("labels"));
+
+    run()
+        .args([
+            "a.json",
+            "b.json",
+            "--profile",
+            "quiet-labels",
+            "--format",
+            "plain",
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("• v: 1 → 2"))
+        .stdout(predicate::str::contains("labels").not());
+
+    run()
+        .args(["a.json", "b.json", "--profile", "nope"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Unknown profile 'nope'"));
+}
+
+#[test]
+fn test_config_ignore_file_is_relative_to_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    std::fs::create_dir(&sub).unwrap();
+    std::fs::write(dir.path().join("a.json"), r#"{"v": 1, "n": 1}"#).unwrap();
+    std::fs::write(dir.path().join("b.json"), r#"{"v": 2, "n": 2}"#).unwrap();
+    std::fs::write(dir.path().join("my-ignore"), "n\n").unwrap();
+    std::fs::write(
+        dir.path().join(".sdiff.toml"),
+        "ignore-file = [\"my-ignore\"]\nformat = \"plain\"\n",
+    )
+    .unwrap();
+
+    sdiff()
+        .current_dir(&sub)
+        .args(["../a.json", "../b.json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("v: 1 → 2"))
+        .stdout(predicate::str::contains("n:").not());
+}
+
+#[test]
+fn test_no_flags_override_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.json"), r#"{"v": 1, "s": "a b"}"#).unwrap();
+    std::fs::write(dir.path().join("b.json"), r#"{"v": 2, "s": " a b "}"#).unwrap();
+    std::fs::write(
+        dir.path().join(".sdiff.toml"),
+        "quiet = true\nignore-whitespace = true\nformat = \"plain\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        sdiff()
+            .current_dir(dir.path())
+            .args(["a.json", "b.json"])
+            .args(args)
+            .assert()
+            .code(1)
+    };
+
+    run(&[])
+        .stdout(predicate::str::contains("Summary").not())
+        .stdout(predicate::str::contains("s:").not());
+    run(&["--no-quiet", "--no-ignore-whitespace"])
+        .stdout(predicate::str::contains("Summary"))
+        .stdout(predicate::str::contains("s:"));
+
+    // The last of a flag and its --no- counterpart wins
+    run(&["--no-quiet", "--quiet"]).stdout(predicate::str::contains("Summary").not());
+    run(&["--quiet", "--no-quiet"]).stdout(predicate::str::contains("Summary"));
+}
+
+#[test]
+fn test_user_config_and_print_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir(home.path().join("sdiff")).unwrap();
+    std::fs::write(
+        home.path().join("sdiff/config.toml"),
+        "array-strategy = \"lcs\"\nmax-value-length = 20\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join(".sdiff.toml"), "max-value-length = 40\n").unwrap();
+
+    sdiff()
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .args(["--print-config", "--format", "plain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("config.toml"))
+        .stdout(predicate::str::contains(".sdiff.toml"))
+        .stdout(predicate::str::contains("format = \"plain\""))
+        .stdout(predicate::str::contains("array-strategy = \"lcs\""))
+        .stdout(predicate::str::contains("max-value-length = 40"));
+}
@@ -0,0 +1,127 @@
+use sdiff_rs::{merge, parse_json, ArrayDiffStrategy, DiffConfig, Node};
+
+fn json(content: &str) -> Node {
+    parse_json(content).unwrap()
+}
+
+#[test]
+fn test_merge_identical() {
+    let base = json(r#"{"a": 1}"#);
+    let result = merge(&base, &base, &base, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, base);
+}
+
+#[test]
+fn test_merge_one_sided_changes() {
+    let base = json(r#"{"a": 1, "b": 2}"#);
+    let ours = json(r#"{"a": 10, "b": 2}"#);
+    let result = merge(&base, &ours, &base, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, ours);
+
+    let result = merge(&base, &base, &ours, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, ours);
+}
+
+#[test]
+fn test_merge_disjoint_changes() {
+    let base = json(r#"{"a": 1, "b": 2, "c": 3}"#);
+    let ours = json(r#"{"a": 10, "b": 2, "c": 3, "d": 4}"#);
+    let theirs = json(r#"{"a": 1, "b": 20}"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, json(r#"{"a": 10, "b": 20, "d": 4}"#));
+}
+
+#[test]
+fn test_merge_identical_changes_do_not_conflict() {
+    let base = json(r#"{"version": "1.0"}"#);
+    let both = json(r#"{"version": "2.0", "extra": true}"#);
+
+    let result = merge(&base, &both, &both, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, both);
+}
+
+#[test]
+fn test_merge_conflicting_modification() {
+    let base = json(r#"{"replicas": 1, "name": "app"}"#);
+    let ours = json(r#"{"replicas": 3, "name": "app"}"#);
+    let theirs = json(r#"{"replicas": 5, "name": "web"}"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert_eq!(result.conflicts.len(), 1);
+
+    let conflict = &result.conflicts[0];
+    assert_eq!(conflict.path.to_string(), "replicas");
+    assert_eq!(conflict.base, Some(Node::Number(1.0)));
+    assert_eq!(conflict.ours, Some(Node::Number(3.0)));
+    assert_eq!(conflict.theirs, Some(Node::Number(5.0)));
+
+    // Conflicts keep our value, non-conflicting changes still apply
+    assert_eq!(result.merged, json(r#"{"replicas": 3, "name": "web"}"#));
+}
+
+#[test]
+fn test_merge_nested_conflict_reported_at_outer_path() {
+    let base = json(r#"{"db": {"host": "a", "port": 1}}"#);
+    let ours = json(r#"{"db": {"host": "b", "port": 1}}"#);
+    let theirs = json(r#"{}"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].path.to_string(), "db");
+    assert!(result.conflicts[0].theirs.is_none());
+    assert_eq!(result.merged, ours);
+}
+
+#[test]
+fn test_merge_array_changes() {
+    let base = json(r#"{"items": [1, 2, 3]}"#);
+    let ours = json(r#"{"items": [10, 2, 3]}"#);
+    let theirs = json(r#"{"items": [1, 2, 3, 4, 5]}"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, json(r#"{"items": [10, 2, 3, 4, 5]}"#));
+}
+
+#[test]
+fn test_merge_array_removals() {
+    let base = json(r#"[1, 2, 3, 4]"#);
+    let ours = json(r#"[1, 2]"#);
+    let theirs = json(r#"[0, 2, 3, 4]"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert!(result.is_clean());
+    assert_eq!(result.merged, json(r#"[0, 2]"#));
+}
+
+#[test]
+fn test_merge_both_resize_same_array_conflicts() {
+    let base = json(r#"{"items": [1, 2, 3]}"#);
+    let ours = json(r#"{"items": [1, 2]}"#);
+    let theirs = json(r#"{"items": [1, 2, 3, 4]}"#);
+
+    let result = merge(&base, &ours, &theirs, &DiffConfig::default());
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].path.to_string(), "items");
+    assert_eq!(result.merged, ours);
+}
+
+#[test]
+fn test_merge_ignores_lcs_strategy() {
+    let base = json(r#"[1, 2, 3]"#);
+    let ours = json(r#"[1, 2, 3, 4]"#);
+    let config = DiffConfig {
+        array_diff_strategy: ArrayDiffStrategy::Lcs,
+        ..DiffConfig::default()
+    };
+
+    let result = merge(&base, &ours, &base, &config);
+    assert!(result.is_clean());
+    assert_eq!(result.merged, ours);
+}
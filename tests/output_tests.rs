@@ -1,12 +1,14 @@
 use sdiff_rs::diff::{Change, ChangeType, Diff, DiffStats};
-use sdiff_rs::output::{format_diff, OutputFormat, OutputOptions};
-use sdiff_rs::Node;
+use sdiff_rs::filter::{filter_diff, FilterConfig, HiddenChanges};
+use sdiff_rs::output::{format_diff, format_path, OutputFormat, OutputOptions};
+use sdiff_rs::{Node, Path};
 
 #[test]
 fn test_format_plain_no_changes() {
     let diff = Diff {
         changes: vec![],
         stats: DiffStats::new(),
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert_eq!(output, "No changes detected.");
@@ -16,7 +18,7 @@ fn test_format_plain_no_changes() {
 fn test_format_plain_with_changes() {
     let diff = Diff {
         changes: vec![Change {
-            path: vec!["age".to_string()],
+            path: Path::root().key("age"),
             change_type: ChangeType::Modified,
             old_value: Some(Node::Number(30.0)),
             new_value: Some(Node::Number(31.0)),
@@ -27,6 +29,7 @@ fn test_format_plain_with_changes() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("age"));
@@ -39,7 +42,7 @@ fn test_format_plain_with_changes() {
 fn test_format_json() {
     let diff = Diff {
         changes: vec![Change {
-            path: vec!["age".to_string()],
+            path: Path::root().key("age"),
             change_type: ChangeType::Modified,
             old_value: Some(Node::Number(30.0)),
             new_value: Some(Node::Number(31.0)),
@@ -50,6 +53,7 @@ fn test_format_json() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Json, &OutputOptions::default()).unwrap();
     assert!(output.contains("\"age\""));
@@ -64,6 +68,7 @@ fn test_format_terminal_no_changes() {
     let diff = Diff {
         changes: vec![],
         stats: DiffStats::new(),
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Terminal, &OutputOptions::default()).unwrap();
     assert!(output.contains("No changes"));
@@ -74,7 +79,7 @@ fn test_format_change_types() {
     let options = OutputOptions::default();
 
     let added = Change {
-        path: vec!["new_field".to_string()],
+        path: Path::root().key("new_field"),
         change_type: ChangeType::Added,
         old_value: None,
         new_value: Some(Node::String("value".to_string())),
@@ -87,13 +92,14 @@ fn test_format_change_types() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.starts_with('+'));
     assert!(output.contains("new_field"));
 
     let removed = Change {
-        path: vec!["old_field".to_string()],
+        path: Path::root().key("old_field"),
         change_type: ChangeType::Removed,
         old_value: Some(Node::String("value".to_string())),
         new_value: None,
@@ -106,13 +112,14 @@ fn test_format_change_types() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.starts_with('-'));
     assert!(output.contains("old_field"));
 
     let modified = Change {
-        path: vec!["field".to_string()],
+        path: Path::root().key("field"),
         change_type: ChangeType::Modified,
         old_value: Some(Node::Number(1.0)),
         new_value: Some(Node::Number(2.0)),
@@ -125,6 +132,7 @@ fn test_format_change_types() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.contains("→"));
@@ -137,6 +145,7 @@ fn test_format_summary_empty() {
     let diff = Diff {
         changes: vec![],
         stats: DiffStats::new(),
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("No changes"));
@@ -147,37 +156,37 @@ fn test_format_summary_with_changes() {
     let diff = Diff {
         changes: vec![
             Change {
-                path: vec!["a".to_string()],
+                path: Path::root().key("a"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Null),
             },
             Change {
-                path: vec!["a".to_string()],
+                path: Path::root().key("a"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Null),
             },
             Change {
-                path: vec!["b".to_string()],
+                path: Path::root().key("b"),
                 change_type: ChangeType::Removed,
                 old_value: Some(Node::Null),
                 new_value: None,
             },
             Change {
-                path: vec!["c".to_string()],
+                path: Path::root().key("c"),
                 change_type: ChangeType::Modified,
                 old_value: Some(Node::Number(1.0)),
                 new_value: Some(Node::Number(2.0)),
             },
             Change {
-                path: vec!["c".to_string()],
+                path: Path::root().key("c"),
                 change_type: ChangeType::Modified,
                 old_value: Some(Node::Number(1.0)),
                 new_value: Some(Node::Number(2.0)),
             },
             Change {
-                path: vec!["c".to_string()],
+                path: Path::root().key("c"),
                 change_type: ChangeType::Modified,
                 old_value: Some(Node::Number(1.0)),
                 new_value: Some(Node::Number(2.0)),
@@ -189,6 +198,7 @@ fn test_format_summary_with_changes() {
             modified: 3,
             unchanged: 5,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("2 added"));
@@ -204,7 +214,7 @@ fn test_should_show_change_compact() {
     };
 
     let added = Change {
-        path: vec!["test".to_string()],
+        path: Path::root().key("test"),
         change_type: ChangeType::Added,
         old_value: None,
         new_value: Some(Node::Null),
@@ -217,12 +227,13 @@ fn test_should_show_change_compact() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.contains("test"));
 
     let unchanged = Change {
-        path: vec!["unchanged".to_string()],
+        path: Path::root().key("unchanged"),
         change_type: ChangeType::Unchanged,
         old_value: Some(Node::Null),
         new_value: Some(Node::Null),
@@ -235,6 +246,7 @@ fn test_should_show_change_compact() {
             modified: 0,
             unchanged: 1,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(!output.contains("unchanged"));
@@ -248,7 +260,7 @@ fn test_should_show_change_non_compact() {
     };
 
     let unchanged = Change {
-        path: vec!["test".to_string()],
+        path: Path::root().key("test"),
         change_type: ChangeType::Unchanged,
         old_value: Some(Node::Null),
         new_value: Some(Node::Null),
@@ -261,6 +273,7 @@ fn test_should_show_change_non_compact() {
             modified: 0,
             unchanged: 1,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.contains("test"));
@@ -271,31 +284,31 @@ fn test_node_to_json_value() {
     let diff = Diff {
         changes: vec![
             Change {
-                path: vec!["null".to_string()],
+                path: Path::root().key("null"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Null),
             },
             Change {
-                path: vec!["bool".to_string()],
+                path: Path::root().key("bool"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Bool(true)),
             },
             Change {
-                path: vec!["number".to_string()],
+                path: Path::root().key("number"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Number(42.0)),
             },
             Change {
-                path: vec!["string".to_string()],
+                path: Path::root().key("string"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::String("test".to_string())),
             },
             Change {
-                path: vec!["array".to_string()],
+                path: Path::root().key("array"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Array(vec![Node::Number(1.0), Node::Number(2.0)])),
@@ -307,6 +320,7 @@ fn test_node_to_json_value() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
 
     let output = format_diff(&diff, &OutputFormat::Json, &OutputOptions::default()).unwrap();
@@ -320,7 +334,7 @@ fn test_node_to_json_value() {
 #[test]
 fn test_format_path_simple() {
     let change = Change {
-        path: vec!["user".to_string(), "name".to_string()],
+        path: Path::root().key("user").key("name"),
         change_type: ChangeType::Modified,
         old_value: Some(Node::String("old".to_string())),
         new_value: Some(Node::String("new".to_string())),
@@ -333,6 +347,7 @@ fn test_format_path_simple() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("user.name"));
@@ -341,7 +356,7 @@ fn test_format_path_simple() {
 #[test]
 fn test_format_path_array() {
     let change = Change {
-        path: vec!["items".to_string(), "[0]".to_string(), "id".to_string()],
+        path: Path::root().key("items").index(0).key("id"),
         change_type: ChangeType::Modified,
         old_value: Some(Node::Number(1.0)),
         new_value: Some(Node::Number(2.0)),
@@ -354,6 +369,7 @@ fn test_format_path_array() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("items[0].id"));
@@ -362,7 +378,7 @@ fn test_format_path_array() {
 #[test]
 fn test_format_path_root() {
     let change = Change {
-        path: vec![],
+        path: Path::root(),
         change_type: ChangeType::Modified,
         old_value: Some(Node::Number(1.0)),
         new_value: Some(Node::Number(2.0)),
@@ -375,6 +391,7 @@ fn test_format_path_root() {
             modified: 1,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("(root)"));
@@ -385,25 +402,25 @@ fn test_format_value_primitives() {
     let diff = Diff {
         changes: vec![
             Change {
-                path: vec!["null".to_string()],
+                path: Path::root().key("null"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Null),
             },
             Change {
-                path: vec!["bool".to_string()],
+                path: Path::root().key("bool"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Bool(true)),
             },
             Change {
-                path: vec!["num".to_string()],
+                path: Path::root().key("num"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::Number(42.0)),
             },
             Change {
-                path: vec!["str".to_string()],
+                path: Path::root().key("str"),
                 change_type: ChangeType::Added,
                 old_value: None,
                 new_value: Some(Node::String("hello".to_string())),
@@ -415,6 +432,7 @@ fn test_format_value_primitives() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let output = format_diff(&diff, &OutputFormat::Plain, &OutputOptions::default()).unwrap();
     assert!(output.contains("null"));
@@ -428,7 +446,7 @@ fn test_format_value_truncation() {
     let long_string = "a".repeat(100);
     let diff = Diff {
         changes: vec![Change {
-            path: vec!["long".to_string()],
+            path: Path::root().key("long"),
             change_type: ChangeType::Added,
             old_value: None,
             new_value: Some(Node::String(long_string)),
@@ -439,6 +457,7 @@ fn test_format_value_truncation() {
             modified: 0,
             unchanged: 0,
         },
+        hidden: HiddenChanges::default(),
     };
     let options = OutputOptions {
         max_value_length: 20,
@@ -447,3 +466,82 @@ fn test_format_value_truncation() {
     let output = format_diff(&diff, &OutputFormat::Plain, &options).unwrap();
     assert!(output.contains("..."));
 }
+
+#[test]
+fn test_format_textconv_sorted_and_canonical() {
+    let a = sdiff_rs::parse_json(r#"{"z": 1, "a": {"y": true, "b": null}, "list": [2.5, "x\"y"]}"#)
+        .unwrap();
+    let b = sdiff_rs::parse_yaml("list:\n  - 2.5\n  - 'x\"y'\na:\n  b: null\n  y: true\nz: 1.0\n")
+        .unwrap();
+
+    let expected = "a.b = null\na.y = true\nlist[0] = 2.5\nlist[1] = \"x\\\"y\"\nz = 1\n";
+    assert_eq!(sdiff_rs::output::format_textconv(&a), expected);
+    assert_eq!(sdiff_rs::output::format_textconv(&b), expected);
+}
+
+#[test]
+fn test_format_textconv_empty_containers() {
+    let node = sdiff_rs::parse_json(r#"{"labels": {}, "items": [], "nested": [[]]}"#).unwrap();
+    assert_eq!(
+        sdiff_rs::output::format_textconv(&node),
+        "items = []\nlabels = {}\nnested[0] = []\n"
+    );
+}
+
+#[test]
+fn test_format_path_quotes_keys_with_dots() {
+    let path = Path::root()
+        .key("metadata")
+        .key("labels")
+        .key("app.kubernetes.io/name");
+    assert_eq!(
+        format_path(&path),
+        r#"metadata.labels."app.kubernetes.io/name""#
+    );
+
+    let path = Path::root().key(r#""hi""#).key("*").index(2).key("[3]");
+    assert_eq!(format_path(&path), r#""\"hi\""."*"[2]."[3]""#);
+}
+
+#[test]
+fn test_format_reports_hidden_changes() {
+    let old = sdiff_rs::parse_json(r#"{"name": "a", "meta": {"x": 1, "y": 1}}"#).unwrap();
+    let new = sdiff_rs::parse_json(r#"{"name": "b", "meta": {"x": 2, "y": 2}}"#).unwrap();
+    let diff = sdiff_rs::compute_diff(&old, &new, &sdiff_rs::DiffConfig::default());
+    let options = OutputOptions::default();
+
+    let filtered = filter_diff(&diff, &FilterConfig::new().ignore("meta.*"));
+    let output = format_diff(&filtered, &OutputFormat::Plain, &options).unwrap();
+    assert!(output.ends_with("Summary: 1 modified (2 changes hidden by filters)"));
+
+    let filtered = filter_diff(&diff, &FilterConfig::new().ignore("meta.*").ignore("name"));
+    let output = format_diff(&filtered, &OutputFormat::Plain, &options).unwrap();
+    assert_eq!(output, "No changes detected. (3 changes hidden by filters)");
+
+    let filtered = filter_diff(&diff, &FilterConfig::new().ignore("name"));
+    let output = format_diff(&filtered, &OutputFormat::Plain, &options).unwrap();
+    assert!(output.ends_with("(1 change hidden by filters)"));
+}
+
+#[test]
+fn test_format_json_hidden_counts() {
+    let old = sdiff_rs::parse_json(r#"{"name": "a", "meta": {"x": 1, "y": 1}}"#).unwrap();
+    let new = sdiff_rs::parse_json(r#"{"name": "b", "meta": {"x": 2, "y": 2}}"#).unwrap();
+    let diff = sdiff_rs::compute_diff(&old, &new, &sdiff_rs::DiffConfig::default());
+    let filter = FilterConfig::new().ignore("meta.*").only("!nmae");
+
+    let filtered = filter_diff(&diff, &filter);
+    let output = format_diff(&filtered, &OutputFormat::Json, &OutputOptions::default()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(json["hidden"]["total"], 2);
+    assert_eq!(json["hidden"]["unselected"], 0);
+    assert_eq!(json["hidden"]["by_change"], 0);
+    assert_eq!(
+        json["hidden"]["patterns"],
+        serde_json::json!([
+            {"pattern": "meta.*", "action": "ignore", "hidden": 2, "matched": true},
+            {"pattern": "!nmae", "action": "only", "hidden": 0, "matched": false},
+        ])
+    );
+}
@@ -0,0 +1,76 @@
+//! Tests for structured paths and their renderings.
+
+use sdiff_rs::path::{Path, PathSegment};
+use sdiff_rs::{compute_diff, parse_json, DiffConfig};
+
+#[test]
+fn test_diff_paths_distinguish_keys_from_indices() {
+    let old = parse_json(r#"{"items": [1], "[0]": 1}"#).unwrap();
+    let new = parse_json(r#"{"items": [2], "[0]": 2}"#).unwrap();
+
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+    let mut paths: Vec<Path> = diff.changes.into_iter().map(|c| c.path).collect();
+    paths.sort();
+
+    assert_eq!(paths[0], Path::root().key("[0]"));
+    assert_eq!(paths[1], Path::root().key("items").index(0));
+    assert_eq!(paths[0].to_string(), r#""[0]""#);
+    assert_eq!(paths[1].to_string(), "items[0]");
+}
+
+#[test]
+fn test_json_pointer() {
+    assert_eq!(Path::root().to_json_pointer(), "");
+    assert_eq!(
+        Path::root()
+            .key("a/b")
+            .key("m~n")
+            .index(3)
+            .to_json_pointer(),
+        "/a~1b/m~0n/3"
+    );
+}
+
+#[test]
+fn test_jsonpath() {
+    assert_eq!(Path::root().to_jsonpath(), "$");
+    assert_eq!(
+        Path::root()
+            .key("metadata")
+            .key("app.kubernetes.io/name")
+            .key("it's")
+            .index(1)
+            .to_jsonpath(),
+        r#"$.metadata['app.kubernetes.io/name']['it\'s'][1]"#
+    );
+}
+
+#[test]
+fn test_dotted() {
+    assert_eq!(Path::root().to_dotted(), "");
+    assert_eq!(Path::root().index(0).key("name").to_dotted(), "[0].name");
+    assert_eq!(
+        Path::root().key("labels").key("example.com").to_dotted(),
+        r#"labels."example.com""#
+    );
+}
+
+#[test]
+fn test_parent_and_segments() {
+    let path = Path::root().key("spec").index(2);
+    assert_eq!(path.parent(), Some(Path::root().key("spec")));
+    assert_eq!(Path::root().parent(), None);
+    assert_eq!(path[1].as_index(), Some(2));
+    assert_eq!(path[0].as_key(), Some("spec"));
+    assert_eq!(path.last(), Some(&PathSegment::Index(2)));
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let path = Path::root().key("items").index(0).key("7");
+    let json = serde_json::to_string(&path).unwrap();
+    assert_eq!(json, r#"["items",0,"7"]"#);
+
+    let parsed: Path = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, path);
+}
@@ -0,0 +1,135 @@
+use sdiff_rs::policy::Policy;
+use sdiff_rs::{compute_diff, parse_json, parse_yaml, Diff, DiffConfig, SdiffError};
+
+fn diff(old: &str, new: &str) -> Diff {
+    compute_diff(
+        &parse_json(old).unwrap(),
+        &parse_json(new).unwrap(),
+        &DiffConfig::default(),
+    )
+}
+
+#[test]
+fn test_protected_path_change_is_violation() {
+    let policy = Policy::new().rule(None, &["database.host"], &[]).unwrap();
+    let diff = diff(
+        r#"{"database": {"host": "a", "port": 1}}"#,
+        r#"{"database": {"host": "b", "port": 2}}"#,
+    );
+
+    let violations = policy.check("app.json", &diff);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].file, "app.json");
+    assert_eq!(violations[0].change.path.to_string(), "database.host");
+}
+
+#[test]
+fn test_changes_inside_protected_path_are_violations() {
+    let policy = Policy::new().rule(None, &["database"], &[]).unwrap();
+    let diff = diff(
+        r#"{"database": {"host": "a"}, "name": "x"}"#,
+        r#"{"database": {"host": "b"}, "name": "y"}"#,
+    );
+
+    let violations = policy.check("app.json", &diff);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].change.path.to_string(), "database.host");
+}
+
+#[test]
+fn test_removing_ancestor_of_protected_path_is_violation() {
+    let policy = Policy::new().rule(None, &["database.host"], &[]).unwrap();
+    let diff = diff(r#"{"database": {"host": "a"}, "x": 1}"#, r#"{"x": 1}"#);
+
+    assert_eq!(policy.check("app.json", &diff).len(), 1);
+}
+
+#[test]
+fn test_allowed_paths_are_exempt() {
+    let policy = Policy::new()
+        .rule(None, &["database.**"], &["database.pool_size"])
+        .unwrap();
+    let diff = diff(
+        r#"{"database": {"host": "a", "pool_size": 5}}"#,
+        r#"{"database": {"host": "a", "pool_size": 10}}"#,
+    );
+
+    assert!(policy.check("app.json", &diff).is_empty());
+}
+
+#[test]
+fn test_rules_apply_to_matching_files_only() {
+    let policy = Policy::new()
+        .rule(Some("config/prod/*.json"), &["database.host"], &[])
+        .unwrap();
+    let diff = diff(
+        r#"{"database": {"host": "a"}}"#,
+        r#"{"database": {"host": "b"}}"#,
+    );
+
+    assert_eq!(policy.check("config/prod/app.json", &diff).len(), 1);
+    assert!(policy.check("config/dev/app.json", &diff).is_empty());
+}
+
+#[test]
+fn test_policy_from_toml() {
+    let policy = Policy::from_toml(
+        r#"
+protected = ["metadata.name"]
+
+[[rules]]
+files = "*.yaml"
+protected = ["spec.**"]
+allowed = ["spec.replicas"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(policy.rules.len(), 2);
+
+    let old = parse_yaml("metadata: {name: a}\nspec: {replicas: 1, image: x}\n").unwrap();
+    let new = parse_yaml("metadata: {name: b}\nspec: {replicas: 2, image: y}\n").unwrap();
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+
+    assert_eq!(policy.check("deploy.yaml", &diff).len(), 2);
+    assert_eq!(policy.check("deploy.json", &diff).len(), 1);
+}
+
+#[test]
+fn test_policy_rejects_unknown_keys() {
+    assert!(Policy::from_toml("protect = [\"a\"]").is_err());
+}
+
+#[test]
+fn test_policy_rejects_malformed_patterns() {
+    let err = Policy::from_toml("protected = [\"items[?(@.name == )]\"]").unwrap_err();
+    assert!(matches!(err, SdiffError::InvalidPattern { .. }), "{}", err);
+    assert!(Policy::from_toml("[[rules]]\nallowed = [\"items[1:2:3:4]\"]\n").is_err());
+    assert!(Policy::new().rule(None, &["a[?(@.b"], &[]).is_err());
+}
+
+#[test]
+fn test_policy_evaluates_filters_in_the_documents() {
+    let old = parse_json(
+        r#"{"items": [{"name": "a", "locked": true, "v": 1}, {"name": "b", "locked": false, "v": 1}]}"#,
+    )
+    .unwrap();
+    let new = parse_json(
+        r#"{"items": [{"name": "a", "locked": true, "v": 2}, {"name": "b", "locked": false, "v": 2}]}"#,
+    )
+    .unwrap();
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+
+    let protected = Policy::new()
+        .rule(None, &["items[?(@.locked == true)]"], &[])
+        .unwrap();
+    let violations = protected.check_in("app.json", &diff, &old, &new);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].change.path.to_string(), "items[0].v");
+
+    let allowed = Policy::new()
+        .rule(None, &["items.**"], &["items[?(@.name == 'b')]"])
+        .unwrap();
+    let violations = allowed.check_in("app.json", &diff, &old, &new);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].change.path.to_string(), "items[0].v");
+}
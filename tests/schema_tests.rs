@@ -0,0 +1,252 @@
+//! Validates JSON output against the shipped JSON Schema.
+
+use assert_cmd::Command;
+use jsonschema::JSONSchema;
+use sdiff_rs::diff::{compute_added_diff, compute_removed_diff, RuleSet};
+use sdiff_rs::dir::{diff_sources_filtered, Source};
+use sdiff_rs::filter::{filter_diff_in, FilterConfig, ValuePredicate};
+use sdiff_rs::output::{
+    format_diff_with_context, format_dir_diff_with_context, DiffContext, InputInfo, JSON_SCHEMA,
+    SCHEMA_VERSION,
+};
+use sdiff_rs::{
+    compute_diff, compute_diff_filtered, format_diff, parse_json, ArrayDiffStrategy, ChangeType,
+    Diff, DiffConfig, FormatHint, OutputFormat, OutputOptions,
+};
+use serde_json::Value;
+use std::fs;
+
+fn schema() -> JSONSchema {
+    let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+    JSONSchema::compile(&schema).unwrap()
+}
+
+fn assert_valid(output: &str) -> Value {
+    let document: Value = serde_json::from_str(output).unwrap();
+    let schema = schema();
+    if let Err(errors) = schema.validate(&document) {
+        let messages: Vec<String> = errors
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        panic!(
+            "output does not match the schema: {:#?}\n{}",
+            messages, output
+        );
+    }
+    document
+}
+
+fn to_json(diff: &Diff, context: &DiffContext) -> String {
+    format_diff_with_context(
+        diff,
+        &OutputFormat::Json,
+        &OutputOptions::default(),
+        context,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_schema_version_matches() {
+    let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+    let version = &schema["definitions"]["diffDocument"]["properties"]["schema_version"]["const"];
+    assert_eq!(version, &Value::from(SCHEMA_VERSION));
+}
+
+#[test]
+fn test_plain_diff_matches_schema() {
+    let old = parse_json(r#"{"name": "web", "replicas": 2}"#).unwrap();
+    let new = parse_json(r#"{"name": "web", "replicas": 3}"#).unwrap();
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+
+    let output = format_diff(&diff, &OutputFormat::Json, &OutputOptions::default()).unwrap();
+    let document = assert_valid(&output);
+
+    assert_eq!(document["schema_version"], 1);
+    assert_eq!(document["kind"], "diff");
+    assert!(document["old"].is_null());
+    assert!(document["config"]["diff"].is_null());
+    assert_eq!(document["stats"]["total"], 1);
+    assert!(document["stats"].get("compared").is_none());
+}
+
+#[test]
+fn test_all_change_types_match_schema() {
+    let old = parse_json(r#"{"gone": null, "kept": 1, "items": [1, {"a": true}]}"#).unwrap();
+    let new = parse_json(r#"{"new": null, "kept": 2, "items": [1, {"a": false}, 3]}"#).unwrap();
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+
+    let document = assert_valid(&to_json(&diff, &DiffContext::new()));
+    let changes = document["changes"].as_array().unwrap();
+
+    let added = changes.iter().find(|c| c["path"][0] == "new").unwrap();
+    assert!(added["new_value"].is_null());
+    assert!(added.get("old_value").is_none());
+
+    let removed = changes.iter().find(|c| c["path"][0] == "gone").unwrap();
+    assert!(removed["old_value"].is_null());
+    assert!(removed.get("new_value").is_none());
+
+    assert!(changes
+        .iter()
+        .any(|c| c["path"] == serde_json::json!(["items", 1, "a"])));
+}
+
+#[test]
+fn test_added_and_removed_documents_match_schema() {
+    let node = parse_json(r#"{"a": {"b": [1, 2]}}"#).unwrap();
+
+    assert_valid(&to_json(&compute_added_diff(&node), &DiffContext::new()));
+    assert_valid(&to_json(&compute_removed_diff(&node), &DiffContext::new()));
+}
+
+#[test]
+fn test_diff_with_context_matches_schema() {
+    let old = parse_json(r#"{"image": "web:1", "replicas": 2, "meta": {"id": 1}}"#).unwrap();
+    let new = parse_json(r#"{"image": "web:latest", "replicas": 3, "meta": {"id": 2}}"#).unwrap();
+    let config = DiffConfig {
+        array_diff_strategy: ArrayDiffStrategy::Lcs,
+        ..DiffConfig::default()
+    }
+    .rule(
+        "items[*]",
+        RuleSet::new().array_key("id").numeric_tolerance(0.5),
+    )
+    .rule("meta.*", RuleSet::new().ignore(true));
+    let filter = FilterConfig::new()
+        .only("**")
+        .ignore("!replicas")
+        .ignore("stauts.**")
+        .only_type(ChangeType::Modified)
+        .new_value(ValuePredicate::parse(":latest$").unwrap());
+    let diff = compute_diff_filtered(&old, &new, &config, &filter);
+
+    let context = DiffContext::new()
+        .inputs(
+            InputInfo::new("old.json", Some(FormatHint::Json)),
+            InputInfo::new("-", Some(FormatHint::Yaml)),
+        )
+        .config(config)
+        .filter(filter);
+    let document = assert_valid(&to_json(&diff, &context));
+
+    assert_eq!(document["old"]["format"], "json");
+    assert_eq!(document["new"]["path"], "-");
+    assert_eq!(document["config"]["diff"]["array_strategy"], "lcs");
+    assert_eq!(document["config"]["diff"]["rules"][0]["array_key"], "id");
+    assert_eq!(
+        document["config"]["filter"]["rules"][1]["pattern"],
+        "!replicas"
+    );
+    assert_eq!(document["config"]["filter"]["new_value"][0], ":latest$");
+    assert_eq!(document["hidden"]["patterns"][2]["matched"], false);
+    assert!(document["hidden"]["total"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_filtered_added_document_matches_schema() {
+    let new = parse_json(r#"{"a": 1, "b": 2}"#).unwrap();
+    let filter = FilterConfig::new().ignore("a");
+    let diff = filter_diff_in(
+        &compute_added_diff(&new),
+        &filter,
+        &sdiff_rs::Node::Null,
+        &new,
+    );
+
+    let document = assert_valid(&to_json(&diff, &DiffContext::new().filter(filter)));
+    assert_eq!(document["hidden"]["total"], 1);
+}
+
+#[test]
+fn test_tree_diff_matches_schema() {
+    let old = tempfile::tempdir().unwrap();
+    let new = tempfile::tempdir().unwrap();
+    fs::write(old.path().join("app.json"), r#"{"replicas": 1}"#).unwrap();
+    fs::write(new.path().join("app.json"), r#"{"replicas": 3}"#).unwrap();
+    fs::write(old.path().join("removed.toml"), "key = \"value\"\n").unwrap();
+    fs::write(new.path().join("added.yaml"), "enabled: true\n").unwrap();
+    fs::write(new.path().join("broken.json"), "{").unwrap();
+    fs::write(new.path().join("notes.txt"), "hello").unwrap();
+
+    let config = DiffConfig::default();
+    let filter = FilterConfig::new().ignore("enabled");
+    let result = diff_sources_filtered(
+        &Source::Directory(old.path().to_path_buf()),
+        &Source::Directory(new.path().to_path_buf()),
+        &config,
+        &filter,
+    )
+    .unwrap();
+
+    let context = DiffContext::new()
+        .inputs(
+            InputInfo::new(&old.path().to_string_lossy(), None),
+            InputInfo::new(&new.path().to_string_lossy(), None),
+        )
+        .config(config)
+        .filter(filter);
+    let output = format_dir_diff_with_context(
+        &result,
+        &OutputFormat::Json,
+        &OutputOptions::default(),
+        &context,
+    )
+    .unwrap();
+    let document = assert_valid(&output);
+
+    assert_eq!(document["kind"], "tree");
+    assert!(document["old"]["format"].is_null());
+    let files = document["files"].as_array().unwrap();
+    let added = files.iter().find(|f| f["path"] == "added.yaml").unwrap();
+    assert_eq!(added["format"], "yaml");
+    assert_eq!(added["hidden"]["total"], 1);
+}
+
+#[test]
+fn test_cli_output_matches_schema() {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("sdiff-rs"))
+        .args([
+            "tests/fixtures/added_old.json",
+            "tests/fixtures/added_new.json",
+            "--format",
+            "json",
+            "--ignore",
+            "missing.**",
+        ])
+        .output()
+        .unwrap();
+    let document = assert_valid(&String::from_utf8(output.stdout).unwrap());
+
+    assert_eq!(document["old"]["path"], "tests/fixtures/added_old.json");
+    assert_eq!(document["old"]["format"], "json");
+    assert_eq!(document["config"]["filter"]["rules"][0]["action"], "ignore");
+    assert_eq!(document["config"]["diff"]["array_strategy"], "positional");
+}
+
+#[test]
+fn test_schema_rejects_invalid_documents() {
+    let old = parse_json(r#"{"a": 1}"#).unwrap();
+    let new = parse_json(r#"{"a": 2, "b": 3}"#).unwrap();
+    let diff = compute_diff(&old, &new, &DiffConfig::default());
+    let document = assert_valid(&to_json(&diff, &DiffContext::new()));
+    let schema = schema();
+
+    let mut wrong_version = document.clone();
+    wrong_version["schema_version"] = Value::from(2);
+    assert!(!schema.is_valid(&wrong_version));
+
+    let mut missing_value = document.clone();
+    let change = missing_value["changes"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|c| c["type"] == "modified")
+        .unwrap();
+    change.as_object_mut().unwrap().remove("new_value");
+    assert!(!schema.is_valid(&missing_value));
+
+    let mut extra_field = document;
+    extra_field["unexpected"] = Value::Bool(true);
+    assert!(!schema.is_valid(&extra_field));
+}
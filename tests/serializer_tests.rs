@@ -0,0 +1,224 @@
+//! Tests for converting Rust values into nodes.
+
+use sdiff_rs::diff::RuleSet;
+use sdiff_rs::{diff_values, parse_json, to_node, ChangeType, DiffConfig, Node, SerializeError};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize)]
+struct Container {
+    name: String,
+    image: String,
+    port: Option<u16>,
+}
+
+#[derive(Serialize)]
+struct Deployment {
+    replicas: u32,
+    containers: Vec<Container>,
+    labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+fn deployment(replicas: u32, image: &str) -> Deployment {
+    Deployment {
+        replicas,
+        containers: vec![Container {
+            name: "web".to_string(),
+            image: image.to_string(),
+            port: None,
+        }],
+        labels: BTreeMap::from([("app".to_string(), "web".to_string())]),
+        note: None,
+    }
+}
+
+#[derive(Serialize)]
+enum Event {
+    Started,
+    Scaled(u32),
+    Moved(String, String),
+    Failed { code: i32, retry: bool },
+}
+
+#[test]
+fn test_to_node_matches_parsed_json() {
+    let value = deployment(3, "web:1");
+
+    let node = to_node(&value).unwrap();
+    let parsed = parse_json(&serde_json::to_string(&value).unwrap()).unwrap();
+
+    assert_eq!(node, parsed);
+}
+
+#[test]
+fn test_to_node_primitives() {
+    assert_eq!(to_node(&true).unwrap(), Node::Bool(true));
+    assert_eq!(to_node(&-7i8).unwrap(), Node::Number(-7.0));
+    assert_eq!(to_node(&2.5f32).unwrap(), Node::Number(2.5));
+    assert_eq!(to_node(&'x').unwrap(), Node::String("x".to_string()));
+    assert_eq!(to_node("text").unwrap(), Node::String("text".to_string()));
+    assert_eq!(to_node(&()).unwrap(), Node::Null);
+    assert_eq!(to_node(&None::<u8>).unwrap(), Node::Null);
+    assert_eq!(to_node(&Some(1)).unwrap(), Node::Number(1.0));
+    assert_eq!(
+        to_node(&(1, "a")).unwrap(),
+        Node::Array(vec![Node::Number(1.0), Node::String("a".to_string())])
+    );
+}
+
+#[test]
+fn test_to_node_enums() {
+    let events = vec![
+        Event::Started,
+        Event::Scaled(3),
+        Event::Moved("a".to_string(), "b".to_string()),
+        Event::Failed {
+            code: 2,
+            retry: false,
+        },
+    ];
+
+    let expected = parse_json(
+        r#"["Started", {"Scaled": 3}, {"Moved": ["a", "b"]}, {"Failed": {"code": 2, "retry": false}}]"#,
+    )
+    .unwrap();
+    assert_eq!(to_node(&events).unwrap(), expected);
+}
+
+#[test]
+fn test_to_node_map_keys() {
+    let by_number = HashMap::from([(1, "one"), (20, "twenty")]);
+    assert_eq!(
+        to_node(&by_number).unwrap(),
+        parse_json(r#"{"1": "one", "20": "twenty"}"#).unwrap()
+    );
+
+    let by_flag = BTreeMap::from([(true, 1)]);
+    assert_eq!(
+        to_node(&by_flag).unwrap(),
+        parse_json(r#"{"true": 1}"#).unwrap()
+    );
+
+    let by_pair = HashMap::from([((1, 2), "pair")]);
+    assert!(matches!(
+        to_node(&by_pair),
+        Err(SerializeError::UnsupportedKey { .. })
+    ));
+}
+
+#[test]
+fn test_to_node_rejects_non_finite_numbers() {
+    assert!(matches!(
+        to_node(&f64::NAN),
+        Err(SerializeError::UnsupportedValue { .. })
+    ));
+    assert!(to_node(&vec![1.0, f64::INFINITY]).is_err());
+}
+
+#[test]
+fn test_to_node_rejects_inexact_integers() {
+    let max = 1u64 << 53;
+    assert_eq!(to_node(&max).unwrap(), Node::Number(max as f64));
+    assert_eq!(
+        to_node(&-(max as i64)).unwrap(),
+        Node::Number(-(max as f64))
+    );
+    assert_eq!(to_node(&(max as u128)).unwrap(), Node::Number(max as f64));
+
+    for err in [
+        to_node(&(max + 1)),
+        to_node(&-(max as i64 + 1)),
+        to_node(&u64::MAX),
+        to_node(&i64::MIN),
+        to_node(&i128::MIN),
+        to_node(&u128::MAX),
+    ] {
+        assert!(matches!(err, Err(SerializeError::UnsupportedValue { .. })));
+    }
+    assert_eq!(
+        to_node(&u64::MAX).unwrap_err().to_string(),
+        "Cannot represent 18446744073709551615 as a node"
+    );
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(untagged)]
+enum Key {
+    Number(u32),
+    Text(String),
+}
+
+#[derive(Serialize)]
+struct Labelled {
+    name: String,
+    #[serde(flatten)]
+    extra: BTreeMap<String, String>,
+}
+
+#[test]
+fn test_to_node_rejects_duplicate_keys() {
+    let keys = BTreeMap::from([(Key::Number(1), "a"), (Key::Text("1".to_string()), "b")]);
+    assert!(matches!(
+        to_node(&keys),
+        Err(SerializeError::DuplicateKey { key }) if key == "1"
+    ));
+
+    let labelled = Labelled {
+        name: "web".to_string(),
+        extra: BTreeMap::from([("name".to_string(), "api".to_string())]),
+    };
+    assert_eq!(
+        to_node(&labelled).unwrap_err().to_string(),
+        "Duplicate object key 'name'"
+    );
+}
+
+#[test]
+fn test_to_node_round_trips_nodes() {
+    let node = parse_json(r#"{"a": [1, null, {"b": "c"}], "d": true}"#).unwrap();
+    assert_eq!(to_node(&node).unwrap(), node);
+}
+
+#[test]
+fn test_diff_values() {
+    let old = deployment(2, "web:1");
+    let mut new = deployment(3, "web:2");
+    new.note = Some("scaled up".to_string());
+
+    let diff = diff_values(&old, &new, &DiffConfig::default()).unwrap();
+
+    let changes: Vec<(String, ChangeType)> = diff
+        .changes
+        .iter()
+        .map(|c| (c.path.to_string(), c.change_type.clone()))
+        .collect();
+    assert_eq!(diff.stats.total_changes(), 3);
+    assert!(changes.contains(&("replicas".to_string(), ChangeType::Modified)));
+    assert!(changes.contains(&("containers[0].image".to_string(), ChangeType::Modified)));
+    assert!(changes.contains(&("note".to_string(), ChangeType::Added)));
+}
+
+#[test]
+fn test_diff_values_uses_config() {
+    let old = deployment(2, "web:1");
+    let new = deployment(3, "web:2");
+    let config = DiffConfig::default()
+        .rule("replicas", RuleSet::new().numeric_tolerance(1.0))
+        .rule("containers", RuleSet::new().array_key("name"));
+
+    let diff = diff_values(&old, &new, &config).unwrap();
+
+    assert_eq!(diff.stats.modified, 1);
+    assert_eq!(diff.changes[0].path.to_string(), "containers[0].image");
+
+    let identical = diff_values(&old, &deployment(2, "web:1"), &config).unwrap();
+    assert!(identical.is_empty());
+}
+
+#[test]
+fn test_diff_values_reports_serialization_errors() {
+    let error = diff_values(&vec![1.0], &vec![f64::NAN], &DiffConfig::default()).unwrap_err();
+    assert_eq!(error.to_string(), "Cannot represent NaN as a node");
+}
@@ -182,3 +182,56 @@ fn test_number_preview_formatting() {
     assert_eq!(Node::Number(42.5).preview(100), "42.5");
     assert_eq!(Node::Number(3.25).preview(100), "3.25");
 }
+
+#[test]
+fn test_node_serde_round_trip() {
+    let node = sdiff_rs::parse_json(
+        r#"{"name": "web", "replicas": 3, "ratio": 0.5, "tags": ["a", null, true], "meta": {}}"#,
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&node).unwrap();
+    assert_eq!(
+        json,
+        r#"{"meta":{},"name":"web","ratio":0.5,"replicas":3,"tags":["a",null,true]}"#
+    );
+    let back: Node = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, node);
+
+    let yaml = serde_yaml::to_string(&node).unwrap();
+    let back: Node = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(back, node);
+}
+
+#[test]
+fn test_node_deserializes_integers() {
+    let node: Node = serde_json::from_str(r#"[-1, 9007199254740992, -9007199254740992]"#).unwrap();
+    assert_eq!(
+        node,
+        Node::Array(vec![
+            Node::Number(-1.0),
+            Node::Number(9007199254740992.0),
+            Node::Number(-9007199254740992.0)
+        ])
+    );
+
+    for json in [
+        "18446744073709551615",
+        "9007199254740993",
+        "-9223372036854775808",
+    ] {
+        let err = serde_json::from_str::<Node>(json).unwrap_err();
+        assert!(err.to_string().contains("exactly"), "{}", err);
+    }
+}
+
+#[test]
+fn test_node_serializes_whole_numbers_as_integers() {
+    let node = Node::Array(vec![
+        Node::Number(3.0),
+        Node::Number(-2.0),
+        Node::Number(2.5),
+        Node::Number(1e300),
+    ]);
+    assert_eq!(serde_json::to_string(&node).unwrap(), "[3,-2,2.5,1e+300]");
+}
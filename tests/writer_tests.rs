@@ -0,0 +1,53 @@
+use sdiff_rs::writer::{write_json, write_toml, write_yaml};
+use sdiff_rs::{parse_json, parse_toml, parse_yaml, Node, OutputError};
+
+fn sample() -> Node {
+    parse_json(
+        r#"{"name": "app", "replicas": 3, "ratio": 0.5, "enabled": true, "tags": ["a", "b"], "db": {"port": 5432}}"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_write_json_round_trip() {
+    let node = sample();
+    let output = write_json(&node).unwrap();
+    assert!(output.contains("\"replicas\": 3,"));
+    assert!(parse_json(&output).unwrap().semantic_equals(&node));
+}
+
+#[test]
+fn test_write_yaml_round_trip() {
+    let node = sample();
+    let output = write_yaml(&node).unwrap();
+    assert!(output.contains("replicas: 3\n"));
+    assert!(parse_yaml(&output).unwrap().semantic_equals(&node));
+}
+
+#[test]
+fn test_write_toml_round_trip() {
+    let node = sample();
+    let output = write_toml(&node).unwrap();
+    assert!(output.contains("replicas = 3\n"));
+    assert!(parse_toml(&output).unwrap().semantic_equals(&node));
+}
+
+#[test]
+fn test_write_json_sorts_keys() {
+    let node = parse_json(r#"{"b": 1, "a": 2, "c": 3}"#).unwrap();
+    let output = write_json(&node).unwrap();
+    let a = output.find("\"a\"").unwrap();
+    let b = output.find("\"b\"").unwrap();
+    let c = output.find("\"c\"").unwrap();
+    assert!(a < b && b < c);
+}
+
+#[test]
+fn test_write_toml_rejects_unsupported_values() {
+    let err = write_toml(&Node::Array(vec![])).unwrap_err();
+    assert!(matches!(err, OutputError::UnsupportedValue { .. }));
+
+    let node = parse_json(r#"{"key": null}"#).unwrap();
+    let err = write_toml(&node).unwrap_err();
+    assert!(err.to_string().contains("null"));
+}